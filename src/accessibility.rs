@@ -0,0 +1,66 @@
+//! The report `build --accessible` prints: data that leaves assistive
+//! technology guessing, such as a document without a language.
+
+use crate::{Author, Finding};
+
+impl Author {
+    /// Findings in the same form as [`Author::lint`]'s, for data the
+    /// accessible output can't fix on its own.
+    pub fn accessibility(&self) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        match &self.language {
+            None => findings.push(Finding {
+                rule: "missing-language",
+                field: "language".to_string(),
+                message:
+                    "isn't set; add a tag such as `en-US` so screen readers pick the right voice"
+                        .to_string(),
+            }),
+            Some(language) if !is_language_tag(language) => findings.push(Finding {
+                rule: "invalid-language",
+                field: "language".to_string(),
+                message: format!("`{}` isn't a BCP 47 tag such as `en` or `pt-BR`", language),
+            }),
+            Some(_) => {}
+        }
+        if self
+            .picture
+            .as_ref()
+            .is_some_and(|picture| picture.alt.is_none())
+        {
+            findings.push(Finding {
+                rule: "missing-alt-text",
+                field: "picture.alt".to_string(),
+                message: format!(
+                    "isn't set, so the photo is described only as `Photo of {}`",
+                    self.name
+                ),
+            });
+        }
+        let mut sites: Vec<_> = self.social.iter().collect();
+        sites.sort_by_key(|(site, _)| *site);
+        for (site, profile) in sites {
+            if profile.username.trim().is_empty() && !profile.url.trim().is_empty() {
+                findings.push(Finding {
+                    rule: "empty-link-text",
+                    field: format!("social.{}.username", site),
+                    message: "is empty, so the link to the profile has no text to read out"
+                        .to_string(),
+                });
+            }
+        }
+        findings
+    }
+}
+
+/// Whether `tag` is shaped like a BCP 47 language tag: a two or three letter
+/// language, then subtags of one to eight letters or digits.
+pub(crate) fn is_language_tag(tag: &str) -> bool {
+    let mut subtags = tag.split('-');
+    let language = subtags.next().unwrap_or_default();
+    (2..=3).contains(&language.len())
+        && language.chars().all(|c| c.is_ascii_alphabetic())
+        && subtags.all(|subtag| {
+            (1..=8).contains(&subtag.len()) && subtag.chars().all(|c| c.is_ascii_alphanumeric())
+        })
+}
@@ -0,0 +1,213 @@
+//! Blind-review copies: the author, their contact details and their employers
+//! replaced with placeholders before the data reaches the templates.
+
+use std::collections::HashMap;
+
+use crate::{Author, ContactDetail, Location};
+
+/// Stands in for the author's name, including in prose that mentions it.
+pub const CANDIDATE: &str = "Candidate";
+
+impl Author {
+    /// Replaces identifying data with placeholders: the name becomes
+    /// [`CANDIDATE`], each employer `Company A`, `Company B` and so on, and
+    /// contact details, profiles, the picture, logos and reference names are dropped.
+    /// Mentions of the replaced names in highlights and other prose are
+    /// replaced as well, and so are the author and the other people in
+    /// publication author and patent inventor lists.
+    pub fn anonymize(&mut self) {
+        let mut replacements = vec![(self.name.clone(), CANDIDATE.to_string())];
+        let mut companies: HashMap<String, String> = HashMap::new();
+        for experience in &mut self.experiences {
+            let next = companies.len();
+            let placeholder = companies
+                .entry(experience.company.name.clone())
+                .or_insert_with(|| format!("Company {}", letters(next)))
+                .clone();
+            replacements.push((experience.company.name.clone(), placeholder.clone()));
+            for other in [
+                &experience.company.display_name,
+                &experience.company.legal_name,
+            ]
+            .into_iter()
+            .flatten()
+            {
+                replacements.push((other.clone(), placeholder.clone()));
+            }
+            experience.company.name = placeholder;
+            experience.company.display_name = None;
+            experience.company.legal_name = None;
+            experience.company.logo = None;
+            experience.website = String::new();
+            for engagement in &mut experience.engagements {
+                let next = companies.len();
+                let placeholder = companies
+                    .entry(engagement.client.clone())
+                    .or_insert_with(|| format!("Company {}", letters(next)))
+                    .clone();
+                replacements.push((engagement.client.clone(), placeholder.clone()));
+                engagement.client = placeholder;
+            }
+        }
+        // Longest first, so `Acme Labs` isn't left as `Company A Labs`.
+        replacements.retain(|(from, _)| !from.trim().is_empty());
+        replacements.sort_by(|a, b| b.0.len().cmp(&a.0.len()).then_with(|| a.0.cmp(&b.0)));
+        replacements.dedup();
+        let scrub = |text: &mut String| {
+            for (from, to) in &replacements {
+                if text.contains(from.as_str()) {
+                    *text = text.replace(from.as_str(), to);
+                }
+            }
+        };
+
+        let mut people = People::new(&self.name);
+        for publication in &mut self.publications {
+            for author in &mut publication.authors {
+                *author = people.placeholder(author);
+            }
+        }
+        for patent in &mut self.patents {
+            for inventor in &mut patent.inventors {
+                *inventor = people.placeholder(inventor);
+            }
+        }
+
+        self.name = CANDIDATE.to_string();
+        self.email = vec![ContactDetail {
+            value: "candidate@example.com".to_string(),
+            label: None,
+        }];
+        self.phone.clear();
+        self.website = String::new();
+        self.picture = None;
+        self.name_pronunciation = None;
+        self.social.clear();
+        self.location = Location {
+            address: None,
+            postal_code: None,
+            ..std::mem::take(&mut self.location)
+        };
+        scrub(&mut self.description);
+        for text in self
+            .summary
+            .iter_mut()
+            .chain(self.objective.iter_mut())
+            .chain(self.cover_letter.iter_mut())
+        {
+            scrub(text);
+        }
+        for experience in &mut self.experiences {
+            for highlight in &mut experience.highlights {
+                scrub(&mut highlight.text);
+            }
+            for engagement in &mut experience.engagements {
+                engagement.outcomes.iter_mut().for_each(scrub);
+            }
+        }
+        for project in &mut self.projects {
+            scrub(&mut project.description);
+        }
+        for (index, reference) in self.references.iter_mut().enumerate() {
+            reference.name = format!("Reference {}", index + 1);
+            scrub(&mut reference.relationship);
+            reference.contact = String::new();
+        }
+    }
+}
+
+/// Placeholders for the people listed by publications and patents, the author as
+/// [`CANDIDATE`] and everyone else `Collaborator A`, `Collaborator B` and so
+/// on, the same in every entry.
+struct People {
+    author: String,
+    others: HashMap<String, String>,
+}
+
+impl People {
+    fn new(author: &str) -> People {
+        People {
+            author: author.to_string(),
+            others: HashMap::new(),
+        }
+    }
+
+    fn placeholder(&mut self, name: &str) -> String {
+        if same_person(&self.author, name) {
+            return CANDIDATE.to_string();
+        }
+        let next = self.others.len();
+        self.others
+            .entry(name_parts(name).join(" "))
+            .or_insert_with(|| format!("Collaborator {}", letters(next)))
+            .clone()
+    }
+}
+
+/// Whether `cited` names the person called `name` the way citations do, e.g.
+/// `J. Doe`, `Doe, Jane` or `**Jane Doe**` for Jane Doe: the same surname
+/// and, if given, the same first initial.
+fn same_person(name: &str, cited: &str) -> bool {
+    let (name, cited) = (name_parts(name), name_parts(cited));
+    let (Some(surname), Some(cited_surname)) = (name.last(), cited.last()) else {
+        return false;
+    };
+    let initial = |parts: &[String]| parts.first().and_then(|first| first.chars().next());
+    surname == cited_surname && (cited.len() == 1 || initial(&name) == initial(&cited))
+}
+
+/// Lowercase given names then surname, with `Surname, Given` turned around
+/// and Markdown emphasis and initials' periods dropped.
+fn name_parts(name: &str) -> Vec<String> {
+    let name = name.replace(['*', '_', '.'], " ");
+    let ordered = match name.split_once(',') {
+        Some((surname, given)) => format!("{} {}", given, surname),
+        None => name,
+    };
+    ordered.split_whitespace().map(str::to_lowercase).collect()
+}
+
+/// `A`, `B`, ... `Z`, `AA`, `AB`, ... for the `index`th placeholder.
+fn letters(mut index: usize) -> String {
+    let mut out = Vec::new();
+    loop {
+        out.push(b'A' + (index % 26) as u8);
+        if index < 26 {
+            break;
+        }
+        index = index / 26 - 1;
+    }
+    out.reverse();
+    String::from_utf8(out).expect("ASCII letters")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cited_forms_of_the_author_match() {
+        for cited in [
+            "Jane Doe",
+            "J. Doe",
+            "Doe, Jane",
+            "Doe, J.",
+            "**Jane Doe**",
+            "Doe",
+        ] {
+            assert!(same_person("Jane Doe", cited), "{}", cited);
+        }
+        for cited in ["John Smith", "A. Doe", "Jane Doering"] {
+            assert!(!same_person("Jane Doe", cited), "{}", cited);
+        }
+    }
+
+    #[test]
+    fn collaborators_keep_one_placeholder() {
+        let mut people = People::new("Jane Doe");
+        assert_eq!(people.placeholder("J. Doe"), CANDIDATE);
+        assert_eq!(people.placeholder("A. Smith"), "Collaborator A");
+        assert_eq!(people.placeholder("B. Jones"), "Collaborator B");
+        assert_eq!(people.placeholder("Smith, A."), "Collaborator A");
+    }
+}
@@ -0,0 +1,113 @@
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use chrono::Local;
+use serde::Serialize;
+use toml::value::Datetime;
+
+use rsume::{Application, Error};
+
+/// Kept in the output directory, one `[[applications]]` table per tailored build.
+pub const LOG_FILE: &str = "applications.toml";
+
+#[derive(Serialize)]
+struct Log<'a> {
+    applications: [Entry<'a>; 1],
+}
+
+#[derive(Serialize)]
+struct Entry<'a> {
+    date: Datetime,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    company: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    role: Option<&'a str>,
+    /// The `--variant` the build used
+    #[serde(skip_serializing_if = "Option::is_none")]
+    variant: Option<&'a str>,
+    /// The template set and file the build used
+    template: String,
+    output: &'a Path,
+}
+
+/// Appends a record of sending `output` for `application` to the log in `dir`.
+pub fn record(
+    dir: &Path,
+    application: &Application,
+    variant: Option<&str>,
+    template: String,
+    output: &Path,
+) -> rsume::Result<PathBuf> {
+    let log = Log {
+        applications: [Entry {
+            date: today(),
+            company: application.company.as_deref(),
+            role: application.role.as_deref(),
+            variant,
+            template,
+            output,
+        }],
+    };
+    let entry = toml::to_string(&log).expect("log entries always serialize");
+    let path = dir.join(LOG_FILE);
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| writeln!(file, "{}", entry))
+        .map_err(|source| Error::Write {
+            path: path.clone(),
+            source,
+        })?;
+    Ok(path)
+}
+
+fn today() -> Datetime {
+    Local::now()
+        .date_naive()
+        .to_string()
+        .parse()
+        .expect("dates format as YYYY-MM-DD")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_record_the_variant() {
+        let dir = tempfile::tempdir().unwrap();
+        let application = Application {
+            company: Some("Acme".to_string()),
+            role: Some("SRE".to_string()),
+            ..Application::default()
+        };
+        let output = dir.path().join("resume-acme-sre.pdf");
+        record(
+            dir.path(),
+            &application,
+            Some("backend"),
+            "builtin:sidebar/resume.tex".to_string(),
+            &output,
+        )
+        .unwrap();
+        record(
+            dir.path(),
+            &application,
+            None,
+            "builtin:sidebar/resume.tex".to_string(),
+            &output,
+        )
+        .unwrap();
+
+        let log: toml::Value =
+            toml::from_str(&std::fs::read_to_string(dir.path().join(LOG_FILE)).unwrap()).unwrap();
+        let entries = log["applications"].as_array().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0]["company"].as_str(), Some("Acme"));
+        assert_eq!(entries[0]["variant"].as_str(), Some("backend"));
+        assert_eq!(entries[0]["output"].as_str(), output.to_str());
+        assert!(entries[1].get("variant").is_none());
+    }
+}
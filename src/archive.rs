@@ -0,0 +1,94 @@
+//! Older roles folded into one compact "Earlier experience" line, so a long
+//! career doesn't need its early entries deleted to fit.
+
+use chrono::{Datelike, Local};
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::Author;
+
+/// An archived experience as templates list it, e.g.
+/// `Initech, Engineer, 2016–2019`.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct EarlierExperience {
+    /// `display_name`, or the name
+    pub company: String,
+    pub position: String,
+    pub start_year: i32,
+    /// Absent while ongoing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_year: Option<i32>,
+}
+
+impl Author {
+    /// Moves the experiences marked `archive`, and with `since` those that
+    /// ended before that year, out of `experiences` into
+    /// `earlier_experiences`, keeping their order.
+    pub fn archive(&mut self, since: Option<i32>) {
+        let (earlier, kept) = self
+            .experiences
+            .drain(..)
+            .partition::<Vec<_>, _>(|experience| {
+                experience.archive
+                    || matches!(
+                        (since, experience.end_date),
+                        (Some(since), Some(end)) if end.year() < since
+                    )
+            });
+        self.experiences = kept;
+        self.earlier_experiences
+            .extend(earlier.into_iter().map(|experience| {
+                EarlierExperience {
+                    company: experience
+                        .company
+                        .display_name
+                        .unwrap_or(experience.company.name),
+                    position: experience.position,
+                    start_year: experience.start_date.year(),
+                    end_year: experience.end_date.map(|end| end.year()),
+                }
+            }));
+    }
+}
+
+/// The archived experiences taken together, exposed as
+/// `earlier_experience_summary` for templates with room for only a mention,
+/// e.g. `Five earlier roles at Initech and Globex, 2008–2015`.
+#[derive(Debug, Clone, Serialize)]
+pub struct EarlierExperienceSummary {
+    pub count: usize,
+    /// Each employer once, in the order they're listed
+    pub employers: Vec<String>,
+    pub start_year: i32,
+    /// Absent if one of them is ongoing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_year: Option<i32>,
+    /// Years from the first start to the last end, or to now
+    pub years: i32,
+}
+
+impl EarlierExperienceSummary {
+    /// Nothing without archived experiences.
+    pub fn new(earlier: &[EarlierExperience]) -> Option<EarlierExperienceSummary> {
+        let start_year = earlier.iter().map(|e| e.start_year).min()?;
+        let end_year = earlier
+            .iter()
+            .map(|e| e.end_year)
+            .collect::<Option<Vec<_>>>()
+            .and_then(|ends| ends.into_iter().max());
+        let mut employers: Vec<String> = Vec::new();
+        for experience in earlier {
+            if !employers.contains(&experience.company) {
+                employers.push(experience.company.clone());
+            }
+        }
+        let last = end_year.unwrap_or_else(|| Local::now().year());
+        Some(EarlierExperienceSummary {
+            count: earlier.len(),
+            employers,
+            start_year,
+            end_year,
+            years: (last - start_year).max(0),
+        })
+    }
+}
@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::{Error, FontFeatures, Ligatures, Numerals, Result, Resume, Theme};
+
+/// The largest picture or logo in bytes. Anything bigger is a camera original
+/// that bloats the PDF and slows every compile; scale it down first.
+pub const MAX_PICTURE_SIZE: u64 = 8 * 1024 * 1024;
+
+/// A file the rendered LaTeX refers to that lives outside the template
+/// directories, copied into the compile's filesystem root as `name`.
+#[derive(Debug, Clone)]
+pub struct Asset {
+    pub source: PathBuf,
+    pub name: String,
+}
+
+impl Asset {
+    /// Copies the file to `dir/<name>`, for outputs such as HTML that reference
+    /// assets relative to themselves.
+    pub fn copy_into(&self, dir: &Path) -> Result<()> {
+        let target = dir.join(&self.name);
+        let write_error = |source| Error::Write {
+            path: target.clone(),
+            source,
+        };
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(write_error)?;
+        }
+        fs::copy(&self.source, &target).map_err(write_error)?;
+        Ok(())
+    }
+}
+
+impl Resume {
+    /// Resolves the data's file references (the picture, company logos, font
+    /// files) against `base`, the directory of the data file, rewrites them to
+    /// the names they are staged under and returns the files to stage.
+    ///
+    /// Pictures and logos over [`MAX_PICTURE_SIZE`] are an error. Missing files
+    /// are left for the compile to report.
+    pub fn stage_assets(&mut self, base: &Path) -> Result<Vec<Asset>> {
+        let mut stager = Stager::default();
+        if let Some(picture) = &mut self.author.picture {
+            let source = base.join(&picture.path);
+            check_picture_size(&source)?;
+            picture.path = stager.stage(source, "pictures");
+        }
+        for experience in &mut self.author.experiences {
+            if let Some(logo) = &mut experience.company.logo {
+                let source = base.join(&logo.path);
+                check_picture_size(&source)?;
+                logo.path = stager.stage(source, "logos");
+            }
+        }
+        for file in &mut self.author.theme.font_files {
+            *file = stager.stage(base.join(&*file), "fonts");
+        }
+        Ok(stager.assets)
+    }
+}
+
+fn check_picture_size(path: &Path) -> Result<()> {
+    match fs::metadata(path) {
+        Ok(metadata) if metadata.len() > MAX_PICTURE_SIZE => Err(Error::PictureTooLarge {
+            path: path.to_path_buf(),
+            size: metadata.len(),
+        }),
+        _ => Ok(()),
+    }
+}
+
+#[derive(Default)]
+struct Stager {
+    staged: HashMap<PathBuf, String>,
+    assets: Vec<Asset>,
+}
+
+impl Stager {
+    fn stage(&mut self, source: PathBuf, dir: &str) -> String {
+        let assets = &mut self.assets;
+        self.staged
+            .entry(source.clone())
+            .or_insert_with(|| {
+                let file_name = source.file_name().unwrap_or_default().to_string_lossy();
+                let mut name = format!("{}/{}", dir, file_name);
+                // Two different files with the same name would overwrite each other.
+                if assets.iter().any(|asset| asset.name == name) {
+                    name = format!("{}/{}-{}", dir, assets.len(), file_name);
+                }
+                assets.push(Asset {
+                    source: source.clone(),
+                    name: name.clone(),
+                });
+                name
+            })
+            .clone()
+    }
+}
+
+/// The `font` template variable: either a family name the engine looks up, or
+/// staged font files sorted into the faces `fontspec` wants, e.g.
+/// `\setmainfont{ {{ font.upright }} }[Path={{ font.path }}, BoldFont=...]`.
+#[derive(Debug, Default, Serialize)]
+pub(crate) struct Font {
+    family: Option<String>,
+    path: Option<String>,
+    upright: Option<String>,
+    bold: Option<String>,
+    italic: Option<String>,
+    bold_italic: Option<String>,
+    /// `fontspec` options for `theme.font_features`, e.g. `Numbers=OldStyle`
+    features: Option<String>,
+}
+
+impl Font {
+    pub(crate) fn new(theme: &Theme) -> Font {
+        let mut font = Font {
+            family: theme.font.clone(),
+            features: features(&theme.font_features),
+            ..Font::default()
+        };
+        for file in &theme.font_files {
+            let (dir, file_name) = file.rsplit_once('/').unwrap_or(("", file));
+            font.path.get_or_insert_with(|| format!("{}/", dir));
+            let lower = file_name.to_lowercase();
+            let bold = lower.contains("bold");
+            let italic = lower.contains("italic") || lower.contains("oblique");
+            let face = match (bold, italic) {
+                (true, true) => &mut font.bold_italic,
+                (true, false) => &mut font.bold,
+                (false, true) => &mut font.italic,
+                (false, false) => &mut font.upright,
+            };
+            face.get_or_insert_with(|| file_name.to_string());
+        }
+        font
+    }
+}
+
+fn features(features: &FontFeatures) -> Option<String> {
+    let mut options = Vec::new();
+    if let Some(ligatures) = features.ligatures {
+        options.push(
+            match ligatures {
+                Ligatures::None => "Ligatures=NoCommon",
+                Ligatures::Common => "Ligatures=Common",
+                Ligatures::Rare => "Ligatures={Common,Rare}",
+                Ligatures::Historic => "Ligatures={Common,Rare,Historic}",
+            }
+            .to_string(),
+        );
+    }
+    if let Some(numerals) = features.numerals {
+        options.push(match numerals {
+            Numerals::Oldstyle => "Numbers=OldStyle".to_string(),
+            Numerals::Lining => "Numbers=Lining".to_string(),
+        });
+    }
+    if let Some(space) = features.small_caps_letterspace {
+        // fontspec's LetterSpace is in hundredths of an em.
+        options.push(format!(
+            "SmallCapsFeatures={{LetterSpace={}}}",
+            space as f64 / 10.0
+        ));
+    }
+    (!options.is_empty()).then(|| options.join(", "))
+}
@@ -0,0 +1,60 @@
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+
+use tectonic::status::StatusBackend;
+use tokio::sync::Semaphore;
+
+use crate::{compile_pdf, CompileOptions, Compiled, Error, Result};
+
+/// Runs [`compile_pdf`] on tokio's blocking pool so bundle downloads and engine
+/// runs don't stall the executor.
+///
+/// Tectonic serializes engine runs internally anyway; the semaphore bounds how many
+/// sessions (and their rendered inputs and bundle handles) are alive at once, so a
+/// burst of requests queues here instead of piling up blocking threads.
+#[derive(Clone)]
+pub struct AsyncCompiler {
+    sessions: Arc<Semaphore>,
+}
+
+impl AsyncCompiler {
+    /// Non-zero, since no session could ever start otherwise.
+    pub fn new(max_sessions: NonZeroUsize) -> AsyncCompiler {
+        AsyncCompiler {
+            sessions: Arc::new(Semaphore::new(max_sessions.get())),
+        }
+    }
+
+    pub async fn compile_pdf<S>(
+        &self,
+        tex_filename: String,
+        content: String,
+        options: CompileOptions,
+        mut status: S,
+    ) -> Result<Compiled>
+    where
+        S: StatusBackend + Send + 'static,
+    {
+        let permit = self
+            .sessions
+            .clone()
+            .acquire_owned()
+            .await
+            .map_err(|_| compile_error("the compile queue was closed".to_string()))?;
+        // Held by the compile itself, which runs on even if the caller drops
+        // this future.
+        tokio::task::spawn_blocking(move || {
+            let _permit = permit;
+            compile_pdf(&tex_filename, &content, &options, &mut status)
+        })
+        .await
+        .map_err(|e| compile_error(format!("the compile task failed: {}", e)))?
+    }
+}
+
+fn compile_error(message: String) -> Error {
+    Error::Compile {
+        message,
+        log_error: None,
+    }
+}
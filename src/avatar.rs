@@ -0,0 +1,105 @@
+//! Pictures given as an email address, fetched from Gravatar and cached, so a
+//! photo doesn't have to be committed next to the data file.
+
+use std::fs::{self, File};
+use std::io::{self, Read};
+
+use md5::{Digest, Md5};
+
+use crate::{cache_dir, Error, Result, Resume, MAX_PICTURE_SIZE};
+
+/// Width and height in pixels when the picture doesn't ask for a size.
+const DEFAULT_SIZE: u32 = 400;
+
+impl Resume {
+    /// If `picture` is `gravatar:<email>`, optionally followed by `?size=<px>`,
+    /// or just an email address, points it at a local copy of that Gravatar,
+    /// downloading it into the cache directory the first time, see
+    /// [`cache_dir`].
+    pub fn fetch_avatar(&mut self) -> Result<()> {
+        let Some(picture) = &mut self.author.picture else {
+            return Ok(());
+        };
+        let Some((email, size)) = gravatar(&picture.path) else {
+            return Ok(());
+        };
+        let avatar_error = |message: String| Error::Avatar {
+            email: email.clone(),
+            message,
+        };
+        let hash = format!("{:x}", Md5::digest(email.as_bytes()));
+        let dir = cache_dir()
+            .or_else(|| dirs::cache_dir().map(|dir| dir.join("rsume")))
+            .ok_or_else(|| avatar_error("there's no cache directory to keep it in".to_string()))?
+            .join("gravatar");
+        let stem = format!("{}-{}", hash, size);
+        let cached = ["jpg", "png"]
+            .iter()
+            .map(|extension| dir.join(&stem).with_extension(extension))
+            .find(|path| path.exists());
+        let path = match cached {
+            Some(path) => path,
+            None => {
+                let url = format!("https://www.gravatar.com/avatar/{}?s={}&d=404", hash, size);
+                let response =
+                    reqwest::blocking::get(url).map_err(|e| avatar_error(e.to_string()))?;
+                if response.status() == reqwest::StatusCode::NOT_FOUND {
+                    return Err(avatar_error("Gravatar has no image for it".to_string()));
+                }
+                // Named for its type, which `\includegraphics` goes by.
+                let png = response
+                    .headers()
+                    .get(reqwest::header::CONTENT_TYPE)
+                    .is_some_and(|kind| kind.as_bytes() == b"image/png");
+                let mut response = response
+                    .error_for_status()
+                    .map_err(|e| avatar_error(e.to_string()))?;
+                let path = dir
+                    .join(&stem)
+                    .with_extension(if png { "png" } else { "jpg" });
+                // Streamed to a partial file and renamed once complete, so an
+                // interrupted download isn't mistaken for a cached one.
+                let partial = path.with_extension("part");
+                let write_error = |source| Error::Write {
+                    path: partial.clone(),
+                    source,
+                };
+                fs::create_dir_all(&dir).map_err(write_error)?;
+                let mut file = File::create(&partial).map_err(write_error)?;
+                let size = io::copy(&mut (&mut response).take(MAX_PICTURE_SIZE + 1), &mut file)
+                    .map_err(|e| avatar_error(e.to_string()))?;
+                drop(file);
+                if size > MAX_PICTURE_SIZE {
+                    let _ = fs::remove_file(&partial);
+                    return Err(Error::PictureTooLarge {
+                        path: path.clone(),
+                        size: response.content_length().unwrap_or(size).max(size),
+                    });
+                }
+                fs::rename(&partial, &path).map_err(|source| Error::Write {
+                    path: path.clone(),
+                    source,
+                })?;
+                path
+            }
+        };
+        picture.path = path.to_string_lossy().into_owned();
+        Ok(())
+    }
+}
+
+/// The normalized email and size a picture path asks for, if it's a Gravatar.
+fn gravatar(path: &str) -> Option<(String, u32)> {
+    let (address, query) = match path.strip_prefix("gravatar:") {
+        Some(rest) => rest.split_once('?').unwrap_or((rest, "")),
+        None if path.contains('@') && !path.contains(['/', '\\']) => (path, ""),
+        None => return None,
+    };
+    let size = query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == "size" || *key == "s")
+        .and_then(|(_, size)| size.parse().ok())
+        .unwrap_or(DEFAULT_SIZE);
+    Some((address.trim().to_lowercase(), size))
+}
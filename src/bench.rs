@@ -0,0 +1,44 @@
+//! `rsume bench`: how long each pipeline stage takes over repeated runs, so a
+//! template or pipeline change that slows builds down shows up in numbers.
+
+use std::time::Duration;
+
+use serde::Serialize;
+
+/// One stage's run times, in milliseconds.
+#[derive(Debug, Serialize)]
+pub struct StageTiming {
+    pub stage: &'static str,
+    pub runs: usize,
+    pub p50: f64,
+    pub p90: f64,
+    pub p99: f64,
+    pub max: f64,
+}
+
+impl StageTiming {
+    pub fn new(stage: &'static str, mut samples: Vec<Duration>) -> StageTiming {
+        samples.sort_unstable();
+        StageTiming {
+            stage,
+            runs: samples.len(),
+            p50: percentile(&samples, 50.0),
+            p90: percentile(&samples, 90.0),
+            p99: percentile(&samples, 99.0),
+            max: samples.last().map_or(0.0, milliseconds),
+        }
+    }
+}
+
+/// The nearest-rank percentile of `sorted`.
+fn percentile(sorted: &[Duration], percent: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = (percent / 100.0 * sorted.len() as f64).ceil() as usize;
+    milliseconds(&sorted[rank.clamp(1, sorted.len()) - 1])
+}
+
+fn milliseconds(duration: &Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
@@ -0,0 +1,120 @@
+use std::path::PathBuf;
+
+use tera::Tera;
+
+use crate::{Error, Manifest, Result, Templates};
+
+/// A template set compiled into the crate, selected with `builtin:<name>`.
+pub struct Builtin {
+    pub name: &'static str,
+    pub description: &'static str,
+    manifest: &'static str,
+    templates: &'static [(&'static str, &'static str)],
+}
+
+pub const BUILTINS: &[Builtin] = &[
+    Builtin {
+        name: "sidebar",
+        description: "Two columns with a colored sidebar for contact details, skills and languages, and a matching cover-letter.tex",
+        manifest: include_str!("../templates/sidebar/manifest.toml"),
+        templates: &[
+            (
+                "resume.tex",
+                include_str!("../templates/sidebar/resume.tex"),
+            ),
+            (
+                "cover-letter.tex",
+                include_str!("../templates/sidebar/cover-letter.tex"),
+            ),
+        ],
+    },
+    Builtin {
+        name: "academic",
+        description: "Long-form CV with numbered publications and an optional table of contents",
+        manifest: include_str!("../templates/academic/manifest.toml"),
+        templates: &[("cv.tex", include_str!("../templates/academic/cv.tex"))],
+    },
+    Builtin {
+        name: "consultant",
+        description: "A consultant profile organized around client engagements, with employers reduced to a one-line history",
+        manifest: include_str!("../templates/consultant/manifest.toml"),
+        templates: &[(
+            "profile.tex",
+            include_str!("../templates/consultant/profile.tex"),
+        )],
+    },
+    Builtin {
+        name: "html",
+        description: "A personal site with print, dark mode and h-resume markup; builds HTML instead of a PDF",
+        manifest: include_str!("../templates/html/manifest.toml"),
+        templates: &[("index.html", include_str!("../templates/html/index.html"))],
+    },
+    Builtin {
+        name: "text",
+        description: "Plain text, for pasting into application forms; `rsume show` prints it in the terminal",
+        manifest: include_str!("../templates/text/manifest.toml"),
+        templates: &[("resume.txt", include_str!("../templates/text/resume.txt"))],
+    },
+];
+
+/// A shared template every [`Templates`] can extend, include or import, unless
+/// the template directory's own `partials/` has one of the same name.
+pub struct Partial {
+    /// E.g. `partials/base.tex`
+    pub name: &'static str,
+    pub description: &'static str,
+    source: &'static str,
+}
+
+pub const PARTIALS: &[Partial] = &[
+    Partial {
+        name: "partials/base.tex",
+        description: "A one-column document to `extends`, with `preamble`, `body`, `font_size` and `margin` blocks",
+        source: include_str!("../templates/partials/base.tex"),
+    },
+    Partial {
+        name: "partials/header.tex",
+        description: "Name, headline and contact line to `include` at the top",
+        source: include_str!("../templates/partials/header.tex"),
+    },
+    Partial {
+        name: "partials/contact.tex",
+        description: "Email, phone, website, location and profiles on one line",
+        source: include_str!("../templates/partials/contact.tex"),
+    },
+    Partial {
+        name: "partials/macros.tex",
+        description: "Macros to `import`: `section(title)`, `entry(title, subtitle, start, end)`, `dates(start, end)` and `bullets(items)`",
+        source: include_str!("../templates/partials/macros.tex"),
+    },
+];
+
+/// A Tera holding just the [`PARTIALS`], to add templates to.
+pub(crate) fn partials() -> Tera {
+    let mut tera = Tera::default();
+    tera.add_raw_templates(
+        PARTIALS
+            .iter()
+            .map(|partial| (partial.name, partial.source)),
+    )
+    .expect("bundled partials parse");
+    tera
+}
+
+impl Templates {
+    /// Loads one of the [`BUILTINS`] by name.
+    pub fn builtin(name: &str) -> Result<Templates> {
+        let builtin = BUILTINS
+            .iter()
+            .find(|builtin| builtin.name == name)
+            .ok_or_else(|| Error::UnknownBuiltin(name.to_string()))?;
+        let mut tera = partials();
+        tera.add_raw_templates(builtin.templates.iter().copied())
+            .map_err(Error::Templates)?;
+        let manifest = Manifest::from_toml(
+            builtin.manifest,
+            PathBuf::from(format!("builtin:{}/manifest.toml", name)),
+        )?;
+        Ok(Templates::with_tera(tera).with_manifest(manifest))
+    }
+}
@@ -0,0 +1,185 @@
+//! The TeX files a build needs, packaged for machines without network
+//! access: `rsume bundle export` records what a compile reads from the
+//! bundle, and `rsume bundle import` makes the copy the bundle every build
+//! uses from then on.
+
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use tectonic::digest::{DigestData, DIGEST_NAME};
+use tectonic::io::{InputHandle, IoProvider, OpenResult};
+use tectonic::status::StatusBackend;
+use tectonic_bundles::Bundle;
+use tempfile::TempDir;
+
+use crate::compile::{catch_engine_errors, latex_to_pdf, open_bundle, Interrupt};
+use crate::{cache_dir, CompileOptions, Error, Result};
+
+/// Where an imported bundle is kept: `bundle/` in [`cache_dir`], or in the
+/// user cache directory's `rsume/`.
+pub fn vendored_bundle_dir() -> Option<PathBuf> {
+    cache_dir()
+        .or_else(|| dirs::cache_dir().map(|dir| dir.join("rsume")))
+        .map(|dir| dir.join("bundle"))
+}
+
+/// The imported bundle, if there is one.
+pub(crate) fn vendored_bundle() -> Option<PathBuf> {
+    vendored_bundle_dir().filter(|dir| dir.join(DIGEST_NAME).is_file())
+}
+
+/// Compiles `content` once and copies every file the engine read from the
+/// bundle into `dir`, returning their names. The LaTeX format is built afresh
+/// for it, so the files it's built from are included too.
+pub fn export_bundle(
+    tex_filename: &str,
+    content: &str,
+    options: &CompileOptions,
+    dir: &Path,
+    status: &mut dyn StatusBackend,
+) -> Result<Vec<String>> {
+    let scratch_error = |e: io::Error| Error::Bundle {
+        path: dir.to_path_buf(),
+        message: format!("couldn't create a scratch directory: {}", e),
+    };
+    let formats = TempDir::new().map_err(scratch_error)?;
+    let output = TempDir::new().map_err(scratch_error)?;
+    let options = CompileOptions {
+        output_dir: output.path().to_path_buf(),
+        ..options.clone()
+    };
+    let (bundle, _) = open_bundle(status).map_err(Error::from)?;
+    let recording = Recording {
+        bundle: Rc::new(RefCell::new(bundle)),
+        names: Rc::default(),
+    };
+    catch_engine_errors(&Interrupt::new(&options), |log_error, compiled| {
+        latex_to_pdf(
+            tex_filename,
+            content,
+            &options,
+            Box::new(recording.clone()),
+            formats.path(),
+            status,
+            log_error,
+            compiled,
+        )
+    })?;
+
+    fs::create_dir_all(dir).map_err(|source| Error::Write {
+        path: dir.to_path_buf(),
+        source,
+    })?;
+    let mut names = recording.names.borrow().clone();
+    // Read by Tectonic itself, to name the format file.
+    names.insert(DIGEST_NAME.to_string());
+    let mut bundle = recording.bundle.borrow_mut();
+    for name in &names {
+        let mut input = match bundle.input_open_name(name, status) {
+            OpenResult::Ok(input) => input,
+            OpenResult::NotAvailable => continue,
+            OpenResult::Err(e) => {
+                return Err(Error::Bundle {
+                    path: dir.to_path_buf(),
+                    message: format!("couldn't read {} from the bundle: {}", name, e),
+                })
+            }
+        };
+        let path = dir.join(name);
+        File::create(&path)
+            .and_then(|mut file| io::copy(&mut input, &mut file))
+            .map_err(|source| Error::Write { path, source })?;
+    }
+    Ok(names.into_iter().collect())
+}
+
+/// Copies an exported bundle in `dir` to [`vendored_bundle_dir`], replacing
+/// any imported before, and returns how many files it has.
+pub fn import_bundle(dir: &Path) -> Result<usize> {
+    let bundle_error = |message: String| Error::Bundle {
+        path: dir.to_path_buf(),
+        message,
+    };
+    if !dir.join(DIGEST_NAME).is_file() {
+        return Err(bundle_error(format!(
+            "it has no {}, so it isn't an exported bundle",
+            DIGEST_NAME
+        )));
+    }
+    let target = vendored_bundle_dir()
+        .ok_or_else(|| bundle_error("there's no cache directory to keep it in".to_string()))?;
+    let write_error = |source| Error::Write {
+        path: target.clone(),
+        source,
+    };
+    if target.exists() {
+        fs::remove_dir_all(&target).map_err(write_error)?;
+    }
+    fs::create_dir_all(&target).map_err(write_error)?;
+    let entries = fs::read_dir(dir).map_err(|source| Error::Read {
+        path: dir.to_path_buf(),
+        source,
+    })?;
+    let mut count = 0;
+    for entry in entries.flatten() {
+        if entry.path().is_file() {
+            fs::copy(entry.path(), target.join(entry.file_name())).map_err(write_error)?;
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+/// A bundle that notes the name of every file it hands out.
+#[derive(Clone)]
+struct Recording {
+    bundle: Rc<RefCell<Box<dyn Bundle>>>,
+    names: Rc<RefCell<BTreeSet<String>>>,
+}
+
+impl Recording {
+    fn record<T>(&self, name: &str, result: OpenResult<T>) -> OpenResult<T> {
+        // Bundles are flat; anything else isn't worth exporting.
+        if matches!(result, OpenResult::Ok(_)) && !name.contains(['/', '\\']) {
+            self.names.borrow_mut().insert(name.to_string());
+        }
+        result
+    }
+}
+
+impl IoProvider for Recording {
+    fn input_open_name(
+        &mut self,
+        name: &str,
+        status: &mut dyn StatusBackend,
+    ) -> OpenResult<InputHandle> {
+        let result = self.bundle.borrow_mut().input_open_name(name, status);
+        self.record(name, result)
+    }
+
+    fn input_open_name_with_abspath(
+        &mut self,
+        name: &str,
+        status: &mut dyn StatusBackend,
+    ) -> OpenResult<(InputHandle, Option<PathBuf>)> {
+        let result = self
+            .bundle
+            .borrow_mut()
+            .input_open_name_with_abspath(name, status);
+        self.record(name, result)
+    }
+}
+
+impl Bundle for Recording {
+    fn get_digest(&mut self, status: &mut dyn StatusBackend) -> anyhow::Result<DigestData> {
+        self.bundle.borrow_mut().get_digest(status)
+    }
+
+    fn all_files(&mut self, status: &mut dyn StatusBackend) -> anyhow::Result<Vec<String>> {
+        self.bundle.borrow_mut().all_files(status)
+    }
+}
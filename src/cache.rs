@@ -0,0 +1,17 @@
+//! Where downloads and generated files are kept between runs.
+
+use std::env;
+use std::path::PathBuf;
+
+/// Names a directory to keep the TeX bundle, format files and Gravatars in.
+pub const CACHE_DIR_VAR: &str = "RSUME_CACHE_DIR";
+
+/// The directory [`CACHE_DIR_VAR`] names, e.g. for CI to keep between runs or
+/// for each user on a shared machine. Without it, Tectonic keeps its own cache
+/// and Gravatars go in the user cache directory, both under `XDG_CACHE_HOME`
+/// where that's set.
+pub fn cache_dir() -> Option<PathBuf> {
+    env::var_os(CACHE_DIR_VAR)
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+}
@@ -0,0 +1,708 @@
+use std::cell::RefCell;
+use std::fs;
+use std::io;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tectonic::digest::DigestData;
+use tectonic::io::{InputHandle, IoProvider, OpenResult};
+use tectonic::status::{MessageKind, StatusBackend};
+use tectonic::{config, ctry, driver};
+use tectonic_bundles::dir::DirBundle;
+use tectonic_bundles::Bundle;
+use tempfile::TempDir;
+
+use crate::bundle::vendored_bundle;
+use crate::sourcemap::{self, TexLogError};
+use crate::{cache_dir, Asset, Engine, Error, Pass, Result};
+
+/// Tectonic reads from a single filesystem root, so the contents of several
+/// resource directories are copied into one staging directory. Earlier
+/// directories take precedence when the same file exists in more than one,
+/// and resource directories take precedence over include directories. Assets
+/// are copied last, under the names the rendered LaTeX refers to them by.
+/// Files are hard-linked where the filesystem allows, so large pictures and
+/// fonts aren't copied for every compile.
+fn stage_resource_dirs(options: &CompileOptions) -> io::Result<TempDir> {
+    let staging = TempDir::new()?;
+    for dir in options.include_dirs.iter().rev() {
+        flatten_dir(dir, staging.path())?;
+    }
+    for dir in options.resource_dirs.iter().rev() {
+        copy_dir(dir, staging.path())?;
+    }
+    for asset in &options.assets {
+        let target = staging.path().join(&asset.name);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        link_or_copy(&asset.source, &target)?;
+    }
+    Ok(staging)
+}
+
+fn link_or_copy(from: &Path, to: &Path) -> io::Result<()> {
+    // An earlier, lower-precedence directory may have staged the same name.
+    if to.exists() {
+        fs::remove_file(to)?;
+    }
+    fs::hard_link(from, to).or_else(|_| fs::copy(from, to).map(drop))
+}
+
+/// Like `copy_dir`, but every file lands directly in `to`, so a shared
+/// `classes/resume.cls` is found by `\documentclass{resume}`.
+fn flatten_dir(from: &Path, to: &Path) -> io::Result<()> {
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            flatten_dir(&entry.path(), to)?;
+        } else {
+            link_or_copy(&entry.path(), &to.join(entry.file_name()))?;
+        }
+    }
+    Ok(())
+}
+
+fn copy_dir(from: &Path, to: &Path) -> io::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            link_or_copy(&entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CompileOptions {
+    /// Directories Tectonic may read .sty/.cls/image files from
+    pub resource_dirs: Vec<PathBuf>,
+    /// Directories of shared inputs made visible by bare file name
+    pub include_dirs: Vec<PathBuf>,
+    /// Individual files to stage, see [`Resume::stage_assets`](crate::Resume::stage_assets)
+    pub assets: Vec<Asset>,
+    pub output_dir: PathBuf,
+    pub synctex: bool,
+    /// Fails the compile with [`Error::TimedOut`] once it has run this long.
+    /// Like [`Cancel`], it's only noticed as the engine opens files, so an
+    /// infinite TeX loop that loads nothing runs past it; callers that must
+    /// stop one need a process they can kill, as the CLI's watchdog does
+    pub timeout: Option<Duration>,
+    /// Fails the compile with [`Error::Cancelled`] once cancelled
+    pub cancel: Cancel,
+    /// Times to compile again after a failure that tends to go away on its
+    /// own, such as a cut-short bundle download
+    pub retries: u32,
+    /// From the template manifest's `[engine]`
+    pub engine: Engine,
+}
+
+impl CompileOptions {
+    /// Where the engine writes `tex_filename`'s output with `extension`, such as
+    /// `pdf` or `synctex.gz`. Only the `.tex` comes off, so `Jane.Doe.tex` gives
+    /// `Jane.Doe.pdf`.
+    pub fn output_path(&self, tex_filename: &str, extension: &str) -> PathBuf {
+        let stem = Path::new(tex_filename).file_stem().unwrap_or_default();
+        self.output_dir
+            .join(format!("{}.{}", stem.to_string_lossy(), extension))
+    }
+}
+
+/// Stops a compile from another thread.
+///
+/// Cancellation is cooperative: the engine notices it the next time it opens a
+/// file from the bundle, which packages and fonts do throughout a compile, so
+/// TeX code looping without loading anything runs on regardless.
+#[derive(Debug, Clone, Default)]
+pub struct Cancel(Arc<AtomicBool>);
+
+impl Cancel {
+    pub fn new() -> Cancel {
+        Cancel::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Why a compile should stop early, checked as the engine opens files.
+#[derive(Clone)]
+pub(crate) struct Interrupt {
+    cancel: Cancel,
+    timeout: Option<(Duration, Instant)>,
+}
+
+impl Interrupt {
+    pub(crate) fn new(options: &CompileOptions) -> Interrupt {
+        Interrupt {
+            cancel: options.cancel.clone(),
+            timeout: options
+                .timeout
+                .map(|timeout| (timeout, Instant::now() + timeout)),
+        }
+    }
+
+    fn error(&self) -> Option<Error> {
+        if self.cancel.is_cancelled() {
+            return Some(Error::Cancelled);
+        }
+        match self.timeout {
+            Some((timeout, deadline)) if Instant::now() >= deadline => {
+                Some(Error::TimedOut(timeout))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// What a successful compile produced.
+#[derive(Debug, Clone, Default)]
+pub struct Compiled {
+    /// Pages in the PDF, if the engine's log said
+    pub pages: Option<usize>,
+}
+
+/// Compiles rendered LaTeX to a PDF in `options.output_dir`.
+///
+/// Tectonic's engines share global state, so concurrent calls are serialized on
+/// its internal engine lock; it is safe to call this from several threads. A panic
+/// inside the engine is reported as an [`Error::Compile`] rather than unwinding
+/// into the caller.
+pub fn compile_pdf(
+    tex_filename: &str,
+    content: &str,
+    options: &CompileOptions,
+    status: &mut dyn StatusBackend,
+) -> Result<Compiled> {
+    retrying(options, status, None, |interrupt, status, transient| {
+        attempt(interrupt, transient, |log_error, compiled| {
+            let (bundle, format_cache_path) = open_bundle(status)?;
+            latex_to_pdf(
+                tex_filename,
+                content,
+                options,
+                Box::new(Interruptible {
+                    bundle,
+                    interrupt: interrupt.clone(),
+                }),
+                &format_cache_path,
+                status,
+                log_error,
+                compiled,
+            )
+        })
+    })
+}
+
+/// Compiles like [`compile_pdf`], keeping the resource bundle open between
+/// compiles instead of opening it for each, for long-running processes such as
+/// `rsume daemon`. Tectonic's bundles aren't `Send`, so neither is this.
+pub struct Compiler {
+    bundle: Rc<RefCell<Box<dyn Bundle>>>,
+    format_cache_path: PathBuf,
+}
+
+impl Compiler {
+    /// Opens the default bundle, downloading its index on first use.
+    pub fn new(status: &mut dyn StatusBackend) -> Result<Compiler> {
+        let (bundle, format_cache_path) = open_bundle(status).map_err(Error::from)?;
+        Ok(Compiler {
+            bundle: Rc::new(RefCell::new(bundle)),
+            format_cache_path,
+        })
+    }
+
+    pub fn compile_pdf(
+        &self,
+        tex_filename: &str,
+        content: &str,
+        options: &CompileOptions,
+        status: &mut dyn StatusBackend,
+    ) -> Result<Compiled> {
+        retrying(
+            options,
+            status,
+            Some(&self.format_cache_path),
+            |interrupt, status, transient| {
+                attempt(interrupt, transient, |log_error, compiled| {
+                    latex_to_pdf(
+                        tex_filename,
+                        content,
+                        options,
+                        Box::new(Interruptible {
+                            bundle: Box::new(SharedBundle(self.bundle.clone())),
+                            interrupt: interrupt.clone(),
+                        }),
+                        &self.format_cache_path,
+                        status,
+                        log_error,
+                        compiled,
+                    )
+                })
+            },
+        )
+    }
+}
+
+/// Failures that have gone away on a second try.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Transient {
+    /// A bundle file or index download cut short
+    Download,
+    /// A format file another process was writing, or left half-written
+    FormatCache,
+}
+
+/// What Tectonic and XeTeX say when the cached format can't be loaded. They
+/// report it only as text, with no error kind of its own.
+const FORMAT_FAILURES: &[&str] = &[
+    "could not open format file",
+    "cannot open the format file",
+    "fatal format file error",
+];
+
+impl Transient {
+    /// Looks through `error`'s causes for an I/O or network error of a kind
+    /// that passes. Format cache failures have no kind, so they're recognized
+    /// by their exact messages in [`FORMAT_FAILURES`].
+    fn of(error: &tectonic::Error) -> Option<Transient> {
+        let causes = || {
+            std::iter::successors(Some(error as &(dyn std::error::Error + 'static)), |cause| {
+                cause.source()
+            })
+        };
+        if causes().any(|cause| {
+            let message = cause.to_string();
+            FORMAT_FAILURES
+                .iter()
+                .any(|failure| message.contains(failure))
+        }) {
+            return Some(Transient::FormatCache);
+        }
+        causes()
+            .any(|cause| match cause.downcast_ref::<tectonic::Error>() {
+                Some(error) => match error.kind() {
+                    tectonic::ErrorKind::Io(error) => passing_io(error),
+                    tectonic::ErrorKind::NewStyle(error) => error.chain().any(passing),
+                    _ => false,
+                },
+                None => passing(cause),
+            })
+            .then_some(Transient::Download)
+    }
+}
+
+/// Whether `error` is a network or I/O failure that tends to pass.
+fn passing(error: &(dyn std::error::Error + 'static)) -> bool {
+    if let Some(error) = error.downcast_ref::<io::Error>() {
+        passing_io(error)
+    } else if let Some(error) = error.downcast_ref::<reqwest::Error>() {
+        error.is_timeout() || error.is_connect() || error.is_request() || error.is_body()
+    } else {
+        false
+    }
+}
+
+fn passing_io(error: &io::Error) -> bool {
+    matches!(
+        error.kind(),
+        io::ErrorKind::ConnectionRefused
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::BrokenPipe
+            | io::ErrorKind::TimedOut
+            | io::ErrorKind::Interrupted
+            | io::ErrorKind::UnexpectedEof
+    )
+}
+
+/// Runs `compile` again, up to `options.retries` times, after failures of a
+/// [`Transient`] kind, removing the cached LaTeX format first when it's the
+/// culprit so it's regenerated. `format_cache_path` is looked up when `None`.
+/// The timeout covers every attempt.
+fn retrying(
+    options: &CompileOptions,
+    status: &mut dyn StatusBackend,
+    format_cache_path: Option<&Path>,
+    mut compile: impl FnMut(
+        &Interrupt,
+        &mut dyn StatusBackend,
+        &mut Option<Transient>,
+    ) -> Result<Compiled>,
+) -> Result<Compiled> {
+    let interrupt = Interrupt::new(options);
+    let mut attempt = 0;
+    loop {
+        let mut transient = None;
+        let error = match compile(&interrupt, status, &mut transient) {
+            Ok(compiled) => return Ok(compiled),
+            Err(error) => error,
+        };
+        let Some(transient) = transient.filter(|_| attempt < options.retries) else {
+            return Err(error);
+        };
+        attempt += 1;
+        if transient == Transient::FormatCache {
+            let path = format_cache_path.map(Path::to_path_buf).or_else(|| {
+                config::PersistentConfig::open(false)
+                    .and_then(|config| formats_dir(&config))
+                    .ok()
+            });
+            if let Some(path) = path {
+                remove_formats(&path, options.engine.format());
+            }
+        }
+        status.report(
+            MessageKind::Warning,
+            format_args!("{}; retrying ({} of {})", error, attempt, options.retries),
+            None,
+        );
+    }
+}
+
+/// Removes the cached formats named `format`, which Tectonic names
+/// `<bundle digest>-<format>-<serial>.fmt`.
+fn remove_formats(format_cache_path: &Path, format: &str) {
+    let infix = format!("-{}-", format);
+    let Ok(entries) = fs::read_dir(format_cache_path) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if name.contains(&infix) && name.ends_with(".fmt") {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+}
+
+/// Runs `compile`, turning its errors and any engine panic into an
+/// [`Error::Compile`] with the TeX log's first error.
+/// A cancelled or timed-out compile is reported as such instead.
+pub(crate) fn catch_engine_errors(
+    interrupt: &Interrupt,
+    compile: impl FnOnce(&mut Option<TexLogError>, &mut Compiled) -> tectonic::errors::Result<()>,
+) -> Result<Compiled> {
+    attempt(interrupt, &mut None, compile)
+}
+
+/// Like [`catch_engine_errors`], also setting `transient` when the failure
+/// may well pass if the compile is repeated.
+fn attempt(
+    interrupt: &Interrupt,
+    transient: &mut Option<Transient>,
+    compile: impl FnOnce(&mut Option<TexLogError>, &mut Compiled) -> tectonic::errors::Result<()>,
+) -> Result<Compiled> {
+    let mut log_error = None;
+    let mut compiled = Compiled::default();
+    let result = panic::catch_unwind(AssertUnwindSafe(|| compile(&mut log_error, &mut compiled)));
+    match result {
+        Ok(Ok(())) => Ok(compiled),
+        Ok(Err(_)) if interrupt.error().is_some() => Err(interrupt.error().expect("checked above")),
+        Ok(Err(error)) => {
+            // A TeX error in the document fails the same way every time.
+            if log_error.is_none() {
+                *transient = Transient::of(&error);
+            }
+            match Error::from(error) {
+                Error::Compile { message, .. } => Err(Error::Compile { message, log_error }),
+                error => Err(error),
+            }
+        }
+        Err(_) => Err(Error::Compile {
+            message: "the LaTeX engine panicked".to_string(),
+            log_error,
+        }),
+    }
+}
+
+/// The imported bundle if there is one, or else the default resource bundle,
+/// and where the format file is cached, both in [`cache_dir`] when it's set.
+pub(crate) fn open_bundle(
+    status: &mut dyn StatusBackend,
+) -> tectonic::errors::Result<(Box<dyn Bundle>, PathBuf)> {
+    let auto_create_config_file = false;
+    let config = ctry!(config::PersistentConfig::open(auto_create_config_file);
+                       "failed to open the default configuration file");
+
+    let only_cached = false;
+    let url = config.default_bundle_loc().to_string();
+    let bundle: Box<dyn Bundle> = match (vendored_bundle(), cache_dir()) {
+        (Some(dir), _) => Box::new(DirBundle::new(dir)),
+        // A local bundle isn't cached.
+        (None, Some(dir)) if !url.starts_with("file:") => ctry!(
+            config.make_cached_url_provider(&url, only_cached, Some(&dir.join("bundles")), status);
+            "failed to load the default resource bundle"
+        ),
+        _ => ctry!(config.default_bundle(only_cached, status);
+                   "failed to load the default resource bundle"),
+    };
+
+    let format_cache_path = ctry!(formats_dir(&config); "failed to set up the format cache");
+    Ok((bundle, format_cache_path))
+}
+
+fn formats_dir(config: &config::PersistentConfig) -> tectonic::errors::Result<PathBuf> {
+    match cache_dir() {
+        Some(dir) => {
+            let formats = dir.join("formats");
+            fs::create_dir_all(&formats)?;
+            Ok(formats)
+        }
+        None => config.format_cache_path(),
+    }
+}
+
+/// A bundle shared between processing sessions, which each take ownership of
+/// theirs.
+struct SharedBundle(Rc<RefCell<Box<dyn Bundle>>>);
+
+impl IoProvider for SharedBundle {
+    fn input_open_name(
+        &mut self,
+        name: &str,
+        status: &mut dyn StatusBackend,
+    ) -> OpenResult<InputHandle> {
+        self.0.borrow_mut().input_open_name(name, status)
+    }
+
+    fn input_open_name_with_abspath(
+        &mut self,
+        name: &str,
+        status: &mut dyn StatusBackend,
+    ) -> OpenResult<(InputHandle, Option<PathBuf>)> {
+        self.0
+            .borrow_mut()
+            .input_open_name_with_abspath(name, status)
+    }
+}
+
+impl Bundle for SharedBundle {
+    fn get_digest(&mut self, status: &mut dyn StatusBackend) -> anyhow::Result<DigestData> {
+        self.0.borrow_mut().get_digest(status)
+    }
+
+    fn all_files(&mut self, status: &mut dyn StatusBackend) -> anyhow::Result<Vec<String>> {
+        self.0.borrow_mut().all_files(status)
+    }
+}
+
+/// A bundle that stops handing out files once its compile is interrupted,
+/// which makes the engine fail.
+struct Interruptible {
+    bundle: Box<dyn Bundle>,
+    interrupt: Interrupt,
+}
+
+impl IoProvider for Interruptible {
+    fn input_open_name(
+        &mut self,
+        name: &str,
+        status: &mut dyn StatusBackend,
+    ) -> OpenResult<InputHandle> {
+        match self.interrupt.error() {
+            Some(error) => OpenResult::Err(anyhow::anyhow!("{}", error)),
+            None => self.bundle.input_open_name(name, status),
+        }
+    }
+
+    fn input_open_name_with_abspath(
+        &mut self,
+        name: &str,
+        status: &mut dyn StatusBackend,
+    ) -> OpenResult<(InputHandle, Option<PathBuf>)> {
+        match self.interrupt.error() {
+            Some(error) => OpenResult::Err(anyhow::anyhow!("{}", error)),
+            None => self.bundle.input_open_name_with_abspath(name, status),
+        }
+    }
+}
+
+impl Bundle for Interruptible {
+    fn get_digest(&mut self, status: &mut dyn StatusBackend) -> anyhow::Result<DigestData> {
+        self.bundle.get_digest(status)
+    }
+
+    fn all_files(&mut self, status: &mut dyn StatusBackend) -> anyhow::Result<Vec<String>> {
+        self.bundle.all_files(status)
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn latex_to_pdf(
+    tex_filename: &str,
+    content: &str,
+    options: &CompileOptions,
+    bundle: Box<dyn Bundle>,
+    format_cache_path: &Path,
+    status: &mut dyn StatusBackend,
+    log_error: &mut Option<TexLogError>,
+    compiled: &mut Compiled,
+) -> tectonic::errors::Result<()> {
+    let log_name = format!(
+        "{}.log",
+        Path::new(tex_filename)
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+    );
+
+    let staging_dir;
+    let filesystem_root = match (
+        &options.resource_dirs[..],
+        &options.include_dirs[..],
+        &options.assets[..],
+    ) {
+        ([], [], []) => None,
+        ([dir], [], []) => Some(dir.as_path()),
+        _ => {
+            staging_dir = ctry!(stage_resource_dirs(options);
+                                "failed to stage the resource directories");
+            Some(staging_dir.path())
+        }
+    };
+
+    {
+        // Looking forward to non-lexical lifetimes!
+        let mut sb = driver::ProcessingSessionBuilder::default();
+        if let Some(root) = filesystem_root {
+            sb.filesystem_root(root);
+        }
+        sb.bundle(bundle)
+            .primary_input_buffer(content.as_bytes())
+            .tex_input_name(tex_filename)
+            .format_name(options.engine.format())
+            .format_cache_path(format_cache_path)
+            .keep_logs(false)
+            .keep_intermediates(false)
+            .print_stdout(false)
+            .synctex(options.synctex)
+            .output_format(driver::OutputFormat::Pdf)
+            .output_dir(&options.output_dir)
+            .pass(match options.engine.pass {
+                Pass::Default => driver::PassSetting::Default,
+                Pass::Tex => driver::PassSetting::Tex,
+                Pass::BibtexFirst => driver::PassSetting::BibtexFirst,
+            });
+        if let Some(reruns) = options.engine.reruns {
+            sb.reruns(reruns);
+        }
+
+        let mut sess =
+            ctry!(sb.create(status); "failed to initialize the LaTeX processing session");
+        if let Err(error) = sess.run(status) {
+            *log_error = sess
+                .into_file_data()
+                .get(&log_name)
+                .and_then(|file| sourcemap::parse_log(&String::from_utf8_lossy(&file.data)));
+            return Err(error.chain_err(|| "the LaTeX engine failed"));
+        }
+        compiled.pages = sess
+            .into_file_data()
+            .get(&log_name)
+            .and_then(|file| sourcemap::parse_page_count(&String::from_utf8_lossy(&file.data)));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cut_short_downloads_are_transient() {
+        let reset = tectonic::Error::from(io::Error::from(io::ErrorKind::ConnectionReset))
+            .chain_err(|| "failed to load the default resource bundle");
+        assert_eq!(Transient::of(&reset), Some(Transient::Download));
+
+        let truncated: tectonic::Error =
+            anyhow::Error::from(io::Error::from(io::ErrorKind::UnexpectedEof))
+                .context("failed to fetch the bundle index")
+                .into();
+        assert_eq!(Transient::of(&truncated), Some(Transient::Download));
+    }
+
+    #[test]
+    fn output_paths_keep_dots_in_the_stem() {
+        let options = CompileOptions {
+            output_dir: PathBuf::from("out"),
+            ..CompileOptions::default()
+        };
+        assert_eq!(
+            options.output_path("Jane.Doe-Acme.tex", "pdf"),
+            Path::new("out/Jane.Doe-Acme.pdf")
+        );
+        assert_eq!(
+            options.output_path("resume.tex", "synctex.gz"),
+            Path::new("out/resume.synctex.gz")
+        );
+    }
+
+    #[test]
+    #[ignore = "connects to a closed port on localhost"]
+    fn refused_requests_are_transient() {
+        let refused = reqwest::blocking::get("http://127.0.0.1:1/").expect_err("nothing listens");
+        let error: tectonic::Error = anyhow::Error::from(refused).into();
+        assert_eq!(Transient::of(&error), Some(Transient::Download));
+    }
+
+    #[test]
+    fn unreadable_format_files_are_transient() {
+        let error = tectonic::Error::from(io::Error::from(io::ErrorKind::InvalidData))
+            .chain_err(|| "could not open format file xelatex");
+        assert_eq!(Transient::of(&error), Some(Transient::FormatCache));
+
+        let aborted = tectonic::Error::from("fatal format file error")
+            .chain_err(|| "the LaTeX engine failed");
+        assert_eq!(Transient::of(&aborted), Some(Transient::FormatCache));
+
+        let mentioned = tectonic::Error::from("couldn't find notes.fmt for the format file list")
+            .chain_err(|| "the LaTeX engine failed");
+        assert_eq!(Transient::of(&mentioned), None);
+    }
+
+    #[test]
+    fn other_failures_are_not_transient() {
+        let missing = tectonic::Error::from(io::Error::from(io::ErrorKind::NotFound))
+            .chain_err(|| "failed to load the default resource bundle");
+        assert_eq!(Transient::of(&missing), None);
+
+        let engine = tectonic::Error::from(tectonic::ErrorKind::EngineError("xetex"))
+            .chain_err(|| "the LaTeX engine failed");
+        assert_eq!(Transient::of(&engine), None);
+
+        // Only the kind counts, not words that happen to be in a message.
+        let worded = tectonic::Error::from("connection timed out in the document")
+            .chain_err(|| "the LaTeX engine failed");
+        assert_eq!(Transient::of(&worded), None);
+    }
+
+    #[test]
+    fn document_errors_are_never_retried() {
+        let mut transient = None;
+        let interrupt = Interrupt::new(&CompileOptions::default());
+        let result = attempt(&interrupt, &mut transient, |log_error, _| {
+            *log_error = Some(TexLogError {
+                message: "Undefined control sequence.".to_string(),
+                line: Some(42),
+            });
+            Err(io::Error::from(io::ErrorKind::ConnectionReset).into())
+        });
+        assert!(result.is_err());
+        assert_eq!(transient, None);
+    }
+}
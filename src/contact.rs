@@ -0,0 +1,248 @@
+//! `contact_block` and `vcard`: the author's contact details with their link
+//! forms worked out once, so templates list them the same way.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+use tera::{try_get_value, Value};
+
+/// Profile sites with a FontAwesome icon of their own; others get `link`.
+const ICON_SITES: &[&str] = &[
+    "github",
+    "gitlab",
+    "linkedin",
+    "twitter",
+    "mastodon",
+    "orcid",
+    "stackoverflow",
+];
+
+/// One way to reach the author.
+#[derive(Debug, Serialize)]
+struct Contact {
+    /// `email`, `phone`, `website` or the profile's site name
+    kind: String,
+    /// An email's or phone's label, e.g. `work`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    label: Option<String>,
+    /// As shown, e.g. `jane.dev` for `https://jane.dev/`
+    display: String,
+    /// `mailto:`, `tel:` or the URL
+    link: String,
+    /// Name for the `icon` function
+    icon: String,
+}
+
+/// Adds `contact_block` and `vcard` from the context's contact fields, after
+/// output-specific privacy options have emptied any of them.
+pub(crate) fn apply(context: &mut Value) {
+    let field = |name: &str| {
+        context[name]
+            .as_str()
+            .unwrap_or_default()
+            .trim()
+            .to_string()
+    };
+    let (name, website) = (field("name"), field("website"));
+    let mut block = Vec::new();
+    for kind in ["email", "phone"] {
+        let details = context[format!("{}s", kind)].as_array().cloned();
+        for detail in details.unwrap_or_default() {
+            let value = detail["value"].as_str().unwrap_or_default().trim();
+            if value.is_empty() {
+                continue;
+            }
+            block.push(Contact {
+                kind: kind.to_string(),
+                label: detail["label"].as_str().map(str::to_string),
+                link: match kind {
+                    "email" => format!("mailto:{}", value),
+                    _ => format!("tel:{}", dialable(value)),
+                },
+                display: value.to_string(),
+                icon: kind.to_string(),
+            });
+        }
+    }
+    if !website.is_empty() {
+        block.push(Contact {
+            kind: "website".to_string(),
+            label: None,
+            display: bare(&website),
+            link: website.clone(),
+            icon: "website".to_string(),
+        });
+    }
+    if let Some(social) = context["social"].as_object() {
+        for (site, profile) in social {
+            let url = profile["url"].as_str().unwrap_or_default();
+            if url.is_empty() {
+                continue;
+            }
+            let username = profile["username"].as_str().unwrap_or_default();
+            block.push(Contact {
+                kind: site.clone(),
+                label: None,
+                display: if username.is_empty() {
+                    bare(url)
+                } else {
+                    username.to_string()
+                },
+                link: url.to_string(),
+                icon: if ICON_SITES.contains(&site.as_str()) {
+                    site.clone()
+                } else {
+                    "link".to_string()
+                },
+            });
+        }
+    }
+
+    let mut vcard = format!("BEGIN:VCARD\nVERSION:3.0\nFN:{}\n", vcard_text(&name));
+    for contact in &block {
+        // vCard only knows these two kinds of use.
+        let kind = match contact.label.as_deref() {
+            Some("work") => ";TYPE=work",
+            Some("home" | "personal") => ";TYPE=home",
+            _ => "",
+        };
+        let line = match contact.kind.as_str() {
+            "email" => format!("EMAIL{}:{}", kind, vcard_text(&contact.display)),
+            "phone" => format!("TEL{}:{}", kind, dialable(&contact.display)),
+            _ => format!("URL:{}", contact.link),
+        };
+        vcard.push_str(&line);
+        vcard.push('\n');
+    }
+    vcard.push_str("END:VCARD");
+    context["contact_block"] = serde_json::to_value(block).unwrap_or_default();
+    context["vcard"] = vcard.into();
+}
+
+/// The digits of a phone number, keeping a leading `+`.
+fn dialable(phone: &str) -> String {
+    phone
+        .chars()
+        .enumerate()
+        .filter(|(i, c)| c.is_ascii_digit() || (*i == 0 && *c == '+'))
+        .map(|(_, c)| c)
+        .collect()
+}
+
+/// A URL without its scheme, `www.` or trailing slash.
+fn bare(url: &str) -> String {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let rest = rest.strip_prefix("www.").unwrap_or(rest);
+    rest.strip_suffix('/').unwrap_or(rest).to_string()
+}
+
+fn vcard_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(',', "\\,")
+        .replace(';', "\\;")
+}
+
+/// A QR code of `text`, e.g. `{{ qr(text=vcard) }}` to save the contact
+/// details from a phone camera.
+pub(crate) fn qr(args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let text = match args.get("text") {
+        Some(text) => try_get_value!("qr", "text", String, text),
+        None => return Err(tera::Error::msg("Function `qr` requires a `text`")),
+    };
+    let size = match args.get("size") {
+        Some(size) => try_get_value!("qr", "size", String, size),
+        None => "2cm".to_string(),
+    };
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\n' => escaped.push_str("\\?"),
+            '\\' | '{' | '}' | '#' | '%' | '&' | '$' | '^' | '_' | '~' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            c => escaped.push(c),
+        }
+    }
+    Ok(Value::String(format!(
+        "\\qrcode[height={}]{{{}}}",
+        size, escaped
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn context() -> Value {
+        json!({
+            "name": "Doe, Jane",
+            "website": "https://www.jane.dev/",
+            "emails": [
+                {"value": "jane@work.example", "label": "work"},
+                {"value": "jane@example.com", "label": "personal"},
+                {"value": " "},
+            ],
+            "phones": [{"value": "+1 (555) 010-2030", "label": "mobile"}],
+            "social": {
+                "github": {"url": "https://github.com/jane", "username": "jane"},
+                "blog": {"url": "https://blog.jane.dev/", "username": ""},
+            },
+        })
+    }
+
+    #[test]
+    fn vcards_list_every_contact_with_its_kind_of_use() {
+        let mut context = context();
+        apply(&mut context);
+        assert_eq!(
+            context["vcard"],
+            "BEGIN:VCARD\n\
+             VERSION:3.0\n\
+             FN:Doe\\, Jane\n\
+             EMAIL;TYPE=work:jane@work.example\n\
+             EMAIL;TYPE=home:jane@example.com\n\
+             TEL:+15550102030\n\
+             URL:https://www.jane.dev/\n\
+             URL:https://blog.jane.dev/\n\
+             URL:https://github.com/jane\n\
+             END:VCARD"
+        );
+    }
+
+    #[test]
+    fn contact_blocks_show_bare_links_and_usernames() {
+        let mut context = context();
+        apply(&mut context);
+        let block = context["contact_block"].as_array().unwrap();
+        let shown: Vec<(&str, &str, &str)> = block
+            .iter()
+            .map(|contact| {
+                (
+                    contact["display"].as_str().unwrap(),
+                    contact["link"].as_str().unwrap(),
+                    contact["icon"].as_str().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            shown,
+            [
+                ("jane@work.example", "mailto:jane@work.example", "email"),
+                ("jane@example.com", "mailto:jane@example.com", "email"),
+                ("+1 (555) 010-2030", "tel:+15550102030", "phone"),
+                ("jane.dev", "https://www.jane.dev/", "website"),
+                ("blog.jane.dev", "https://blog.jane.dev/", "link"),
+                ("jane", "https://github.com/jane", "github"),
+            ]
+        );
+    }
+
+    #[test]
+    fn dialable_numbers_keep_only_a_leading_plus() {
+        assert_eq!(dialable("+44 20 7946 0958"), "+442079460958");
+        assert_eq!(dialable("555-0100 ext. +2"), "55501002");
+    }
+}
@@ -0,0 +1,81 @@
+//! WCAG contrast between the theme's colors, so a personalized palette stays
+//! readable on screen and once printed in black and white.
+
+use crate::{Finding, Theme};
+
+/// WCAG AA's minimum contrast ratio for body text.
+const MIN_CONTRAST: f64 = 4.5;
+
+impl Theme {
+    /// The color pairs bundled templates draw text with whose contrast ratio
+    /// is below WCAG AA's 4.5:1, as `low-contrast` findings.
+    pub fn contrast(&self) -> Vec<Finding> {
+        let pairs = [
+            ("accent_color", &self.accent_color, "white pages", "FFFFFF"),
+            (
+                "accent_color",
+                &self.accent_color,
+                "sidebar_color",
+                self.sidebar_color.as_str(),
+            ),
+            (
+                "sidebar_text_color",
+                &self.sidebar_text_color,
+                "sidebar_color",
+                self.sidebar_color.as_str(),
+            ),
+        ];
+        let mut findings = Vec::new();
+        for (field, color, background_name, background) in pairs {
+            let (Some(foreground), Some(background)) = (luminance(color), luminance(background))
+            else {
+                continue;
+            };
+            let ratio = (foreground.max(background) + 0.05) / (foreground.min(background) + 0.05);
+            if ratio < MIN_CONTRAST {
+                findings.push(Finding {
+                    rule: "low-contrast",
+                    field: format!("theme.{}", field),
+                    message: format!(
+                        "`{}` has a contrast ratio of {:.1}:1 against {}; WCAG AA asks for at least {}:1",
+                        color, ratio, background_name, MIN_CONTRAST
+                    ),
+                });
+            }
+        }
+        for (field, color) in [
+            ("accent_color", &self.accent_color),
+            ("sidebar_color", &self.sidebar_color),
+            ("sidebar_text_color", &self.sidebar_text_color),
+        ] {
+            if luminance(color).is_none() {
+                findings.push(Finding {
+                    rule: "low-contrast",
+                    field: format!("theme.{}", field),
+                    message: format!(
+                        "`{}` isn't a hex RGB color such as `1F4E79`, so its contrast wasn't checked",
+                        color
+                    ),
+                });
+            }
+        }
+        findings
+    }
+}
+
+/// The relative luminance of a hex RGB color, from 0 for black to 1 for white.
+fn luminance(hex: &str) -> Option<f64> {
+    let hex = hex.strip_prefix('#').unwrap_or(hex);
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |i: usize| -> f64 {
+        let value = u8::from_str_radix(&hex[i..i + 2], 16).unwrap_or_default() as f64 / 255.0;
+        if value <= 0.03928 {
+            value / 12.92
+        } else {
+            ((value + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    Some(0.2126 * channel(0) + 0.7152 * channel(2) + 0.0722 * channel(4))
+}
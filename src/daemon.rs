@@ -0,0 +1,108 @@
+//! `rsume daemon`: builds on request over a Unix socket, so an editor's
+//! rebuild on save skips the startup work. Requests are
+//! [`Request`]s as lines of JSON.
+
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use rsume::Error;
+use serde::Serialize;
+use tracing::{info, warn};
+
+use crate::error_chain;
+use crate::rebuild::{Rebuilder, Request};
+
+/// Longest request line in bytes; a client sending more is disconnected.
+const MAX_REQUEST_SIZE: u64 = 64 * 1024;
+
+/// The answer to a [`Request`], as a line of JSON.
+#[derive(Debug, Serialize)]
+struct Response {
+    success: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    artifacts: Vec<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<String>,
+    milliseconds: u128,
+}
+
+/// Listens on `socket` until killed, answering requests one at a time.
+pub fn serve(socket: &Path, mut rebuilder: Rebuilder) -> rsume::Result<()> {
+    // Left behind by a daemon that didn't shut down cleanly.
+    if socket.exists() && UnixStream::connect(socket).is_err() {
+        let _ = fs::remove_file(socket);
+    }
+    let listener = UnixListener::bind(socket).map_err(|source| Error::Write {
+        path: socket.to_path_buf(),
+        source,
+    })?;
+    info!(socket = %socket.display(), "listening");
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => handle(&mut rebuilder, stream),
+            Err(e) => warn!("couldn't accept a connection: {}", e),
+        }
+    }
+    Ok(())
+}
+
+fn handle(rebuilder: &mut Rebuilder, stream: UnixStream) {
+    let mut writer = match stream.try_clone() {
+        Ok(writer) => writer,
+        Err(e) => return warn!("couldn't answer a connection: {}", e),
+    };
+    let mut reader = BufReader::new(stream);
+    loop {
+        let mut line = String::new();
+        match (&mut reader)
+            .take(MAX_REQUEST_SIZE + 1)
+            .read_line(&mut line)
+        {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {}
+        }
+        // The rest of an overlong line is never read, so the connection can't
+        // go on.
+        let too_long = !line.ends_with('\n') && line.len() as u64 > MAX_REQUEST_SIZE;
+        if !too_long && line.trim().is_empty() {
+            continue;
+        }
+        let start = Instant::now();
+        let result = if too_long {
+            Err(format!(
+                "the request is longer than {} bytes",
+                MAX_REQUEST_SIZE
+            ))
+        } else {
+            serde_json::from_str::<Request>(&line)
+                .map_err(|e| format!("couldn't parse the request: {}", e))
+                .and_then(|request| rebuilder.build(&request).map_err(|e| error_chain(&e)))
+        };
+        let response = match result {
+            Ok(artifacts) => Response {
+                success: true,
+                artifacts,
+                message: None,
+                milliseconds: start.elapsed().as_millis(),
+            },
+            Err(message) => Response {
+                success: false,
+                artifacts: Vec::new(),
+                message: Some(message),
+                milliseconds: start.elapsed().as_millis(),
+            },
+        };
+        info!(
+            success = response.success,
+            milliseconds = response.milliseconds as u64,
+            "built"
+        );
+        let response = serde_json::to_string(&response).expect("responses always serialize");
+        if writeln!(writer, "{}", response).is_err() || too_long {
+            break;
+        }
+    }
+}
@@ -0,0 +1,236 @@
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+use schemars::schema::{InstanceType, Schema, SchemaObject, SingleOrVec};
+use schemars::schema_for;
+
+use crate::render::{BUILTIN_FUNCTIONS, FILTERS, FUNCTIONS};
+use crate::{Author, Manifest, BABEL_LANGUAGES, PARTIALS, SECTIONS};
+
+/// A Markdown reference of the template context, generated from the `Author`
+/// schema and the registered filters so it can't drift from the code. When
+/// `used` is given, the variables that template reads are listed as well, and
+/// with a `manifest` the display keys it declares.
+pub fn template_reference(
+    used: Option<(&str, &BTreeSet<String>)>,
+    manifest: Option<&Manifest>,
+) -> String {
+    let root = schema_for!(Author);
+    let mut out = String::from("# Template reference\n\n");
+
+    out.push_str("## Context\n\n");
+    write_object(&mut out, &root.schema);
+    let flags: Vec<String> = SECTIONS
+        .iter()
+        .map(|section| format!("`has_{}`", section))
+        .collect();
+    let _ = writeln!(
+        out,
+        "Sections may be missing or empty; {} are false when they are, so templates can skip the heading.\n",
+        flags.join(", ")
+    );
+    out.push_str(
+        "`contact_block` lists the emails, phones, website and profiles as `kind`, `label` (for labeled emails and phones), `display`, `link` and `icon` (a name for `icon()`), and `vcard` holds them as a vCard for `qr()`; both leave out what HTML options hide.\n\n",
+    );
+    out.push_str(
+        "`earlier_experience_summary` sums up `earlier_experiences` when there are any, as `count`, `employers`, `start_year`, `end_year` (absent while one is ongoing) and `years`, for a one-line mention such as `{{ earlier_experience_summary.count }} earlier roles since {{ earlier_experience_summary.start_year }}`.\n\n",
+    );
+    out.push_str(
+        "`engagements` lists client work across `experiences`, most recent first, as `client`, `role`, `employer` (absent when it's the client), `start_date`, `end_date`, `months`, `duration` (e.g. `1 year 4 months`), `stack` and `outcomes`; an experience without `engagements` counts as one for its company.\n\n",
+    );
+    out.push_str(
+        "`generated_on` is the build's date and `valid_until` the date `stamp.valid_days` later, both as `YYYY-MM-DD` and only when `stamp` asks for them.\n\n",
+    );
+    let tags: Vec<String> = BABEL_LANGUAGES
+        .iter()
+        .map(|(tag, _)| format!("`{}`", tag))
+        .collect();
+    let _ = writeln!(
+        out,
+        "`babel_language` is the `babel` name for `language`, e.g. `ngerman` for `de`, to load with `\\usepackage[{{{{ babel_language }}}}]{{babel}}`. LaTeX templates fail on other languages than {}.\n",
+        tags.join(", ")
+    );
+    for (name, schema) in &root.definitions {
+        if let Schema::Object(object) = schema {
+            let _ = writeln!(out, "### {}\n", name);
+            write_object(&mut out, object);
+        }
+    }
+
+    out.push_str("## Filters\n\n");
+    out.push_str(
+        "Tera's [builtin filters](https://keats.github.io/tera/docs/#built-in-filters) plus:\n\n",
+    );
+    out.push_str("| Filter | Description |\n| --- | --- |\n");
+    for filter in FILTERS {
+        let _ = writeln!(out, "| `{}` | {} |", filter.name, filter.description);
+    }
+    out.push('\n');
+
+    out.push_str("## Functions\n\n");
+    out.push_str("| Function | Description |\n| --- | --- |\n");
+    for function in FUNCTIONS {
+        let _ = writeln!(
+            out,
+            "| `{}` | {} |",
+            function.signature, function.description
+        );
+    }
+    for (signature, description) in BUILTIN_FUNCTIONS {
+        let _ = writeln!(out, "| `{}` | {} |", signature, description);
+    }
+    out.push('\n');
+
+    out.push_str("## Partials\n\n");
+    out.push_str("Bundled templates to `extends`, `include` or `import`; a file of the same name in the template directory's `partials/` replaces one.\n\n");
+    out.push_str("| Template | Description |\n| --- | --- |\n");
+    for partial in PARTIALS {
+        let _ = writeln!(out, "| `{}` | {} |", partial.name, partial.description);
+    }
+    out.push('\n');
+
+    if let Some(manifest) = manifest {
+        out.push_str("## Display keys\n\n");
+        out.push_str("Values accepted in `experiences[].display`.\n\n");
+        out.push_str("| Key | Description |\n| --- | --- |\n");
+        for (key, description) in &manifest.display {
+            let _ = writeln!(out, "| `{}` | {} |", key, description);
+        }
+        out.push('\n');
+    }
+
+    if let Some((name, used)) = used {
+        let _ = writeln!(out, "## Variables used by `{}`\n", name);
+        for path in used {
+            let _ = writeln!(out, "- `{}`", path);
+        }
+        out.push('\n');
+    }
+    out
+}
+
+fn write_object(out: &mut String, object: &SchemaObject) {
+    if let Some(description) = object
+        .metadata
+        .as_ref()
+        .and_then(|m| m.description.as_ref())
+    {
+        let _ = writeln!(out, "{}\n", description);
+    }
+    let Some(properties) = object.object.as_ref().map(|o| &o.properties) else {
+        write_enum(out, object);
+        return;
+    };
+    out.push_str("| Field | Type | Description |\n| --- | --- | --- |\n");
+    for (name, schema) in properties {
+        let (type_name, description) = match schema {
+            Schema::Object(property) => (
+                type_name(property),
+                property
+                    .metadata
+                    .as_ref()
+                    .and_then(|m| m.description.clone())
+                    .unwrap_or_default(),
+            ),
+            Schema::Bool(_) => ("any".to_string(), String::new()),
+        };
+        let _ = writeln!(out, "| `{}` | {} | {} |", name, type_name, description);
+    }
+    out.push('\n');
+}
+
+/// Unit enums are either a plain `enum` of strings or, when variants are
+/// documented, a `oneOf` of single-value enums.
+fn write_enum(out: &mut String, object: &SchemaObject) {
+    let variants: Vec<(String, String)> = match &object.enum_values {
+        Some(values) => values
+            .iter()
+            .map(|v| (value_name(v), String::new()))
+            .collect(),
+        None => object
+            .subschemas
+            .iter()
+            .flat_map(|s| s.one_of.iter().flatten())
+            .filter_map(|variant| match variant {
+                Schema::Object(variant) => Some((
+                    value_name(variant.enum_values.as_ref()?.first()?),
+                    variant
+                        .metadata
+                        .as_ref()
+                        .and_then(|m| m.description.clone())
+                        .unwrap_or_default(),
+                )),
+                Schema::Bool(_) => None,
+            })
+            .collect(),
+    };
+    if variants.is_empty() {
+        return;
+    }
+    out.push_str("| Value | Description |\n| --- | --- |\n");
+    for (value, description) in variants {
+        let _ = writeln!(out, "| `{}` | {} |", value, description);
+    }
+    out.push('\n');
+}
+
+fn value_name(value: &serde_json::Value) -> String {
+    value
+        .as_str()
+        .map_or_else(|| value.to_string(), String::from)
+}
+
+fn type_name(schema: &SchemaObject) -> String {
+    if let Some(reference) = &schema.reference {
+        let name = reference.rsplit('/').next().unwrap_or(reference);
+        return format!("[{}](#{})", name, name.to_lowercase());
+    }
+    // `Option<T>` fields are `T` or `null`; references to other structs hide in
+    // `subschemas`/`all_of`.
+    if let Some(all_of) = schema.subschemas.as_ref().and_then(|s| s.all_of.as_ref()) {
+        if let Some(Schema::Object(inner)) = all_of.first() {
+            return type_name(inner);
+        }
+    }
+    let mut optional = false;
+    let instance = match &schema.instance_type {
+        Some(SingleOrVec::Single(instance)) => Some(**instance),
+        Some(SingleOrVec::Vec(types)) => {
+            optional = types.contains(&InstanceType::Null);
+            types.iter().copied().find(|t| *t != InstanceType::Null)
+        }
+        None => None,
+    };
+    let name = match instance {
+        Some(InstanceType::Array) => {
+            let item = match schema.array.as_ref().and_then(|a| a.items.as_ref()) {
+                Some(SingleOrVec::Single(item)) => match item.as_ref() {
+                    Schema::Object(item) => type_name(item),
+                    Schema::Bool(_) => "any".to_string(),
+                },
+                _ => "any".to_string(),
+            };
+            format!("list of {}", item)
+        }
+        Some(InstanceType::Object) => {
+            match schema
+                .object
+                .as_ref()
+                .and_then(|o| o.additional_properties.as_deref())
+            {
+                Some(Schema::Object(value)) => format!("map of {}", type_name(value)),
+                _ => "table".to_string(),
+            }
+        }
+        Some(InstanceType::String) => "string".to_string(),
+        Some(InstanceType::Boolean) => "boolean".to_string(),
+        Some(InstanceType::Integer) => "integer".to_string(),
+        Some(InstanceType::Number) => "number".to_string(),
+        Some(InstanceType::Null) | None => "any".to_string(),
+    };
+    if optional {
+        format!("{} (optional)", name)
+    } else {
+        name
+    }
+}
@@ -0,0 +1,88 @@
+//! Emoji in LaTeX output. The bundled fonts have no emoji glyphs, so XeTeX
+//! would print nothing and bury a missing-character warning in its log;
+//! common ones become FontAwesome icons instead and the rest are dropped.
+
+use std::collections::HashMap;
+
+use tera::{try_get_value, Value};
+
+/// Emoji with a FontAwesome 5 equivalent.
+const ICONS: &[(char, &str)] = &[
+    ('🚀', "rocket"),
+    ('⭐', "star"),
+    ('🌟', "star"),
+    ('✅', "check-circle"),
+    ('✔', "check"),
+    ('❤', "heart"),
+    ('🔥', "fire"),
+    ('💡', "lightbulb"),
+    ('📈', "chart-line"),
+    ('📊', "chart-bar"),
+    ('🏆', "trophy"),
+    ('🥇', "medal"),
+    ('🎓', "graduation-cap"),
+    ('💻', "laptop"),
+    ('📧', "envelope"),
+    ('✉', "envelope"),
+    ('📞', "phone"),
+    ('📱', "mobile-alt"),
+    ('🌐', "globe"),
+    ('🌍', "globe-europe"),
+    ('🌎', "globe-americas"),
+    ('🌏', "globe-asia"),
+    ('📍', "map-marker-alt"),
+    ('🔗', "link"),
+    ('🐛', "bug"),
+    ('🔒', "lock"),
+    ('⚡', "bolt"),
+    ('👍', "thumbs-up"),
+    ('🛠', "tools"),
+    ('🔧', "wrench"),
+    ('📚', "book"),
+    ('📝', "edit"),
+    ('🤝', "handshake"),
+    ('💰', "coins"),
+    ('🎉', "glass-cheers"),
+    ('➡', "arrow-right"),
+];
+
+/// Whether `c` is an emoji, or one of the joiners, modifiers and selectors
+/// emoji sequences are built from.
+pub(crate) fn is_emoji(c: char) -> bool {
+    matches!(
+        c,
+        '\u{1F000}'..='\u{1FAFF}'
+            | '\u{2600}'..='\u{27BF}'
+            | '\u{2B00}'..='\u{2BFF}'
+            | '\u{FE00}'..='\u{FE0F}'
+            | '\u{200D}'
+            | '\u{20E3}'
+            | '\u{E0020}'..='\u{E007F}'
+    )
+}
+
+/// `\faIcon{...}` for an emoji with an equivalent, nothing otherwise.
+pub(crate) fn to_latex(c: char) -> String {
+    ICONS
+        .iter()
+        .find(|(emoji, _)| *emoji == c)
+        .map(|(_, icon)| format!("\\faIcon{{{}}}", icon))
+        .unwrap_or_default()
+}
+
+pub(crate) fn emoji(value: &Value, args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let input = try_get_value!("emoji", "value", String, value);
+    let strip = match args.get("strip") {
+        Some(strip) => try_get_value!("emoji", "strip", bool, strip),
+        None => false,
+    };
+    let mut output = String::with_capacity(input.len());
+    for c in input.chars() {
+        match c {
+            c if is_emoji(c) && !strip => output.push_str(&to_latex(c)),
+            c if is_emoji(c) => {}
+            c => output.push(c),
+        }
+    }
+    Ok(Value::String(output))
+}
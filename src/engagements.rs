@@ -0,0 +1,106 @@
+//! `engagements`: client work across all experiences in one list, for
+//! profiles organized around projects rather than employers.
+
+use std::cmp::Reverse;
+
+use chrono::{Datelike, Local, NaiveDate};
+use serde::Serialize;
+
+use crate::Author;
+
+/// An engagement as templates list it, e.g. `Globex, Platform lead, via
+/// Initech, 1 year 4 months`.
+#[derive(Debug, Clone, Serialize)]
+pub struct EngagementEntry {
+    pub client: String,
+    pub role: String,
+    /// The employer the work was done through; absent when that's the client
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub employer: Option<String>,
+    pub start_date: NaiveDate,
+    /// Absent while ongoing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_date: Option<NaiveDate>,
+    /// Calendar months from the start to the end, or to now, counting both
+    pub months: u32,
+    /// `months` in words, e.g. `1 year 4 months`
+    pub duration: String,
+    pub stack: Vec<String>,
+    pub outcomes: Vec<String>,
+}
+
+impl Author {
+    /// Every experience's engagements, with an experience that has none
+    /// standing in as one for its company, most recent first.
+    pub fn engagements(&self) -> Vec<EngagementEntry> {
+        let today = Local::now().date_naive();
+        let mut entries = Vec::new();
+        for experience in &self.experiences {
+            let company = experience
+                .company
+                .display_name
+                .clone()
+                .unwrap_or_else(|| experience.company.name.clone());
+            if experience.engagements.is_empty() {
+                entries.push(EngagementEntry {
+                    client: company,
+                    role: experience.position.clone(),
+                    employer: None,
+                    start_date: experience.start_date,
+                    end_date: experience.end_date,
+                    months: 0,
+                    duration: String::new(),
+                    stack: experience.technologies.clone(),
+                    outcomes: experience
+                        .highlights
+                        .iter()
+                        .map(|highlight| highlight.text.clone())
+                        .collect(),
+                });
+                continue;
+            }
+            for engagement in &experience.engagements {
+                entries.push(EngagementEntry {
+                    client: engagement.client.clone(),
+                    role: engagement
+                        .role
+                        .clone()
+                        .unwrap_or_else(|| experience.position.clone()),
+                    employer: (engagement.client != company).then(|| company.clone()),
+                    start_date: engagement.start_date,
+                    end_date: engagement.end_date,
+                    months: 0,
+                    duration: String::new(),
+                    stack: engagement.stack.clone(),
+                    outcomes: engagement.outcomes.clone(),
+                });
+            }
+        }
+        for entry in &mut entries {
+            entry.months = months(entry.start_date, entry.end_date, today);
+            entry.duration = duration(entry.months);
+        }
+        // Stable, so engagements starting together keep their order.
+        entries.sort_by_key(|entry| Reverse(entry.start_date));
+        entries
+    }
+}
+
+/// Calendar months touched from `start` to `end`, so January to March is 3.
+fn months(start: NaiveDate, end: Option<NaiveDate>, today: NaiveDate) -> u32 {
+    let end = end.unwrap_or(today);
+    let months = (end.year() - start.year()) * 12 + end.month() as i32 - start.month() as i32;
+    (months + 1).max(1) as u32
+}
+
+fn duration(months: u32) -> String {
+    let unit = |count: u32, name: &str| match count {
+        1 => format!("1 {}", name),
+        _ => format!("{} {}s", count, name),
+    };
+    match (months / 12, months % 12) {
+        (0, months) => unit(months, "month"),
+        (years, 0) => unit(years, "year"),
+        (years, months) => format!("{} {}", unit(years, "year"), unit(months, "month")),
+    }
+}
@@ -0,0 +1,156 @@
+use std::io;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::sourcemap::TexLogError;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Everything that can go wrong between reading a data file and writing a PDF.
+///
+/// Messages don't repeat their source; walk `source()` for the full chain.
+/// Tectonic's own error type isn't `Sync`, so its error chain is flattened into
+/// `message`.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("couldn't read {}", path.display())]
+    Read { path: PathBuf, source: io::Error },
+    #[error("couldn't write {}", path.display())]
+    Write { path: PathBuf, source: io::Error },
+    #[error("couldn't parse resume data")]
+    Parse(#[source] toml::de::Error),
+    #[error("couldn't parse resume data")]
+    Edit(#[source] toml_edit::TomlError),
+    #[error(
+        "resume data uses schema version {0}; run `rsume migrate` to upgrade it to version {}",
+        crate::SCHEMA_VERSION
+    )]
+    OutdatedSchema(u32),
+    #[error(
+        "resume data uses schema version {0}, newer than the supported version {}",
+        crate::SCHEMA_VERSION
+    )]
+    UnsupportedSchema(u32),
+    #[error("resume data uses schema version {0}, which isn't a valid version")]
+    InvalidSchema(i64),
+    #[error("{field} has {}", if *.ended { "an end_date but current = true" } else { "no end_date but current = false" })]
+    Current { field: String, ended: bool },
+    #[error("{field} `{value}` isn't a valid URL: {message}")]
+    Url {
+        field: String,
+        value: String,
+        message: String,
+    },
+    #[error("couldn't parse revision history {}", path.display())]
+    History {
+        path: PathBuf,
+        source: Box<toml::de::Error>,
+    },
+    #[error("the data already has a revision tagged `{0}`")]
+    TagExists(String),
+    #[error("couldn't import line {line}: {message}")]
+    Import { line: usize, message: String },
+    #[error(
+        "LaTeX templates can't hyphenate language `{0}`; they support {}",
+        crate::BABEL_LANGUAGES.iter().map(|(tag, _)| *tag).collect::<Vec<_>>().join(", ")
+    )]
+    UnsupportedLanguage(String),
+    #[error("no variant named `{name}`; the data declares {}", declared_list(.declared))]
+    UnknownVariant { name: String, declared: Vec<String> },
+    #[error("no built-in template set named {0}")]
+    UnknownBuiltin(String),
+    #[error("couldn't parse template manifest {}", path.display())]
+    Manifest {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+    #[error("unknown display key `{key}` in {field}; the template declares {}", declared_list(.declared))]
+    UnknownDisplayKey {
+        key: String,
+        field: String,
+        declared: Vec<String>,
+    },
+    #[error("template path {} is not valid UTF-8", .0.display())]
+    TemplatePath(PathBuf),
+    #[error("couldn't load templates")]
+    Templates(#[source] tera::Error),
+    #[error("rendering template {name} failed")]
+    Render { name: String, source: tera::Error },
+    #[error(
+        "{} is {:.1} MB, more than the {} MB allowed for pictures; scale it down first",
+        path.display(),
+        *.size as f64 / 1048576.0,
+        crate::MAX_PICTURE_SIZE / 1048576
+    )]
+    PictureTooLarge { path: PathBuf, size: u64 },
+    #[error("{what} is {size} bytes, more than the limit of {limit}")]
+    TooLarge { what: String, size: u64, limit: u64 },
+    #[error("{field} `{path}` is outside the data file's directory")]
+    OutsideFile { field: String, path: String },
+    #[error("{field} {} is outside {}", path.display(), root.display())]
+    OutsideRoot {
+        field: String,
+        path: PathBuf,
+        root: PathBuf,
+    },
+    #[error("{field} contains `{character}`, which TeX reads as markup")]
+    TexMarkup { field: String, character: char },
+    #[error("couldn't fetch the Gravatar for {email}: {message}")]
+    Avatar { email: String, message: String },
+    #[error("couldn't refresh project stats: {0}")]
+    Refresh(String),
+    #[error("couldn't load signing certificate {}: {message}", path.display())]
+    Certificate { path: PathBuf, message: String },
+    #[error("couldn't use bundle {}: {message}", path.display())]
+    Bundle { path: PathBuf, message: String },
+    #[error("couldn't optimize {}: {message}", path.display())]
+    Optimize { path: PathBuf, message: String },
+    #[error("couldn't compare {}: {message}", path.display())]
+    DiffPdf { path: PathBuf, message: String },
+    #[error("couldn't sign {}: {message}", path.display())]
+    Sign { path: PathBuf, message: String },
+    #[error("the compile was cancelled")]
+    Cancelled,
+    #[error("the compile took longer than {} seconds", .0.as_secs())]
+    TimedOut(Duration),
+    #[error("{message}")]
+    Compile {
+        message: String,
+        /// The first error in the TeX log, when the engine got far enough to write one
+        log_error: Option<TexLogError>,
+    },
+}
+
+fn declared_list(declared: &[String]) -> String {
+    if declared.is_empty() {
+        "none".to_string()
+    } else {
+        declared
+            .iter()
+            .map(|key| format!("`{}`", key))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+#[cfg(feature = "pdf")]
+impl From<tectonic::Error> for Error {
+    fn from(error: tectonic::Error) -> Self {
+        Error::Compile {
+            message: error
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(": "),
+            log_error: None,
+        }
+    }
+}
+
+// Embedders hand errors across threads and into `anyhow`/`Box<dyn Error>`.
+fn _assert_send_sync() {
+    fn is_send_sync<T: Send + Sync + 'static>() {}
+    is_send_sync::<Error>();
+    is_send_sync::<crate::Resume>();
+    is_send_sync::<crate::Templates>();
+}
@@ -0,0 +1,78 @@
+//! General-purpose template functions, so templates don't hardcode values that
+//! go stale: `today()`, `years_since()` and `pluralize()`.
+
+use std::collections::HashMap;
+
+use chrono::{Datelike, Local, NaiveDate};
+use tera::{try_get_value, Value};
+
+use crate::render::strftime;
+
+pub fn today(args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let format = match args.get("format") {
+        Some(format) => try_get_value!("today", "format", String, format),
+        None => "%Y-%m-%d".to_string(),
+    };
+    strftime("Function `today`", Local::now().date_naive(), &format).map(Value::String)
+}
+
+pub fn years_since(args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let date = |name: &str| -> tera::Result<Option<NaiveDate>> {
+        let Some(value) = args.get(name) else {
+            return Ok(None);
+        };
+        let text = try_get_value!("years_since", name, String, value);
+        // TOML dates come through as `YYYY-MM-DD`, possibly followed by a time.
+        let date = text.get(..10).unwrap_or(&text);
+        NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map(Some)
+            .map_err(|_| {
+                tera::Error::msg(format!(
+                    "Function `years_since` got {}=`{}`, expected a date like 2020-01-31",
+                    name, text
+                ))
+            })
+    };
+    let Some(from) = date("date")? else {
+        return Err(tera::Error::msg("Function `years_since` requires a `date`"));
+    };
+    let until = date("until")?.unwrap_or_else(|| Local::now().date_naive());
+    let mut years = until.year() - from.year();
+    if (until.month(), until.day()) < (from.month(), from.day()) {
+        years -= 1;
+    }
+    Ok(Value::from(years.max(0)))
+}
+
+pub fn pluralize(args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let count = match args.get("count") {
+        Some(count) => try_get_value!("pluralize", "count", f64, count),
+        None => return Err(tera::Error::msg("Function `pluralize` requires a `count`")),
+    };
+    let singular = match args.get("singular") {
+        Some(singular) => try_get_value!("pluralize", "singular", String, singular),
+        None => {
+            return Err(tera::Error::msg(
+                "Function `pluralize` requires a `singular`",
+            ))
+        }
+    };
+    let plural = match args.get("plural") {
+        Some(plural) => try_get_value!("pluralize", "plural", String, plural),
+        None => format!("{}s", singular),
+    };
+    Ok(Value::String(if count == 1.0 { singular } else { plural }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bad_today_formats_are_errors() {
+        let args = |format: &str| HashMap::from([("format".to_string(), Value::from(format))]);
+        let year = Local::now().year().to_string();
+        assert_eq!(today(&args("%Y")).unwrap(), Value::from(year));
+        assert!(today(&args("%Q")).is_err());
+    }
+}
@@ -0,0 +1,55 @@
+//! Where the data file stands in git, so a build can say which commit it came
+//! from and whether the data had moved on since.
+
+use std::path::Path;
+use std::process::Command;
+
+use schemars::JsonSchema;
+use serde::Serialize;
+
+/// The commit checked out where the data file lives.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct GitInfo {
+    /// None on a detached HEAD
+    pub branch: Option<String>,
+    pub sha: String,
+    /// The first seven characters of `sha`
+    pub short_sha: String,
+    /// Whether the data file has uncommitted changes or isn't committed at all
+    pub dirty: bool,
+}
+
+/// The commit and state of the data file at `data_path`; none when it isn't
+/// in a git repository, or git isn't installed.
+pub fn git_info(data_path: &Path) -> Option<GitInfo> {
+    let dir = match data_path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let git = |args: &[&str]| -> Option<String> {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(args)
+            .output()
+            .ok()?;
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    };
+    let sha = git(&["rev-parse", "HEAD"])?;
+    let branch = git(&["rev-parse", "--abbrev-ref", "HEAD"]).filter(|branch| branch != "HEAD");
+    let status = git(&[
+        "status",
+        "--porcelain",
+        "--",
+        data_path.file_name()?.to_str()?,
+    ])?;
+    Some(GitInfo {
+        branch,
+        short_sha: sha.chars().take(7).collect(),
+        sha,
+        dirty: !status.is_empty(),
+    })
+}
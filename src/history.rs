@@ -0,0 +1,135 @@
+//! `rsume tag`: named revisions of a data file, kept in a history file beside
+//! it, so a printed copy stamped with its tag leads back to the exact data.
+
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::NaiveDate;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use toml::value::Datetime;
+
+use crate::model::datetime_to_date;
+use crate::{Error, Result};
+
+/// Kept next to the data file, one `[[revisions]]` table per tag.
+pub const HISTORY_FILE: &str = "rsume-history.toml";
+
+/// A tagged revision of the data.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Revision {
+    /// E.g. `v2025-05`
+    pub tag: String,
+    /// When it was tagged, rendered as `YYYY-MM-DD`
+    #[serde(deserialize_with = "datetime_to_date")]
+    #[schemars(with = "String")]
+    pub date: NaiveDate,
+    /// SHA-256 of the data file, in hex
+    pub hash: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    /// Whether the data is unchanged since it was tagged
+    #[serde(default, skip_deserializing)]
+    pub current: bool,
+}
+
+#[derive(Deserialize)]
+struct History {
+    #[serde(default)]
+    revisions: Vec<Revision>,
+}
+
+#[derive(Serialize)]
+struct Entry<'a> {
+    revisions: [EntryRevision<'a>; 1],
+}
+
+#[derive(Serialize)]
+struct EntryRevision<'a> {
+    tag: &'a str,
+    date: Datetime,
+    hash: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    note: Option<&'a str>,
+}
+
+/// The history file for the data file at `data_path`.
+fn history_path(data_path: &Path) -> PathBuf {
+    data_path.with_file_name(HISTORY_FILE)
+}
+
+/// SHA-256 of `source`, in hex.
+fn data_hash(source: &str) -> String {
+    Sha256::digest(source.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// The revisions recorded for the data file at `data_path`, oldest first;
+/// none without a history file.
+fn revisions(data_path: &Path) -> Result<Vec<Revision>> {
+    let path = history_path(data_path);
+    let source = match fs::read_to_string(&path) {
+        Ok(source) => source,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(source) => return Err(Error::Read { path, source }),
+    };
+    let history: History = toml::from_str(&source).map_err(|source| Error::History {
+        path,
+        source: Box::new(source),
+    })?;
+    Ok(history.revisions)
+}
+
+/// The newest revision of the data file at `data_path`, marked current when
+/// `source`, its contents, still match.
+pub fn latest_revision(data_path: &Path, source: &str) -> Result<Option<Revision>> {
+    let mut latest = revisions(data_path)?.pop();
+    if let Some(revision) = &mut latest {
+        revision.current = revision.hash == data_hash(source);
+    }
+    Ok(latest)
+}
+
+/// Records `source`, the contents of the data file at `data_path`, as revision
+/// `tag`, returning the history file's path.
+pub fn tag_revision(
+    data_path: &Path,
+    source: &str,
+    tag: &str,
+    note: Option<&str>,
+) -> Result<PathBuf> {
+    if revisions(data_path)?
+        .iter()
+        .any(|revision| revision.tag == tag)
+    {
+        return Err(Error::TagExists(tag.to_string()));
+    }
+    let entry = Entry {
+        revisions: [EntryRevision {
+            tag,
+            date: chrono::Local::now()
+                .date_naive()
+                .to_string()
+                .parse()
+                .expect("dates format as YYYY-MM-DD"),
+            hash: data_hash(source),
+            note,
+        }],
+    };
+    let entry = toml::to_string(&entry).expect("revisions always serialize");
+    let path = history_path(data_path);
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| writeln!(file, "{}", entry))
+        .map_err(|source| Error::Write {
+            path: path.clone(),
+            source,
+        })?;
+    Ok(path)
+}
@@ -0,0 +1,84 @@
+//! Privacy controls that only apply to HTML output, which ends up on the public
+//! web rather than in a PDF sent to one reader.
+
+use tera::Value;
+
+use crate::markdown::escape_html;
+use crate::{EmailObfuscation, Html};
+
+/// Whether a template produces HTML rather than LaTeX, going by its name.
+pub fn is_html(template_name: &str) -> bool {
+    template_name.ends_with(".html") || template_name.ends_with(".htm")
+}
+
+/// Strips what the `[html]` options hide and adds `email_link`, a ready-made
+/// (and, if asked for, obfuscated) mail link to mark `safe`, and the same as
+/// `link` on each of `emails`. When obfuscating, the addresses themselves are
+/// emptied so a template can't leak them by accident.
+pub(crate) fn apply(context: &mut Value, options: &Html) {
+    let obfuscate = options.obfuscate_email != EmailObfuscation::None;
+    let email = context["email"].as_str().unwrap_or_default().to_string();
+    if !email.is_empty() {
+        context["email_link"] = email_link(&email, options.obfuscate_email).into();
+        if obfuscate {
+            context["email"] = "".into();
+        }
+    }
+    if let Some(emails) = context["emails"].as_array_mut() {
+        for email in emails {
+            let address = email["value"].as_str().unwrap_or_default().to_string();
+            email["link"] = email_link(&address, options.obfuscate_email).into();
+            if obfuscate {
+                email["value"] = "".into();
+            }
+        }
+    }
+    if options.hide_phone {
+        context["phone"] = "".into();
+        context["phones"] = Value::Array(Vec::new());
+    }
+    if options.hide_address {
+        context["location"]["address"] = Value::Null;
+        context["location"]["postal_code"] = Value::Null;
+    }
+}
+
+fn email_link(email: &str, obfuscation: EmailObfuscation) -> String {
+    match obfuscation {
+        EmailObfuscation::None => {
+            let email = escape_html(email);
+            format!(
+                "<a class=\"u-email\" itemprop=\"email\" href=\"mailto:{0}\">{0}</a>",
+                email
+            )
+        }
+        // Every character as a numeric entity: browsers show it normally, naive
+        // scrapers don't see an address.
+        EmailObfuscation::Entities => {
+            let encode = |text: &str| -> String {
+                text.chars().map(|c| format!("&#{};", c as u32)).collect()
+            };
+            format!(
+                "<a class=\"u-email\" href=\"{}\">{}</a>",
+                encode(&format!("mailto:{}", email)),
+                encode(email)
+            )
+        }
+        // The address only exists once a script has put it together.
+        EmailObfuscation::Script => {
+            let (user, domain) = email.split_once('@').unwrap_or((email, ""));
+            let reverse = |text: &str| escape_html(&text.chars().rev().collect::<String>());
+            format!(
+                concat!(
+                    "<span data-u=\"{}\" data-d=\"{}\">(enable JavaScript to see the email address)</span>",
+                    "<script>(function(s){{var r=function(t){{return t.split('').reverse().join('')}},",
+                    "e=r(s.dataset.u)+'@'+r(s.dataset.d),a=document.createElement('a');",
+                    "a.className='u-email';a.href='mailto:'+e;a.textContent=e;s.replaceWith(a)",
+                    "}})(document.currentScript.previousElementSibling)</script>"
+                ),
+                reverse(user),
+                reverse(domain)
+            )
+        }
+    }
+}
@@ -0,0 +1,64 @@
+//! The `babel` language for the document's `language`, so LaTeX templates
+//! hyphenate with the right patterns.
+
+/// BCP 47 tags the bundled templates can hyphenate, with the `babel` names
+/// they load. A region-specific tag is looked up before its bare language.
+/// Only Latin-script languages, since the templates' fonts cover no others.
+pub const BABEL_LANGUAGES: &[(&str, &str)] = &[
+    ("ca", "catalan"),
+    ("cs", "czech"),
+    ("da", "danish"),
+    ("de", "ngerman"),
+    ("de-AT", "naustrian"),
+    ("de-CH", "nswissgerman"),
+    ("en", "english"),
+    ("en-AU", "australian"),
+    ("en-CA", "canadian"),
+    ("en-GB", "british"),
+    ("en-NZ", "newzealand"),
+    ("en-US", "american"),
+    ("es", "spanish"),
+    ("et", "estonian"),
+    ("eu", "basque"),
+    ("fi", "finnish"),
+    ("fr", "french"),
+    ("fr-CA", "canadien"),
+    ("ga", "irish"),
+    ("gl", "galician"),
+    ("hr", "croatian"),
+    ("hu", "magyar"),
+    ("id", "bahasai"),
+    ("is", "icelandic"),
+    ("it", "italian"),
+    ("lt", "lithuanian"),
+    ("lv", "latvian"),
+    ("nb", "norsk"),
+    ("nl", "dutch"),
+    ("nn", "nynorsk"),
+    ("no", "norsk"),
+    ("pl", "polish"),
+    ("pt", "portuguese"),
+    ("pt-BR", "brazilian"),
+    ("ro", "romanian"),
+    ("sk", "slovak"),
+    ("sl", "slovene"),
+    ("sv", "swedish"),
+    ("tr", "turkish"),
+];
+
+/// The `babel` name for `tag`, matching its language and region case-insensitively.
+pub(crate) fn babel_language(tag: &str) -> Option<&'static str> {
+    let mut subtags = tag.split('-');
+    let language = subtags.next().unwrap_or_default();
+    // The region is the first two-letter subtag after the language and any script.
+    let region = subtags.find(|subtag| subtag.len() == 2);
+    let find = |wanted: &str| {
+        BABEL_LANGUAGES
+            .iter()
+            .find(|(tag, _)| tag.eq_ignore_ascii_case(wanted))
+            .map(|(_, name)| *name)
+    };
+    region
+        .and_then(|region| find(&format!("{}-{}", language, region)))
+        .or_else(|| find(language))
+}
@@ -0,0 +1,181 @@
+//! Conversion of work history kept elsewhere into `[[experiences]]` tables.
+
+use toml::value::{Datetime, Table};
+use toml::Value;
+
+use crate::{Error, Result};
+
+/// Column names `experiences_from_csv` understands, each with its alternatives.
+const COLUMNS: &[(&str, &[&str])] = &[
+    ("employer", &["company"]),
+    ("title", &["position", "role"]),
+    ("start", &["start date"]),
+    ("end", &["end date"]),
+    ("bullets", &["highlights"]),
+    ("department", &["team"]),
+    ("location", &[]),
+    ("website", &["url"]),
+    ("technologies", &["tech"]),
+];
+
+/// Converts a spreadsheet of roles, exported as CSV, to TOML `[[experiences]]`
+/// tables ready to paste into a data file.
+///
+/// The header row names the columns, in any order and case; unknown ones are
+/// ignored. `employer` (or `company`), `title` (or `position`, `role`) and
+/// `start` are required. `end` is left empty or `present` for a current role;
+/// dates are `YYYY-MM-DD`, `YYYY-MM` or `YYYY`. `bullets` holds one highlight
+/// per line of the cell, `technologies` a comma-separated list, and
+/// `department`, `location` and `website` are copied as they are.
+pub fn experiences_from_csv(source: &str) -> Result<String> {
+    let mut rows = parse_csv(source)?.into_iter();
+    let Some((_, header)) = rows.next() else {
+        return Ok(String::new());
+    };
+    let columns: Vec<Option<&str>> = header
+        .iter()
+        .map(|name| {
+            let name = name.trim().to_lowercase();
+            COLUMNS
+                .iter()
+                .find(|(column, aliases)| *column == name || aliases.contains(&name.as_str()))
+                .map(|(column, _)| *column)
+        })
+        .collect();
+    for required in ["employer", "title", "start"] {
+        if !columns.contains(&Some(required)) {
+            return Err(Error::Import {
+                line: 1,
+                message: format!("the header has no `{}` column", required),
+            });
+        }
+    }
+
+    let mut experiences = Vec::new();
+    for (line, row) in rows {
+        if row.iter().all(|cell| cell.trim().is_empty()) {
+            continue;
+        }
+        let cell = |name: &str| {
+            columns
+                .iter()
+                .position(|column| *column == Some(name))
+                .and_then(|i| row.get(i))
+                .map_or("", |cell| cell.trim())
+        };
+        let error = |message: String| Error::Import { line, message };
+        for required in ["employer", "title", "start"] {
+            if cell(required).is_empty() {
+                return Err(error(format!("`{}` is empty", required)));
+            }
+        }
+        let start = parse_date(cell("start"))
+            .ok_or_else(|| error(format!("`{}` isn't a date like 2020-01-31", cell("start"))))?;
+        let end = match cell("end") {
+            "" => None,
+            end if end.eq_ignore_ascii_case("present") => None,
+            end => Some(
+                parse_date(end)
+                    .ok_or_else(|| error(format!("`{}` isn't a date like 2020-01-31", end)))?,
+            ),
+        };
+
+        let mut company = Table::new();
+        company.insert("name".into(), cell("employer").into());
+        company.insert("location".into(), cell("location").into());
+        let mut experience = Table::new();
+        experience.insert("position".into(), cell("title").into());
+        experience.insert("department".into(), cell("department").into());
+        experience.insert("website".into(), cell("website").into());
+        experience.insert("start_date".into(), Value::Datetime(start));
+        experience.insert("current".into(), end.is_none().into());
+        if let Some(end) = end {
+            experience.insert("end_date".into(), Value::Datetime(end));
+        }
+        experience.insert("display".into(), Value::Array(Vec::new()));
+        experience.insert(
+            "highlights".into(),
+            cell("bullets")
+                .lines()
+                .map(|bullet| bullet.trim().trim_start_matches(['-', '•', '*']).trim())
+                .filter(|bullet| !bullet.is_empty())
+                .map(Value::from)
+                .collect::<Vec<_>>()
+                .into(),
+        );
+        let technologies: Vec<Value> = cell("technologies")
+            .split(',')
+            .map(str::trim)
+            .filter(|technology| !technology.is_empty())
+            .map(Value::from)
+            .collect();
+        if !technologies.is_empty() {
+            experience.insert("technologies".into(), technologies.into());
+        }
+        experience.insert("company".into(), Value::Table(company));
+        experiences.push(Value::Table(experience));
+    }
+
+    let mut document = Table::new();
+    document.insert("experiences".into(), Value::Array(experiences));
+    Ok(toml::to_string(&document).expect("TOML values always serialize"))
+}
+
+/// `YYYY-MM-DD`, `YYYY-MM` (the first of the month) or `YYYY` (January 1st).
+fn parse_date(text: &str) -> Option<Datetime> {
+    let parts: Vec<&str> = text.split('-').collect();
+    let date = match parts[..] {
+        [year, month, day] => format!("{}-{:0>2}-{:0>2}", year, month, day),
+        [year, month] => format!("{}-{:0>2}-01", year, month),
+        [year] => format!("{}-01-01", year),
+        _ => return None,
+    };
+    date.parse().ok()
+}
+
+/// RFC 4180 CSV: quoted fields may hold commas, newlines and doubled quotes.
+/// Each row comes with the line it starts on.
+fn parse_csv(source: &str) -> Result<Vec<(usize, Vec<String>)>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut line = 1;
+    let mut row_line = 1;
+    let mut chars = source.trim_start_matches('\u{feff}').chars().peekable();
+    while let Some(c) = chars.next() {
+        match (quoted, c) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            (true, '"') => quoted = false,
+            (false, '"') if field.is_empty() => quoted = true,
+            (false, ',') => row.push(std::mem::take(&mut field)),
+            (false, '\r') if chars.peek() == Some(&'\n') => {}
+            (false, '\n') => {
+                line += 1;
+                row.push(std::mem::take(&mut field));
+                rows.push((row_line, std::mem::take(&mut row)));
+                row_line = line;
+            }
+            (_, c) => {
+                if c == '\n' {
+                    line += 1;
+                }
+                field.push(c);
+            }
+        }
+    }
+    if quoted {
+        return Err(Error::Import {
+            line: row_line,
+            message: "a quoted field is never closed".to_string(),
+        });
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push((row_line, row));
+    }
+    Ok(rows)
+}
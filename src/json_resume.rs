@@ -0,0 +1,136 @@
+//! Export to the [JSON Resume](https://jsonresume.org/schema/) format.
+
+use serde_json::{json, Map, Value};
+
+use crate::{ContactDetail, EmailObfuscation, ReferenceVisibility, Resume};
+
+/// The file name the export is published under, next to the HTML page.
+pub const JSON_RESUME_FILE: &str = "resume.json";
+
+impl Resume {
+    /// The data as a JSON Resume document. It is published alongside the HTML
+    /// page, so the same `[html]` privacy options, `hide_gpa` and reference
+    /// visibility apply; unset and empty fields are left out.
+    pub fn to_json_resume(&self) -> Value {
+        let author = &self.author;
+        let html = &author.html;
+        let location = &author.location;
+        let mut profiles: Vec<_> = author.social.iter().collect();
+        profiles.sort_by_key(|(network, _)| *network);
+
+        let document = json!({
+            "$schema": "https://raw.githubusercontent.com/jsonresume/resume-schema/v1.0.0/schema.json",
+            "basics": {
+                "name": author.name,
+                "label": author.description,
+                "email": if html.obfuscate_email == EmailObfuscation::None { first(&author.email) } else { "" },
+                "phone": if html.hide_phone { "" } else { first(&author.phone) },
+                "url": author.website,
+                "image": author.picture.as_ref().map(|picture| &picture.path),
+                "summary": author.summary,
+                "location": {
+                    "address": location.address.as_ref().filter(|_| !html.hide_address),
+                    "postalCode": location.postal_code.as_ref().filter(|_| !html.hide_address),
+                    "city": location.city,
+                    "countryCode": location.country_code,
+                    "region": location.region,
+                },
+                "profiles": profiles.into_iter().map(|(network, social)| json!({
+                    "network": network,
+                    "username": social.username,
+                    "url": social.url,
+                })).collect::<Vec<_>>(),
+            },
+            // JSON Resume has no promotions, so earlier roles are entries of their own.
+            "work": author.experiences.iter().flat_map(|experience| {
+                let company = experience.company.display_name.as_ref().unwrap_or(&experience.company.name);
+                let latest = json!({
+                    "name": company,
+                    "location": experience.company.location,
+                    "position": experience.position,
+                    "url": experience.website,
+                    "startDate": experience.start_date,
+                    "endDate": experience.end_date,
+                    "highlights": experience.highlights,
+                });
+                std::iter::once(latest).chain(experience.roles.iter().map(move |role| json!({
+                    "name": company,
+                    "location": experience.company.location,
+                    "position": role.position,
+                    "url": experience.website,
+                    "startDate": role.start_date,
+                    "endDate": role.end_date,
+                })))
+            }).collect::<Vec<_>>(),
+            "education": author.educations.iter().map(|education| json!({
+                "institution": education.institution,
+                "url": education.website,
+                "area": education.major,
+                "studyType": education.degree,
+                "startDate": education.start_date,
+                "endDate": education.end_date,
+                "score": education.gpa.as_ref()
+                    .filter(|_| !author.hide_gpa)
+                    .map(|gpa| format!("{}/{}", gpa.overall, gpa.scale)),
+                "courses": education.courses,
+            })).collect::<Vec<_>>(),
+            "skills": author.skills.iter().map(|skill| json!({
+                "name": skill.name,
+                "level": skill.level,
+                "keywords": skill.keywords,
+            })).collect::<Vec<_>>(),
+            "languages": author.languages.iter().map(|language| json!({
+                "language": language.language,
+                "fluency": language.fluency,
+            })).collect::<Vec<_>>(),
+            "projects": author.projects.iter().map(|project| json!({
+                "name": project.name,
+                "description": project.description,
+                "url": if project.website.is_empty() { &project.source } else { &project.website },
+            })).collect::<Vec<_>>(),
+            "publications": author.publications.iter().map(|publication| json!({
+                "name": publication.title,
+                "publisher": publication.venue,
+                "releaseDate": publication.date,
+                "url": publication.url.clone()
+                    .or_else(|| publication.doi.as_ref().map(|doi| format!("https://doi.org/{}", doi))),
+            })).collect::<Vec<_>>(),
+            "interests": author.interests.iter().map(|interest| json!({
+                "name": interest.name,
+                "keywords": interest.keywords,
+            })).collect::<Vec<_>>(),
+            "references": author.references.iter()
+                .filter(|_| author.references_visibility == ReferenceVisibility::Full)
+                .map(|reference| json!({
+                    "name": reference.name,
+                    "reference": reference.relationship,
+                })).collect::<Vec<_>>(),
+        });
+        prune(document).unwrap_or_default()
+    }
+}
+
+/// Drops nulls, empty strings and whatever becomes empty without them.
+fn prune(value: Value) -> Option<Value> {
+    match value {
+        Value::Null => None,
+        Value::String(s) if s.is_empty() => None,
+        Value::Array(items) => {
+            let items: Vec<_> = items.into_iter().filter_map(prune).collect();
+            (!items.is_empty()).then_some(Value::Array(items))
+        }
+        Value::Object(fields) => {
+            let fields: Map<_, _> = fields
+                .into_iter()
+                .filter_map(|(key, value)| Some((key, prune(value)?)))
+                .collect();
+            (!fields.is_empty()).then_some(Value::Object(fields))
+        }
+        value => Some(value),
+    }
+}
+
+/// JSON Resume has room for one address and one number: the first.
+fn first(details: &[ContactDetail]) -> &str {
+    details.first().map_or("", |detail| detail.value.as_str())
+}
@@ -0,0 +1,93 @@
+//! Render resume data through Tera templates and compile the result with Tectonic.
+
+mod accessibility;
+mod anonymize;
+mod archive;
+mod assets;
+#[cfg(feature = "async")]
+mod asynchronous;
+#[cfg(feature = "gravatar")]
+mod avatar;
+mod builtin;
+#[cfg(feature = "pdf")]
+mod bundle;
+mod cache;
+#[cfg(feature = "pdf")]
+mod compile;
+mod contact;
+mod contrast;
+pub mod docs;
+mod emoji;
+mod engagements;
+mod error;
+mod functions;
+mod git;
+mod history;
+mod html;
+mod hyphenation;
+mod import;
+mod json_resume;
+mod limits;
+mod links;
+mod lint;
+mod location;
+mod manifest;
+mod markdown;
+mod migrate;
+mod model;
+#[cfg(feature = "optimize")]
+mod optimize;
+#[cfg(any(feature = "optimize", feature = "sign"))]
+mod pdf;
+#[cfg(feature = "diff-pdf")]
+mod pdfdiff;
+#[cfg(feature = "refresh")]
+mod refresh;
+mod render;
+#[cfg(feature = "sign")]
+mod sign;
+mod skills;
+pub mod sourcemap;
+mod stats;
+#[cfg(test)]
+mod testing;
+mod timeline;
+mod variant;
+
+pub use anonymize::CANDIDATE;
+pub use archive::{EarlierExperience, EarlierExperienceSummary};
+pub use assets::{Asset, MAX_PICTURE_SIZE};
+#[cfg(feature = "async")]
+pub use asynchronous::AsyncCompiler;
+pub use builtin::{Builtin, Partial, BUILTINS, PARTIALS};
+#[cfg(feature = "pdf")]
+pub use bundle::{export_bundle, import_bundle, vendored_bundle_dir};
+pub use cache::{cache_dir, CACHE_DIR_VAR};
+#[cfg(feature = "pdf")]
+pub use compile::{compile_pdf, Cancel, CompileOptions, Compiled, Compiler};
+pub use engagements::EngagementEntry;
+pub use error::{Error, Result};
+pub use git::{git_info, GitInfo};
+pub use history::{latest_revision, tag_revision, Revision, HISTORY_FILE};
+pub use html::is_html;
+pub use hyphenation::BABEL_LANGUAGES;
+pub use import::experiences_from_csv;
+pub use json_resume::JSON_RESUME_FILE;
+pub use limits::Limits;
+pub use lint::{Finding, LintOptions, LINT_RULES, MAX_GRADE};
+pub use manifest::{Engine, Manifest, Pass, MANIFEST_FILE};
+pub use migrate::{migrate, Migration, SCHEMA_VERSION};
+pub use model::*;
+#[cfg(feature = "optimize")]
+pub use optimize::{optimize_pdf, Optimized};
+#[cfg(feature = "diff-pdf")]
+pub use pdfdiff::{diff_pdfs, DiffMode, DiffOptions, PageDiff};
+#[cfg(feature = "refresh")]
+pub use refresh::{refresh_stats, Refresh, GITHUB_TOKEN_VAR};
+pub use render::{
+    escape_latex_str, template_dir, FilterDoc, FunctionDoc, Templates, FILTERS, FUNCTIONS,
+};
+#[cfg(feature = "sign")]
+pub use sign::{sign_pdf, Signer};
+pub use skills::{SkillSuggestion, SkillSuggestions};
+pub use stats::{Readability, RoleStats, SectionStats, Stats};
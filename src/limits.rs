@@ -0,0 +1,340 @@
+//! Bounds for building data that arrives from people who shouldn't be able to
+//! exhaust or read the host, such as a build service behind a web form.
+
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use serde_json::Value;
+
+use crate::{template_dir, Error, Result, Resume};
+
+/// Characters that start TeX markup wherever they appear.
+const MARKUP: &[char] = &['\\', '{', '}', '^'];
+
+/// Characters TeX reads as markup in text, but hyperref takes literally in
+/// URLs and email addresses.
+const TEXT_MARKUP: &[char] = &['~', '_'];
+
+#[derive(Debug, Clone)]
+pub struct Limits {
+    /// Largest data file in bytes
+    pub max_data_size: u64,
+    /// Largest rendered template in bytes
+    pub max_output_size: u64,
+    /// Directory the data, templates and output must be in
+    pub root: PathBuf,
+    /// Times to compile again after a failure that tends to go away on its own
+    pub retries: u32,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_data_size: 1024 * 1024,
+            max_output_size: 4 * 1024 * 1024,
+            root: PathBuf::from("."),
+            retries: 1,
+        }
+    }
+}
+
+impl Limits {
+    /// Checks the data file at `path` by its size on disk, before it is read.
+    pub fn check_data_file(&self, path: &Path) -> Result<()> {
+        let size = fs::metadata(path)
+            .map_err(|source| Error::Read {
+                path: path.to_path_buf(),
+                source,
+            })?
+            .len();
+        check("the data file", size, self.max_data_size)
+    }
+
+    pub fn check_data(&self, source: &str) -> Result<()> {
+        check("the data", source.len() as u64, self.max_data_size)
+    }
+
+    pub fn check_output(&self, name: &str, rendered: &str) -> Result<()> {
+        check(
+            &format!("rendered template {}", name),
+            rendered.len() as u64,
+            self.max_output_size,
+        )
+    }
+
+    /// Fails if `path`, with symlinks and `..` resolved, lies outside `root`.
+    /// The part of `path` that doesn't exist yet, such as a new output
+    /// directory, is taken as written.
+    pub fn check_path(&self, field: &str, path: &Path) -> Result<()> {
+        match (resolve(&self.root), resolve(path)) {
+            (Some(root), Some(resolved)) if resolved.starts_with(&root) => Ok(()),
+            _ => Err(Error::OutsideRoot {
+                field: field.to_string(),
+                path: path.to_path_buf(),
+                root: self.root.clone(),
+            }),
+        }
+    }
+
+    /// Like [`check_path`](Limits::check_path) for the directory a template
+    /// glob is rooted at. The rest of the glob is only expanded as the
+    /// templates load, so it can't use `..` at all.
+    pub fn check_template_path(&self, template_path: &Path) -> Result<()> {
+        let dir = template_dir(template_path);
+        let glob = template_path.strip_prefix(&dir).unwrap_or(template_path);
+        if glob.components().any(|c| c == Component::ParentDir) {
+            return Err(Error::OutsideRoot {
+                field: "template_path".to_string(),
+                path: template_path.to_path_buf(),
+                root: self.root.clone(),
+            });
+        }
+        self.check_path("template_path", &dir)
+    }
+}
+
+/// `path` made absolute, resolving as much of it as exists.
+fn resolve(path: &Path) -> Option<PathBuf> {
+    let mut existing = path;
+    let mut rest = Vec::new();
+    loop {
+        let dir = if existing.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            existing
+        };
+        if let Ok(resolved) = fs::canonicalize(dir) {
+            return Some(
+                rest.into_iter()
+                    .rev()
+                    .fold(resolved, |path, name| path.join(name)),
+            );
+        }
+        // `None` for a `..` past a directory that doesn't exist.
+        rest.push(existing.file_name()?);
+        existing = existing.parent()?;
+    }
+}
+
+fn check(what: &str, size: u64, limit: u64) -> Result<()> {
+    if size > limit {
+        return Err(Error::TooLarge {
+            what: what.to_string(),
+            size,
+            limit,
+        });
+    }
+    Ok(())
+}
+
+impl Resume {
+    /// Fails if a file the data refers to (the picture, company logos, font
+    /// files) lies outside `base`, the data file's directory, so the data can't
+    /// embed other files on the host in its output. Call before
+    /// [`stage_assets`](Resume::stage_assets).
+    pub fn confine_files(&self, base: &Path) -> Result<()> {
+        let author = &self.author;
+        let mut paths = Vec::new();
+        if let Some(picture) = &author.picture {
+            paths.push(("picture".to_string(), &picture.path));
+        }
+        for (i, experience) in author.experiences.iter().enumerate() {
+            if let Some(logo) = &experience.company.logo {
+                paths.push((format!("experiences[{}].company.logo", i), &logo.path));
+            }
+        }
+        for (i, file) in author.theme.font_files.iter().enumerate() {
+            paths.push((format!("theme.font_files[{}]", i), file));
+        }
+        for (field, path) in paths {
+            if !within(base, Path::new(path)) {
+                return Err(Error::OutsideFile {
+                    field,
+                    path: path.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Fails if a string in the data has a character TeX reads as markup
+    /// rather than text, so the data can't add TeX code of its own to the
+    /// output: escaping covers only `&`, `%`, `#` and `$`, and templates
+    /// print URLs and names unescaped.
+    pub fn reject_markup(&self) -> Result<()> {
+        let data = serde_json::to_value(&self.author).unwrap_or_default();
+        reject_markup(String::new(), &data)
+    }
+}
+
+fn reject_markup(field: String, value: &Value) -> Result<()> {
+    match value {
+        Value::String(text) => {
+            let link = text.contains("://")
+                || text.starts_with("mailto:")
+                || (text.contains('@') && !text.contains(char::is_whitespace));
+            let markup = |c: &char| MARKUP.contains(c) || (!link && TEXT_MARKUP.contains(c));
+            match text.chars().find(markup) {
+                Some(character) => Err(Error::TexMarkup { field, character }),
+                None => Ok(()),
+            }
+        }
+        Value::Array(items) => items
+            .iter()
+            .enumerate()
+            .try_for_each(|(i, item)| reject_markup(format!("{}[{}]", field, i), item)),
+        Value::Object(fields) => fields.iter().try_for_each(|(name, value)| {
+            let path = if field.is_empty() {
+                name.clone()
+            } else {
+                format!("{}.{}", field, name)
+            };
+            // Keys such as `social` site names are printed too.
+            if let Some(character) = name.chars().find(|c| MARKUP.contains(c)) {
+                return Err(Error::TexMarkup {
+                    field: path,
+                    character,
+                });
+            }
+            reject_markup(path, value)
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Whether `path`, relative to `base`, stays inside it, following symlinks
+/// as far as the path exists.
+fn within(base: &Path, path: &Path) -> bool {
+    let lexically = path
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+    if !lexically {
+        return false;
+    }
+    match (resolve(base), resolve(&base.join(path))) {
+        (Some(base), Some(path)) => path.starts_with(base),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+
+    fn author(extra: &str) -> Resume {
+        let mut resume = testing::resume(extra, &["Cut build times by 40% & more"]);
+        resume.author.website = "https://example.com/~jane_doe".to_string();
+        resume
+    }
+
+    fn markup(resume: &Resume) -> Option<(String, char)> {
+        match resume.reject_markup() {
+            Ok(()) => None,
+            Err(Error::TexMarkup { field, character }) => Some((field, character)),
+            Err(e) => panic!("unexpected error {}", e),
+        }
+    }
+
+    #[test]
+    fn plain_data_and_links_pass() {
+        assert_eq!(markup(&author("")), None);
+        assert_eq!(markup(&author(r#"summary = "jane_doe@example.com""#)), None);
+    }
+
+    #[test]
+    fn tex_markup_is_refused() {
+        assert_eq!(
+            markup(&author(r#"summary = "\\input{/etc/passwd}""#)),
+            Some(("summary".to_string(), '\\'))
+        );
+        assert_eq!(
+            markup(&author(r#"summary = "^^5cinput""#)),
+            Some(("summary".to_string(), '^'))
+        );
+        assert_eq!(
+            markup(&author(r#"summary = "snake_case in text""#)),
+            Some(("summary".to_string(), '_'))
+        );
+        let mut resume = author("");
+        resume.author.experiences[0].company.name = "Acme}".to_string();
+        assert_eq!(
+            markup(&resume),
+            Some(("experiences[0].company.name".to_string(), '}'))
+        );
+    }
+
+    #[test]
+    fn links_still_refuse_braces_and_backslashes() {
+        assert_eq!(
+            markup(&author(r#"summary = "https://example.com/}\\def""#)),
+            Some(("summary".to_string(), '}'))
+        );
+    }
+
+    #[test]
+    fn paths_must_resolve_inside_the_root() {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir(root.path().join("data")).unwrap();
+        let limits = Limits {
+            root: root.path().to_path_buf(),
+            ..Limits::default()
+        };
+        let inside = |path: &str| limits.check_path("output_dir", &root.path().join(path));
+        assert!(inside("data").is_ok());
+        assert!(inside("data/new/out").is_ok());
+        assert!(inside("data/../data/new").is_ok());
+        // The root itself.
+        assert!(inside("data/..").is_ok());
+        assert!(matches!(
+            inside("data/../.."),
+            Err(Error::OutsideRoot { .. })
+        ));
+        assert!(inside("new/../../escape").is_err());
+        assert!(limits
+            .check_path("input_path", Path::new("/etc/passwd"))
+            .is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_out_of_the_root_are_refused() {
+        let root = tempfile::tempdir().unwrap();
+        let elsewhere = tempfile::tempdir().unwrap();
+        std::os::unix::fs::symlink(elsewhere.path(), root.path().join("link")).unwrap();
+        let limits = Limits {
+            root: root.path().to_path_buf(),
+            ..Limits::default()
+        };
+        assert!(limits
+            .check_path("output_dir", &root.path().join("link/out"))
+            .is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn files_that_dont_exist_yet_still_follow_symlinks() {
+        let base = tempfile::tempdir().unwrap();
+        let elsewhere = tempfile::tempdir().unwrap();
+        std::os::unix::fs::symlink(elsewhere.path(), base.path().join("link")).unwrap();
+        assert!(within(base.path(), Path::new("pictures/me.jpg")));
+        assert!(!within(base.path(), Path::new("link/me.jpg")));
+        assert!(!within(base.path(), Path::new("../me.jpg")));
+    }
+
+    #[test]
+    fn template_globs_cant_climb_out() {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir(root.path().join("templates")).unwrap();
+        let limits = Limits {
+            root: root.path().to_path_buf(),
+            ..Limits::default()
+        };
+        let templates = root.path().join("templates");
+        assert!(limits.check_template_path(&templates.join("*.tex")).is_ok());
+        assert!(limits
+            .check_template_path(&templates.join("**/../../../*.tex"))
+            .is_err());
+        assert!(limits.check_template_path(Path::new("/etc/*.tex")).is_err());
+    }
+}
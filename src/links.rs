@@ -0,0 +1,133 @@
+//! URLs in the data, checked and completed at load so templates never get a
+//! broken `\href` target.
+
+use url::Url;
+
+use crate::{Author, Error, Result};
+
+impl Author {
+    /// Adds `https://` to every URL written without a scheme, e.g.
+    /// `github.com/jane`, and rejects ones that still don't parse.
+    pub(crate) fn normalize_urls(&mut self) -> Result<()> {
+        normalize("website", &mut self.website)?;
+        if let Some(audio) = self
+            .name_pronunciation
+            .as_mut()
+            .and_then(|pronunciation| pronunciation.audio.as_mut())
+        {
+            normalize("name_pronunciation.audio", audio)?;
+        }
+        let mut sites: Vec<_> = self.social.iter_mut().collect();
+        sites.sort_by(|a, b| a.0.cmp(b.0));
+        for (site, profile) in sites {
+            normalize(&format!("social.{}.url", site), &mut profile.url)?;
+        }
+        for (i, e) in self.experiences.iter_mut().enumerate() {
+            normalize(&format!("experiences[{}].website", i), &mut e.website)?;
+        }
+        for (i, e) in self.educations.iter_mut().enumerate() {
+            normalize(&format!("educations[{}].website", i), &mut e.website)?;
+        }
+        for (i, p) in self.projects.iter_mut().enumerate() {
+            normalize(&format!("projects[{}].website", i), &mut p.website)?;
+            normalize(&format!("projects[{}].source", i), &mut p.source)?;
+        }
+        for (i, p) in self.publications.iter_mut().enumerate() {
+            if let Some(url) = &mut p.url {
+                normalize(&format!("publications[{}].url", i), url)?;
+            }
+        }
+        for (i, p) in self.patents.iter_mut().enumerate() {
+            if let Some(url) = &mut p.url {
+                normalize(&format!("patents[{}].url", i), url)?;
+            }
+        }
+        for (i, t) in self.talks.iter_mut().enumerate() {
+            for (name, url) in [("slides", &mut t.slides), ("video", &mut t.video)] {
+                if let Some(url) = url {
+                    normalize(&format!("talks[{}].{}", i, name), url)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Leaves an empty `value` alone, since templates skip those.
+fn normalize(field: &str, value: &mut String) -> Result<()> {
+    let trimmed = value.trim();
+    if trimmed.is_empty() {
+        return Ok(());
+    }
+    let with_scheme = if trimmed.contains("://") || trimmed.starts_with("mailto:") {
+        trimmed.to_string()
+    } else {
+        format!("https://{}", trimmed)
+    };
+    let url_error = |message: String| Error::Url {
+        field: field.to_string(),
+        value: value.clone(),
+        message,
+    };
+    let url = Url::parse(&with_scheme).map_err(|e| url_error(e.to_string()))?;
+    if matches!(url.scheme(), "http" | "https")
+        && url
+            .host_str()
+            .is_none_or(|host| !host.contains('.') && host != "localhost")
+    {
+        return Err(url_error("it has no domain".to_string()));
+    }
+    // As written rather than `url`'s serialization, which would add a
+    // trailing slash to bare domains shown as link text.
+    *value = with_scheme;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn normalized(value: &str) -> Result<String> {
+        let mut value = value.to_string();
+        normalize("website", &mut value)?;
+        Ok(value)
+    }
+
+    #[test]
+    fn urls_without_a_scheme_get_https() {
+        assert_eq!(
+            normalized(" github.com/jane ").unwrap(),
+            "https://github.com/jane"
+        );
+        assert_eq!(normalized("jane.dev").unwrap(), "https://jane.dev");
+    }
+
+    #[test]
+    fn urls_with_a_scheme_are_kept_as_written() {
+        assert_eq!(normalized("http://jane.dev").unwrap(), "http://jane.dev");
+        assert_eq!(
+            normalized("mailto:jane@example.com").unwrap(),
+            "mailto:jane@example.com"
+        );
+        assert_eq!(
+            normalized("http://localhost:8080").unwrap(),
+            "http://localhost:8080"
+        );
+        assert_eq!(normalized("").unwrap(), "");
+    }
+
+    #[test]
+    fn urls_without_a_domain_are_rejected() {
+        match normalized("jane") {
+            Err(Error::Url { field, message, .. }) => {
+                assert_eq!(field, "website");
+                assert_eq!(message, "it has no domain");
+            }
+            other => panic!("expected a URL error, got {:?}", other),
+        }
+        assert!(matches!(
+            normalized("https://exa mple.com"),
+            Err(Error::Url { .. })
+        ));
+    }
+}
@@ -0,0 +1,309 @@
+//! Checks on the resume data that aren't errors but usually deserve a second look.
+
+use chrono::Local;
+use serde::Serialize;
+
+use crate::stats::{reading_level, sentences};
+use crate::Author;
+
+/// Every rule with a description; `strength` rules only run when asked for.
+pub const LINT_RULES: &[(&str, &str)] = &[
+    (
+        "duplicate-highlight",
+        "Identical or nearly identical highlights across experiences",
+    ),
+    (
+        "remote-location",
+        "A company location of `Remote` instead of `remote = true`",
+    ),
+    (
+        "low-contrast",
+        "Theme colors whose text is below WCAG AA contrast against its background",
+    ),
+    (
+        "reading-level",
+        "A sentence above the grade level set by `--max-grade`, 14 by default",
+    ),
+    (
+        "stale-stats",
+        "Project `stats` last refreshed more than 90 days ago; see `rsume refresh`",
+    ),
+    (
+        "action-verb",
+        "Strength: a highlight that doesn't start with an action verb",
+    ),
+    (
+        "quantified",
+        "Strength: a highlight without a number or metric",
+    ),
+];
+
+/// Verbs a highlight may start with besides any past tense ending in `-ed`.
+const ACTION_VERBS: &str = include_str!("action-verbs.txt");
+
+/// Which rules [`Author::lint`] runs.
+#[derive(Debug, Clone, Default)]
+pub struct LintOptions {
+    /// Also run the writing-strength rules
+    pub strength: bool,
+    /// Rules to skip, by name
+    pub disabled: Vec<String>,
+    /// Flesch–Kincaid grade above which a sentence is flagged, by default
+    /// [`MAX_GRADE`]
+    pub max_grade: Option<f64>,
+}
+
+/// The grade level `reading-level` allows unless told otherwise: past it,
+/// sentences tend to read as jargon.
+pub const MAX_GRADE: f64 = 14.0;
+
+/// Days after which `stale-stats` flags a project's stats.
+const STATS_MAX_AGE: i64 = 90;
+
+/// Highlights at least this similar, from 0 to 1, are reported as near-duplicates.
+const SIMILARITY_THRESHOLD: f64 = 0.8;
+
+#[derive(Debug, Serialize)]
+pub struct Finding {
+    /// Name of the rule that fired, e.g. `duplicate-highlight`
+    pub rule: &'static str,
+    /// Path of the offending value, e.g. `experiences[1].highlights[0]`
+    pub field: String,
+    pub message: String,
+}
+
+impl Author {
+    pub fn lint(&self, options: &LintOptions) -> Vec<Finding> {
+        let mut findings = duplicate_highlights(self);
+        findings.extend(self.theme.contrast());
+        findings.extend(remote_locations(self));
+        findings.extend(hard_sentences(self, options.max_grade.unwrap_or(MAX_GRADE)));
+        findings.extend(stale_stats(self));
+        if options.strength {
+            findings.extend(weak_highlights(self));
+        }
+        findings.retain(|finding| !options.disabled.iter().any(|rule| rule == finding.rule));
+        findings
+    }
+}
+
+/// Remote roles written into the location, which templates then can't tell
+/// apart from a place.
+fn remote_locations(author: &Author) -> Vec<Finding> {
+    author
+        .experiences
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| e.company.location.trim().eq_ignore_ascii_case("remote"))
+        .map(|(i, e)| Finding {
+            rule: "remote-location",
+            field: format!("experiences[{}].company.location", i),
+            message: if e.remote {
+                "repeats `remote = true`; give the office's city or leave it empty".to_string()
+            } else {
+                "is `Remote`; set `remote = true` and give the office's city or leave it empty"
+                    .to_string()
+            },
+        })
+        .collect()
+}
+
+/// Stats that no longer say what the projects' pages do.
+fn stale_stats(author: &Author) -> Vec<Finding> {
+    let today = Local::now().date_naive();
+    author
+        .projects
+        .iter()
+        .enumerate()
+        .filter_map(|(i, project)| {
+            let age = (today - project.stats.as_ref()?.refreshed).num_days();
+            (age > STATS_MAX_AGE).then(|| Finding {
+                rule: "stale-stats",
+                field: format!("projects[{}].stats", i),
+                message: format!(
+                    "was refreshed {} days ago; run `rsume refresh` to update it",
+                    age
+                ),
+            })
+        })
+        .collect()
+}
+
+/// Sentences of the summary, objective and highlights above `max_grade`.
+fn hard_sentences(author: &Author, max_grade: f64) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    for (field, text) in author.prose() {
+        for sentence in sentences(text) {
+            match reading_level(&[sentence]) {
+                Some(grade) if grade > max_grade => findings.push(Finding {
+                    rule: "reading-level",
+                    field: field.clone(),
+                    message: format!(
+                        "has a sentence at grade {:.1}, above {}: `{}`; split it or use plainer words",
+                        grade, max_grade, sentence
+                    ),
+                }),
+                _ => {}
+            }
+        }
+    }
+    findings
+}
+
+/// Highlights that don't open with an action verb or don't quantify anything.
+fn weak_highlights(author: &Author) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    for (i, e) in author.experiences.iter().enumerate() {
+        for (j, highlight) in e.highlights.iter().enumerate() {
+            let field = format!("experiences[{}].highlights[{}]", i, j);
+            let first = normalize(highlight)
+                .split(' ')
+                .next()
+                .unwrap_or_default()
+                .to_string();
+            if !first.ends_with("ed") && !is_action_verb(&first) {
+                findings.push(Finding {
+                    rule: "action-verb",
+                    field: field.clone(),
+                    message: format!(
+                        "starts with `{}`; lead with what you did, e.g. `Built` or `Reduced`",
+                        first
+                    ),
+                });
+            }
+            if !highlight
+                .chars()
+                .any(|c| c.is_ascii_digit() || "%$€£×".contains(c))
+            {
+                findings.push(Finding {
+                    rule: "quantified",
+                    field,
+                    message: "has no number; say how much, how many or how fast".to_string(),
+                });
+            }
+        }
+    }
+    findings
+}
+
+/// Copy-pasted highlights, identical or nearly so once case and punctuation are
+/// ignored, anywhere across `experiences`.
+fn duplicate_highlights(author: &Author) -> Vec<Finding> {
+    let highlights: Vec<(String, String)> = author
+        .experiences
+        .iter()
+        .enumerate()
+        .flat_map(|(i, e)| {
+            e.highlights.iter().enumerate().map(move |(j, highlight)| {
+                (
+                    format!("experiences[{}].highlights[{}]", i, j),
+                    normalize(highlight),
+                )
+            })
+        })
+        .collect();
+    let mut findings = Vec::new();
+    for (later, (field, text)) in highlights.iter().enumerate() {
+        let earlier = highlights[..later]
+            .iter()
+            .map(|(earlier, other)| (earlier, similarity(text, other)))
+            .filter(|(_, similarity)| *similarity >= SIMILARITY_THRESHOLD)
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        if let Some((earlier, similarity)) = earlier {
+            findings.push(Finding {
+                rule: "duplicate-highlight",
+                field: field.clone(),
+                message: if similarity == 1.0 {
+                    format!("repeats {}", earlier)
+                } else {
+                    format!("is {:.0}% similar to {}", similarity * 100.0, earlier)
+                },
+            });
+        }
+    }
+    findings
+}
+
+fn is_action_verb(word: &str) -> bool {
+    ACTION_VERBS
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .any(|verb| verb == word)
+}
+
+/// Lowercase words separated by single spaces.
+fn normalize(text: &str) -> String {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// One minus the Levenshtein distance over the longer length.
+fn similarity(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 1.0;
+    }
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    1.0 - row[b.len()] as f64 / longest as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+
+    fn author(highlights: &[&str]) -> Author {
+        testing::resume("", highlights).author
+    }
+
+    #[test]
+    fn near_duplicate_highlights_are_reported() {
+        let findings = duplicate_highlights(&author(&[
+            "Cut build times by 40% across the monorepo",
+            "Cut build time by 40% across the monorepo.",
+        ]));
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].field, "experiences[0].highlights[1]");
+        assert!(findings[0]
+            .message
+            .contains("similar to experiences[0].highlights[0]"));
+    }
+
+    #[test]
+    fn repeated_highlights_are_reported_as_repeats() {
+        let findings = duplicate_highlights(&author(&["Led the migration", "led the MIGRATION!"]));
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].message, "repeats experiences[0].highlights[0]");
+    }
+
+    #[test]
+    fn distinct_highlights_pass() {
+        let findings = duplicate_highlights(&author(&[
+            "Cut build times by 40% across the monorepo",
+            "Mentored four engineers through their first on-call rotation",
+        ]));
+        assert!(findings.is_empty());
+    }
+
+    #[test]
+    fn similarity_is_one_minus_edit_distance_over_length() {
+        assert_eq!(similarity("", ""), 1.0);
+        assert_eq!(similarity("abcd", "abcd"), 1.0);
+        assert_eq!(similarity("abcd", "abce"), 0.75);
+        assert_eq!(similarity("abcd", ""), 0.0);
+    }
+}
@@ -0,0 +1,124 @@
+use crate::Location;
+
+/// How each country lays out an address, after the Latin-script formats of
+/// Google's address metadata: `%A` is the street address, `%Z` the postal
+/// code, `%C` the city and `%S` the region, with `\n` between lines.
+/// The separator before a missing part is dropped with it.
+const ADDRESS_FORMATS: &[(&str, &str)] = &[
+    ("US", "%A\n%C, %S %Z"),
+    ("CA", "%A\n%C, %S %Z"),
+    ("AU", "%A\n%C, %S %Z"),
+    ("BR", "%A\n%C, %S %Z"),
+    ("IN", "%A\n%C %Z\n%S"),
+    ("MX", "%A\n%Z %C, %S"),
+    ("GB", "%A\n%C\n%Z"),
+    ("IE", "%A\n%C\n%S\n%Z"),
+    ("ES", "%A\n%Z %C\n%S"),
+    ("IT", "%A\n%Z %C %S"),
+    ("JP", "%A, %C\n%S %Z"),
+    ("CN", "%A\n%C\n%S %Z"),
+    ("KR", "%A\n%C\n%S %Z"),
+    ("SG", "%A\n%C %Z"),
+    ("AE", "%A\n%C\n%S"),
+    ("NZ", "%A\n%C %Z"),
+    ("RU", "%A\n%C\n%S\n%Z"),
+];
+
+/// Most of continental Europe, and the fallback.
+const DEFAULT_FORMAT: &str = "%A\n%Z %C";
+
+/// Display names for the codes that aren't conventionally written out in full.
+const COUNTRY_NAMES: &[(&str, &str)] = &[
+    ("US", "USA"),
+    ("GB", "UK"),
+    ("AE", "UAE"),
+    ("AU", "Australia"),
+    ("BR", "Brazil"),
+    ("CA", "Canada"),
+    ("CH", "Switzerland"),
+    ("CN", "China"),
+    ("DE", "Germany"),
+    ("DK", "Denmark"),
+    ("ES", "Spain"),
+    ("FI", "Finland"),
+    ("FR", "France"),
+    ("IE", "Ireland"),
+    ("IN", "India"),
+    ("IT", "Italy"),
+    ("JP", "Japan"),
+    ("MX", "Mexico"),
+    ("NL", "Netherlands"),
+    ("NO", "Norway"),
+    ("NZ", "New Zealand"),
+    ("PL", "Poland"),
+    ("PT", "Portugal"),
+    ("SE", "Sweden"),
+    ("SG", "Singapore"),
+];
+
+impl Location {
+    /// A single display line such as `San Francisco, CA, USA` or `10115 Berlin,
+    /// Germany`, skipping missing parts. The street address and postal code are
+    /// only included with `address`, the country only with `country`.
+    pub fn one_line(&self, address: bool, country: bool) -> String {
+        let code = self
+            .country_code
+            .as_deref()
+            .unwrap_or_default()
+            .to_uppercase();
+        let part = |field: &Option<String>| {
+            field
+                .as_deref()
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
+        };
+        let format = ADDRESS_FORMATS
+            .iter()
+            .find(|(c, _)| *c == code)
+            .map_or(DEFAULT_FORMAT, |(_, format)| format);
+        let mut parts: Vec<String> = format
+            .split('\n')
+            .filter_map(|line| {
+                fill(line, |field| match field {
+                    'A' => part(&self.address).filter(|_| address),
+                    'Z' => part(&self.postal_code).filter(|_| address),
+                    'C' => part(&self.city),
+                    'S' => part(&self.region),
+                    _ => None,
+                })
+            })
+            .collect();
+        if country && !code.is_empty() {
+            parts.push(
+                COUNTRY_NAMES
+                    .iter()
+                    .find(|(c, _)| *c == code)
+                    .map_or(code.clone(), |(_, name)| name.to_string()),
+            );
+        }
+        parts.join(", ")
+    }
+}
+
+/// One line of an address format with the parts filled in, each after the
+/// separator just before it, or nothing if no part is present.
+fn fill(line: &str, value: impl Fn(char) -> Option<String>) -> Option<String> {
+    let mut filled = String::new();
+    let mut separator = String::new();
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            separator.push(c);
+            continue;
+        }
+        if let Some(value) = chars.next().and_then(&value) {
+            if !filled.is_empty() {
+                filled.push_str(&separator);
+            }
+            filled.push_str(&value);
+        }
+        separator.clear();
+    }
+    (!filled.is_empty()).then_some(filled)
+}
@@ -0,0 +1,46 @@
+use std::fmt::Arguments;
+
+use anyhow::Error;
+use tectonic::status::{MessageKind, StatusBackend};
+use tracing::{debug, error, info, warn};
+use tracing_subscriber::EnvFilter;
+
+/// Logs go to stderr so stdout stays free for `--message-format json`. `RSUME_LOG`
+/// takes any `tracing` filter directive and overrides the `-q`/`-v` flags.
+pub fn init(quiet: bool, verbose: u8) {
+    let level = match (quiet, verbose) {
+        (true, _) => "error",
+        (false, 0) => "warn",
+        (false, 1) => "info",
+        (false, 2) => "debug",
+        (false, _) => "trace",
+    };
+    let filter = EnvFilter::try_from_env("RSUME_LOG")
+        .unwrap_or_else(|_| EnvFilter::new(format!("rsume={}", level)));
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr)
+        .with_target(false)
+        .init();
+}
+
+/// Forwards Tectonic's status messages to `tracing`.
+pub struct TracingStatusBackend;
+
+impl StatusBackend for TracingStatusBackend {
+    fn report(&mut self, kind: MessageKind, args: Arguments, err: Option<&Error>) {
+        let message = match err {
+            Some(err) => format!("{}: {:#}", args, err),
+            None => args.to_string(),
+        };
+        match kind {
+            MessageKind::Note => info!(target: "rsume::tectonic", "{}", message),
+            MessageKind::Warning => warn!(target: "rsume::tectonic", "{}", message),
+            MessageKind::Error => error!(target: "rsume::tectonic", "{}", message),
+        }
+    }
+
+    fn dump_error_logs(&mut self, output: &[u8]) {
+        debug!(target: "rsume::tectonic", "{}", String::from_utf8_lossy(output));
+    }
+}
@@ -0,0 +1,254 @@
+//! `rsume lsp`: enough of the Language Server Protocol for editors to show
+//! parse errors and lint findings in data files as they're typed, and to
+//! rebuild on save, without reimplementing the pipeline.
+
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+use std::ops::Range;
+use std::path::PathBuf;
+
+use rsume::sourcemap::data_span;
+use rsume::{Error, LintOptions, Resume};
+use serde_json::{json, Value};
+use tracing::{debug, info};
+use url::Url;
+
+use crate::error_chain;
+use crate::rebuild::{Rebuilder, Request};
+
+/// LSP's diagnostic severities.
+const ERROR: u8 = 1;
+const WARNING: u8 = 2;
+
+/// Where saves rebuild to; the data file is the saved document.
+#[derive(Debug, Clone)]
+pub struct Target {
+    pub template_path: PathBuf,
+    pub template_filename: String,
+    pub output_dir: PathBuf,
+}
+
+struct Server {
+    target: Option<Target>,
+    rebuilder: Rebuilder,
+    /// Open documents' text, keyed by URI
+    documents: HashMap<String, String>,
+}
+
+/// Serves on stdin and stdout until the client says `exit`.
+pub fn serve(target: Option<Target>) -> io::Result<()> {
+    let mut server = Server {
+        target,
+        rebuilder: Rebuilder::default(),
+        documents: HashMap::new(),
+    };
+    let mut input = io::stdin().lock();
+    let mut output = io::stdout().lock();
+    while let Some(message) = read_message(&mut input)? {
+        let method = message["method"].as_str().unwrap_or_default();
+        debug!(method, "received");
+        if method == "exit" {
+            break;
+        }
+        for reply in server.handle(method, &message) {
+            write_message(&mut output, &reply)?;
+        }
+    }
+    Ok(())
+}
+
+impl Server {
+    /// The responses and notifications to send for `message`.
+    fn handle(&mut self, method: &str, message: &Value) -> Vec<Value> {
+        let params = &message["params"];
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+        let respond =
+            |result: Value| vec![json!({"jsonrpc": "2.0", "id": message["id"], "result": result})];
+        match method {
+            "initialize" => respond(json!({
+                "capabilities": {
+                    // Full text on every change, and a notification on save.
+                    "textDocumentSync": {"openClose": true, "change": 1, "save": {}},
+                },
+                "serverInfo": {"name": "rsume", "version": env!("CARGO_PKG_VERSION")},
+            })),
+            "shutdown" => respond(Value::Null),
+            "textDocument/didOpen" => {
+                let text = params["textDocument"]["text"].as_str().unwrap_or_default();
+                self.documents.insert(uri.to_string(), text.to_string());
+                vec![self.publish(uri)]
+            }
+            "textDocument/didChange" => {
+                let changes = params["contentChanges"].as_array();
+                if let Some(text) = changes
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change["text"].as_str())
+                {
+                    self.documents.insert(uri.to_string(), text.to_string());
+                }
+                vec![self.publish(uri)]
+            }
+            "textDocument/didClose" => {
+                self.documents.remove(uri);
+                vec![notification(
+                    "textDocument/publishDiagnostics",
+                    json!({"uri": uri, "diagnostics": []}),
+                )]
+            }
+            "textDocument/didSave" => {
+                let mut replies = vec![self.publish(uri)];
+                replies.extend(self.rebuild(uri));
+                replies
+            }
+            _ if message.get("id").is_some() => vec![json!({
+                "jsonrpc": "2.0",
+                "id": message["id"],
+                "error": {"code": -32601, "message": format!("rsume doesn't handle {}", method)},
+            })],
+            _ => Vec::new(),
+        }
+    }
+
+    fn publish(&self, uri: &str) -> Value {
+        let source = self.documents.get(uri).map_or("", String::as_str);
+        notification(
+            "textDocument/publishDiagnostics",
+            json!({"uri": uri, "diagnostics": diagnostics(source)}),
+        )
+    }
+
+    /// Builds the saved data file with the target's templates, if there is one.
+    fn rebuild(&mut self, uri: &str) -> Option<Value> {
+        let target = self.target.clone()?;
+        let input_path = Url::parse(uri).ok()?.to_file_path().ok()?;
+        let request = Request {
+            input_path,
+            template_path: target.template_path,
+            template_filename: target.template_filename,
+            output_dir: target.output_dir,
+            reload: false,
+        };
+        Some(match self.rebuilder.build(&request) {
+            Ok(artifacts) => {
+                info!(?artifacts, "rebuilt");
+                let artifacts: Vec<String> = artifacts
+                    .iter()
+                    .map(|path| path.display().to_string())
+                    .collect();
+                notification(
+                    "window/logMessage",
+                    json!({"type": 3, "message": format!("rsume built {}", artifacts.join(", "))}),
+                )
+            }
+            Err(e) => notification(
+                "window/showMessage",
+                json!({"type": 1, "message": format!("rsume: {}", error_chain(&e))}),
+            ),
+        })
+    }
+}
+
+/// Parse errors, or once the data parses, lint findings.
+fn diagnostics(source: &str) -> Vec<Value> {
+    let resume = match Resume::from_toml(source) {
+        Ok(resume) => resume,
+        Err(error) => {
+            let span = match &error {
+                Error::Parse(e) => e.span(),
+                Error::Current { field, .. } | Error::Url { field, .. } => source
+                    .parse()
+                    .ok()
+                    .and_then(|data| data_span(source, &data, field)),
+                _ => None,
+            };
+            return vec![diagnostic(source, span, ERROR, None, error_chain(&error))];
+        }
+    };
+    resume
+        .author
+        .lint(&LintOptions::default())
+        .into_iter()
+        .map(|finding| {
+            diagnostic(
+                source,
+                data_span(source, &resume.data, &finding.field),
+                WARNING,
+                Some(finding.rule),
+                format!("{} {}", finding.field, finding.message),
+            )
+        })
+        .collect()
+}
+
+/// A diagnostic at `span`, or at the start of the file without one.
+fn diagnostic(
+    source: &str,
+    span: Option<Range<usize>>,
+    severity: u8,
+    code: Option<&str>,
+    message: String,
+) -> Value {
+    let span = span.unwrap_or(0..0);
+    json!({
+        "range": {"start": position(source, span.start), "end": position(source, span.end)},
+        "severity": severity,
+        "code": code,
+        "source": "rsume",
+        "message": message,
+    })
+}
+
+/// The line and UTF-16 column of byte `offset`, as LSP counts them.
+fn position(source: &str, offset: usize) -> Value {
+    let mut offset = offset.min(source.len());
+    while !source.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    let before = &source[..offset];
+    let line = before.matches('\n').count();
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let character: usize = before[line_start..].chars().map(char::len_utf16).sum();
+    json!({"line": line, "character": character})
+}
+
+fn notification(method: &str, params: Value) -> Value {
+    json!({"jsonrpc": "2.0", "method": method, "params": params})
+}
+
+/// The next message, framed by a `Content-Length` header; none at the end of
+/// the input.
+fn read_message(input: &mut impl BufRead) -> io::Result<Option<Value>> {
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+    let length = length.ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "message without a Content-Length",
+        )
+    })?;
+    let mut body = vec![0; length];
+    input.read_exact(&mut body)?;
+    serde_json::from_slice(&body)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn write_message(output: &mut impl Write, message: &Value) -> io::Result<()> {
+    let body = serde_json::to_string(message).expect("JSON values always serialize");
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    output.flush()
+}
@@ -1,243 +1,1793 @@
-use std::collections::HashMap;
-use std::fs::{self, File};
-use std::io::Write;
+mod applications;
+mod bench;
+#[cfg(unix)]
+mod daemon;
+mod logging;
+mod lsp;
+mod messages;
+mod progress;
+mod rebuild;
+mod watchdog;
+
+use std::error::Error as _;
+use std::io::{self, IsTerminal};
 use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use std::{env, fs};
 
-use clap::Parser;
-use tectonic::errors::Result;
-use tectonic::{config, ctry, driver, status};
-use tera::{try_get_value, Context, Tera, Value};
+use clap::{Args, CommandFactory, FromArgMatches, Parser, Subcommand};
+use rsume::{
+    compile_pdf, diff_pdfs, docs, is_html, optimize_pdf, sign_pdf, sourcemap, template_dir,
+    Application, Asset, Author, CompileOptions, Compiled, DiffMode, DiffOptions, Error, Finding,
+    Limits, LintOptions, Recipients, ReferenceVisibility, Resume, Signer, Spacing, Templates,
+    Variant, BUILTINS, JSON_RESUME_FILE, LINT_RULES, MAX_TIGHTEN, SCHEMA_VERSION,
+};
+use tectonic::status;
+use tracing::{debug, error, info, info_span, warn};
 
-use serde::{Deserialize, Deserializer, Serialize};
-use toml::value::Datetime;
+use bench::StageTiming;
+use logging::TracingStatusBackend;
+use messages::{JsonStatusBackend, Message, MessageFormat, Span};
+use progress::ProgressStatusBackend;
+use rebuild::Rebuilder;
 
 #[derive(Parser, Debug)]
-#[command(author, version, about, long_about = None)]
-struct Args {
+#[command(author, version, about, long_about = None, args_conflicts_with_subcommands = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+    #[command(flatten)]
+    build: Option<BuildArgs>,
+    #[arg(long, value_enum, global = true, default_value_t = MessageFormat::Human)]
+    message_format: MessageFormat,
+    /// Only log errors
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+    /// Log more (-v for progress, -vv for debugging); RSUME_LOG overrides both
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    verbose: u8,
+    /// Keep the TeX bundle, format files and Gravatars here, e.g. for CI to
+    /// cache between runs; RSUME_CACHE_DIR sets it too
+    #[arg(long, global = true, value_parser = parse_path)]
+    cache_dir: Option<PathBuf>,
+}
+
+// What a template is rendered from, shared by builds and `render`. Plain comments,
+// since clap would show a doc comment as the top-level help text.
+#[derive(Args, Debug, Clone)]
+struct RenderArgs {
     #[arg(value_parser = parse_path)]
     input_path: PathBuf,
+    /// Template glob such as `templates/*.tex`, or `builtin:<name>` (see `rsume template list`)
     #[arg(value_parser = parse_path)]
     template_path: PathBuf,
     template_filename: String,
-    #[arg(value_parser = parse_path)]
-    tex_root: PathBuf,
+    #[command(flatten)]
+    data: DataArgs,
+    /// Font family for templates that support it, overriding `theme.font`
+    #[arg(long)]
+    font: Option<String>,
+    /// Font file to stage for the compile, e.g. each face of a local family (repeatable)
+    #[arg(long = "font-file", value_parser = parse_path)]
+    font_files: Vec<PathBuf>,
+    /// Company the build is for: exposed as `application.company`, appended to
+    /// the output name and recorded in the output directory's applications.toml
+    #[arg(long)]
+    company: Option<String>,
+    /// Role the build is for, used like --company
+    #[arg(long)]
+    role: Option<String>,
+    /// Set per recipient by `cover-letter --batch`
+    #[arg(skip)]
+    recipient: Application,
+}
+
+// Overrides of the data that change the template context, shared by builds
+// and `context`.
+#[derive(Args, Debug, Clone)]
+struct DataArgs {
+    /// Whether to list references, overriding `references_visibility` in the data
+    #[arg(long, value_enum)]
+    references: Option<ReferenceVisibility>,
+    /// Tailoring declared as `variants.<name>` in the data, e.g. trimming highlights
+    #[arg(long)]
+    variant: Option<String>,
+    /// Only include tagged highlights with one of these tags, replacing the
+    /// variant's `tags`; untagged highlights always stay
+    #[arg(long, value_delimiter = ',')]
+    tags: Vec<String>,
+    /// List roles that ended before this year as a compact `earlier_experiences`
+    /// line, like those marked `archive`
+    #[arg(long, value_name = "YEAR")]
+    since: Option<i32>,
+    /// Replace the name, contact details and employers with placeholders, for
+    /// blind review or sharing a draft
+    #[arg(long)]
+    anonymize: bool,
+    /// Swap the theme colors for shades of gray, for copies printed in black and white
+    #[arg(long)]
+    grayscale: bool,
+    /// Template variable exposed as `vars.<key>`; the value is parsed as JSON
+    /// when it can be, so `compact=true` is a boolean (repeatable)
+    #[arg(long = "define", value_name = "KEY=VALUE", value_parser = parse_define)]
+    defines: Vec<(String, serde_json::Value)>,
+}
+
+impl DataArgs {
+    fn apply(&self, author: &mut Author) -> rsume::Result<()> {
+        let mut variant = match &self.variant {
+            Some(name) => {
+                let variant = author.variant(name)?;
+                author.select_variant(name);
+                variant
+            }
+            None => Variant::default(),
+        };
+        if !self.tags.is_empty() {
+            variant.tags = self.tags.clone();
+        }
+        author.tailor(&variant);
+        if self.anonymize {
+            author.anonymize();
+        }
+        author.archive(self.since);
+        if self.grayscale {
+            author.theme.grayscale();
+        }
+        if let Some(visibility) = self.references {
+            author.references_visibility = visibility;
+        }
+        author.vars.extend(self.defines.iter().cloned());
+        Ok(())
+    }
+}
+
+#[derive(Args, Debug, Clone)]
+struct BuildArgs {
+    #[command(flatten)]
+    render: RenderArgs,
     #[arg(value_parser = parse_path)]
     output_root: PathBuf,
+    /// Directory Tectonic may read .sty/.cls/image files from (repeatable,
+    /// defaults to the template directory)
+    #[arg(long = "resource-dir", value_parser = parse_path)]
+    resource_dirs: Vec<PathBuf>,
+    /// Directory of shared LaTeX inputs whose files are made visible by bare
+    /// name regardless of nesting (repeatable)
+    #[arg(long = "include-dir", value_parser = parse_path)]
+    include_dirs: Vec<PathBuf>,
+    /// Also write SyncTeX data next to the PDF for editor integration
+    #[arg(long)]
+    synctex: bool,
+    /// Retry with progressively tighter spacing until the PDF fits in this many pages
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    max_pages: Option<u64>,
+    /// Shrink the PDF by compressing its objects together; fonts are already
+    /// subset, and the file isn't linearized
+    #[arg(long)]
+    optimize: bool,
+    /// Sign the PDF with the key in --cert, so recipients can verify it wasn't altered
+    #[arg(long, requires = "cert")]
+    sign: bool,
+    /// PKCS#12 (.p12/.pfx) file holding the signing key and certificate; its
+    /// password is read from RSUME_CERT_PASSWORD
+    #[arg(long, value_parser = parse_path)]
+    cert: Option<PathBuf>,
+    /// Add the document title and image alternative text for assistive
+    /// technology, and report data that still needs attention
+    #[arg(long)]
+    accessible: bool,
+    /// Fail instead of warning when the data file has uncommitted changes, so
+    /// a final copy always matches a commit
+    #[arg(long)]
+    require_clean: bool,
+    /// Fail a compile that takes longer than this many seconds, e.g. in an
+    /// infinite TeX loop
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    timeout: Option<u64>,
+    /// Compile again this many times after failures that tend to go away on
+    /// their own, such as a cut-short download or a half-written format cache
+    #[arg(long, default_value_t = 1)]
+    retries: u32,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Upgrade a data file to the current schema version
+    Migrate {
+        #[arg(value_parser = parse_path)]
+        input_path: PathBuf,
+        /// Write the upgraded data here instead of stdout
+        #[arg(short, long, value_parser = parse_path)]
+        output: Option<PathBuf>,
+        /// Overwrite the input file
+        #[arg(long, conflicts_with = "output")]
+        in_place: bool,
+    },
+    /// Render a template without compiling it, e.g. to run a local TeX
+    /// installation on the output or to inspect it
+    Render {
+        #[command(flatten)]
+        render: Box<RenderArgs>,
+        /// Write here instead of stdout, copying the files the output refers
+        /// to, such as logos and fonts, alongside; required with
+        /// --message-format json
+        #[arg(short, long, value_parser = parse_path)]
+        output: Option<PathBuf>,
+    },
+    /// Time parsing the data, rendering and compiling separately over repeated
+    /// runs and report percentiles
+    Bench {
+        #[command(flatten)]
+        render: Box<RenderArgs>,
+        /// Timed runs per stage
+        #[arg(long, default_value_t = 10)]
+        runs: usize,
+        /// Untimed runs per stage first, e.g. so the bundle is cached before
+        /// the compile is timed
+        #[arg(long, default_value_t = 1)]
+        warmup: usize,
+        /// Only time parsing and rendering
+        #[arg(long)]
+        no_compile: bool,
+    },
+    /// Compile a LaTeX file as it is, skipping the template stage
+    Compile {
+        #[arg(value_parser = parse_path)]
+        tex_path: PathBuf,
+        /// Directory to write the PDF to
+        #[arg(short, long, value_parser = parse_path, default_value = ".")]
+        output: PathBuf,
+        /// Directory Tectonic may read .sty/.cls/image files from (repeatable,
+        /// defaults to the file's directory)
+        #[arg(long = "resource-dir", value_parser = parse_path)]
+        resource_dirs: Vec<PathBuf>,
+        /// Directory of shared LaTeX inputs whose files are made visible by bare
+        /// name regardless of nesting (repeatable)
+        #[arg(long = "include-dir", value_parser = parse_path)]
+        include_dirs: Vec<PathBuf>,
+        /// Also write SyncTeX data next to the PDF for editor integration
+        #[arg(long)]
+        synctex: bool,
+        /// Fail a compile that takes longer than this many seconds, e.g. in an
+        /// infinite TeX loop
+        #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+        timeout: Option<u64>,
+    },
+    /// Build a cover letter per recipient, optionally each with a tailored resume
+    CoverLetter {
+        #[command(flatten)]
+        build: Box<BuildArgs>,
+        /// TOML file of `[[recipients]]` tables, each with a company, role,
+        /// addressee, address and note
+        #[arg(long, value_parser = parse_path)]
+        batch: PathBuf,
+        /// Also build this template from the same set for each recipient, e.g. resume.tex
+        #[arg(long)]
+        resume: Option<String>,
+    },
+    /// Print the JSON context a template receives, for debugging what it shows
+    Context {
+        #[arg(value_parser = parse_path)]
+        input_path: PathBuf,
+        /// Template glob or `builtin:<name>` whose manifest applies
+        #[arg(long, value_parser = parse_path)]
+        template_path: Option<PathBuf>,
+        /// The template to show the context of; HTML templates get a different one
+        #[arg(long, default_value = "resume.tex")]
+        template: String,
+        #[command(flatten)]
+        data: DataArgs,
+    },
+    /// Print the resume as text in the terminal, to check the data before a full build
+    Show {
+        #[arg(value_parser = parse_path)]
+        input_path: PathBuf,
+        /// Plain-text template glob to use instead of the built-in `text` set, or
+        /// `builtin:<name>`
+        #[arg(long, value_parser = parse_path)]
+        template_path: Option<PathBuf>,
+        #[arg(long, default_value = "resume.txt")]
+        template: String,
+    },
+    /// Point out likely problems in the data, such as duplicated highlights
+    Lint {
+        #[arg(value_parser = parse_path)]
+        input_path: PathBuf,
+        /// Also flag highlights without an action verb or a number
+        #[arg(long)]
+        strength: bool,
+        /// Skip a rule (repeatable)
+        #[arg(long, value_parser = clap::builder::PossibleValuesParser::new(LINT_RULES.iter().map(|(rule, _)| *rule)))]
+        disable: Vec<String>,
+        /// Flag sentences above this Flesch–Kincaid grade level
+        #[arg(long, default_value_t = rsume::MAX_GRADE)]
+        max_grade: f64,
+    },
+    /// Report words per section, bullets per role, reading level and frequent keywords
+    Stats {
+        #[arg(value_parser = parse_path)]
+        input_path: PathBuf,
+        /// How many keywords to list
+        #[arg(long, default_value_t = 10)]
+        keywords: usize,
+    },
+    /// Suggest skills the experiences show but `skills` doesn't list, and
+    /// listed skills no experience mentions
+    SuggestSkills {
+        #[arg(value_parser = parse_path)]
+        input_path: PathBuf,
+    },
+    /// Propose a `[variants.<name>]` table for a job posting
+    ///
+    /// The table, to edit and add to the data, keeps the keywords and highlight
+    /// tags the posting mentions, lists the skills it asks for first, and
+    /// starts a summary naming the top keywords.
+    Tailor {
+        #[arg(value_parser = parse_path)]
+        input_path: PathBuf,
+        /// Plain-text job posting
+        #[arg(long, value_parser = parse_path)]
+        job: PathBuf,
+        /// Name of the variant, used with `--variant`
+        #[arg(long)]
+        variant_name: String,
+        /// Write the table here instead of to stdout
+        #[arg(short, long, value_parser = parse_path)]
+        output: Option<PathBuf>,
+    },
+    /// Look up GitHub stars and crates.io and npm versions and downloads for
+    /// projects with a GitHub `source`, a `crate` or an `npm` package, and
+    /// store them in the data as `stats`
+    ///
+    /// Set GITHUB_TOKEN to raise GitHub's rate limit.
+    Refresh {
+        #[arg(value_parser = parse_path)]
+        input_path: PathBuf,
+        /// Keep stats refreshed fewer than this many days ago
+        #[arg(long, value_name = "DAYS", default_value_t = 0)]
+        max_age: u32,
+        /// Write the updated data here instead of stdout
+        #[arg(short, long, value_parser = parse_path)]
+        output: Option<PathBuf>,
+        /// Overwrite the input file
+        #[arg(long, conflicts_with = "output")]
+        in_place: bool,
+    },
+    /// Record the data as a named revision in rsume-history.toml beside it;
+    /// templates can stamp the latest one as `revision.tag`
+    Tag {
+        #[arg(value_parser = parse_path)]
+        input_path: PathBuf,
+        /// Name of the revision, e.g. `v2025-05`
+        tag: String,
+        /// What changed, or who the revision was sent to
+        #[arg(long)]
+        note: Option<String>,
+    },
+    /// Compare two PDFs page by page, writing a difference image for each page
+    /// that changed; exits with status 1 when any did
+    ///
+    /// Removed content is drawn red and added content green. Needs Poppler's
+    /// pdftoppm.
+    DiffPdf {
+        #[arg(value_parser = parse_path)]
+        old: PathBuf,
+        #[arg(value_parser = parse_path)]
+        new: PathBuf,
+        /// Directory for the `page-<n>.png` images
+        #[arg(short, long, default_value = "pdf-diff", value_parser = parse_path)]
+        output_dir: PathBuf,
+        #[arg(long, value_enum, default_value_t)]
+        mode: DiffMode,
+        /// Resolution to compare at
+        #[arg(long, default_value_t = 72)]
+        dpi: u32,
+    },
+    /// Build on request over a Unix socket, keeping templates parsed and the
+    /// Tectonic bundle open between builds, for editor integrations
+    ///
+    /// Each request is a line of JSON such as `{"input_path": "resume.toml",
+    /// "template_path": "builtin:sidebar", "template_filename": "resume.tex",
+    /// "output_dir": "out"}`, with `"reload": true` to parse the templates
+    /// again. Each answer is a line with `success`, the `artifacts` or an error
+    /// `message`, and `milliseconds`.
+    ///
+    /// Data is treated as untrusted unless --trusted: requests can't use paths
+    /// outside --root, the data is size-limited, can't refer to files outside
+    /// its directory, contain TeX markup or fetch a Gravatar, git info is left
+    /// out, and templates can't `get_env`.
+    Daemon {
+        #[arg(long, default_value = "rsume.sock", value_parser = parse_path)]
+        socket: PathBuf,
+        /// Largest data file to build, in bytes or with a K or M suffix
+        #[arg(long, default_value = "1M", value_parser = parse_size)]
+        max_data_size: u64,
+        /// Largest rendered template to write or compile
+        #[arg(long, default_value = "4M", value_parser = parse_size)]
+        max_output_size: u64,
+        /// Seconds a compile may take before it fails; one that doesn't stop
+        /// then ends the daemon, so run it under a supervisor that restarts it
+        #[arg(long, default_value_t = 60)]
+        compile_timeout: u64,
+        /// Directory the data, templates and output of requests must be in
+        #[arg(long, default_value = ".", value_parser = parse_path)]
+        root: PathBuf,
+        /// Compile again this many times after failures that tend to go away
+        /// on their own, as for a build
+        #[arg(long, default_value_t = 1)]
+        retries: u32,
+        /// Lift the limits on data, for data you wrote yourself
+        #[arg(long)]
+        trusted: bool,
+    },
+    /// Serve the Language Server Protocol on stdin and stdout, for editor
+    /// plugins
+    ///
+    /// Open data files get diagnostics from parsing and `rsume lint` as they
+    /// change; with --template-path and --template-filename, saving one also
+    /// rebuilds it.
+    Lsp {
+        /// Templates to rebuild with on save, as for a build
+        #[arg(long, requires = "template_filename", value_parser = parse_path)]
+        template_path: Option<PathBuf>,
+        #[arg(long, requires = "template_path")]
+        template_filename: Option<String>,
+        /// Directory saves build into
+        #[arg(long, default_value = "out", value_parser = parse_path)]
+        output_dir: PathBuf,
+    },
+    /// Package the TeX files a template needs, for building without network
+    /// access
+    Bundle {
+        #[command(subcommand)]
+        command: BundleCommand,
+    },
+    /// Convert work history kept elsewhere into resume data
+    Import {
+        #[command(subcommand)]
+        command: ImportCommand,
+    },
+    /// Work with templates
+    Template {
+        #[command(subcommand)]
+        command: TemplateCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ImportCommand {
+    /// Print `[[experiences]]` tables for a CSV export of a spreadsheet of roles
+    ///
+    /// The header row names the columns, in any order and case: employer (or
+    /// company), title (or position, role) and start are required; end (empty or
+    /// "present" while current), bullets (one per line in the cell), department,
+    /// location, website and technologies (comma-separated) are optional. Dates
+    /// are YYYY-MM-DD, YYYY-MM or YYYY.
+    Csv {
+        #[arg(value_parser = parse_path)]
+        input_path: PathBuf,
+        /// Write the tables here instead of stdout
+        #[arg(short, long, value_parser = parse_path)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum BundleCommand {
+    /// Build once and copy every file the compile reads from the TeX bundle
+    /// into a directory
+    ///
+    /// Copy the directory to the offline machine and run `rsume bundle import`
+    /// there. Builds needing other packages or fonts than this one did will
+    /// fail on that machine.
+    Export {
+        #[arg(value_parser = parse_path)]
+        dir: PathBuf,
+        #[command(flatten)]
+        render: Box<RenderArgs>,
+    },
+    /// Build from an exported bundle from now on instead of downloading one
+    Import {
+        #[arg(value_parser = parse_path)]
+        dir: PathBuf,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum TemplateCommand {
+    /// Print a Markdown reference of the fields, filters and functions templates can use
+    Docs {
+        /// Template glob to load; with --template, also list the variables it uses
+        #[arg(long, value_parser = parse_path)]
+        template_path: Option<PathBuf>,
+        #[arg(long, requires = "template_path")]
+        template: Option<String>,
+    },
+    /// List the built-in template sets
+    List,
+}
+
+fn builtin_name(template_path: &Path) -> Option<&str> {
+    template_path.to_str()?.strip_prefix("builtin:")
+}
+
+fn load_templates(template_path: &Path) -> rsume::Result<Templates> {
+    match builtin_name(template_path) {
+        Some(name) => Templates::builtin(name),
+        None => Templates::new(template_path),
+    }
+}
+
+fn parse_define(s: &str) -> std::result::Result<(String, serde_json::Value), String> {
+    let (key, value) = s
+        .split_once('=')
+        .ok_or_else(|| format!("`{}` isn't of the form KEY=VALUE", s))?;
+    let value = serde_json::from_str(value).unwrap_or_else(|_| value.into());
+    Ok((key.to_string(), value))
 }
 
 fn parse_path(s: &str) -> std::result::Result<PathBuf, String> {
     Ok(Path::new(s).to_path_buf())
 }
 
-#[derive(Serialize, Deserialize)]
-struct Location {
-    address: String,
-    postal_code: String,
-    city: String,
-    country_code: String,
-    region: String,
-}
-
-#[derive(Serialize, Deserialize)]
-struct Social {
-    username: String,
-    url: String,
-}
-
-#[derive(Serialize, Deserialize)]
-struct Company {
-    name: String,
-    location: String,
-}
-
-#[derive(Serialize, Deserialize)]
-struct Experience {
-    company: Company,
-    department: String,
-    position: String,
-    website: String,
-    #[serde(deserialize_with = "datetime_to_string")]
-    start_date: String,
-    #[serde(default)]
-    #[serde(deserialize_with = "datetime_to_option_string")]
-    end_date: Option<String>,
-    current: bool,
-    display: Vec<String>,
-    highlights: Vec<String>,
-}
-
-#[derive(Serialize, Deserialize)]
-struct GradePointAverage {
-    major: f64,
-    overall: f64,
-}
-
-#[derive(Serialize, Deserialize)]
-struct Education {
-    institution: String,
-    website: String,
-    major: String,
-    minor: String,
-    #[serde(deserialize_with = "datetime_to_string")]
-    start_date: String,
-    #[serde(default)]
-    #[serde(deserialize_with = "datetime_to_option_string")]
-    end_date: Option<String>,
-    current: bool,
-    gpa: GradePointAverage,
-    achievements: Vec<String>,
-    location: String,
-    degree: String,
-    latin_honors: String,
-}
-
-#[derive(Serialize, Deserialize)]
-struct Skill {
-    name: String,
-    level: String,
-    keywords: String,
-    category: String,
-}
-
-#[derive(Serialize, Deserialize)]
-struct Project {
-    name: String,
-    website: String,
-    source: String,
-    description: String,
-}
-
-#[derive(Serialize, Deserialize)]
-struct Author {
-    name: String,
-    email: String,
-    description: String,
-    summary: String,
-    phone: String,
-    website: String,
-    location: Location,
-    social: HashMap<String, Social>,
-    experiences: Vec<Experience>,
-    educations: Vec<Education>,
-    skills: Vec<Skill>,
-    projects: Vec<Project>,
-}
-
-fn datetime_to_string<'de, D>(deserializer: D) -> std::result::Result<String, D::Error>
-where
-    D: Deserializer<'de>,
-{
-    let datetime: Datetime = Deserialize::deserialize(deserializer)?;
-    Ok(datetime.to_string())
-}
-
-fn datetime_to_option_string<'de, D>(
-    deserializer: D,
-) -> std::result::Result<Option<String>, D::Error>
-where
-    D: Deserializer<'de>,
-{
-    let opt = Option::deserialize(deserializer)?;
-    Ok(opt.map(|d: Datetime| d.to_string()))
-}
-
-fn escape_latex(value: &Value, _: &HashMap<String, Value>) -> tera::Result<Value> {
-    let input = try_get_value!("escape_latex", "value", String, value);
-    let mut output = String::with_capacity(input.len() * 2);
-    for c in input.chars() {
-        match c {
-            '&' | '%' | '#' | '$' => output.push_str(format!("\\{}", c).as_str()),
-            _ => output.push(c),
-        }
-    }
-
-    Ok(Value::String(output))
+/// A byte count such as `512`, `64K` or `2M`.
+fn parse_size(s: &str) -> std::result::Result<u64, String> {
+    let (digits, multiplier) = match s.trim().to_ascii_uppercase() {
+        s if s.ends_with('K') => (s.trim_end_matches('K').to_string(), 1024),
+        s if s.ends_with('M') => (s.trim_end_matches('M').to_string(), 1024 * 1024),
+        s => (s, 1),
+    };
+    let invalid = || format!("`{}` isn't a size like 512, 64K or 2M", s);
+    let n = digits.parse::<u64>().map_err(|_| invalid())?;
+    n.checked_mul(multiplier)
+        .ok_or_else(|| format!("`{}` is too large a size", s))
+}
+
+fn fail(format: MessageFormat, message: String) -> ! {
+    match format {
+        MessageFormat::Human => error!("{}", message),
+        MessageFormat::Json => {
+            format.emit(&Message::Error {
+                message,
+                spans: Vec::new(),
+                fields: Vec::new(),
+            });
+            format.emit(&Message::BuildFinished { success: false });
+        }
+    }
+    ::std::process::exit(1);
+}
+
+/// The error and its sources, e.g. `rendering template resume.tex failed: ...: Variable
+/// `nme` not found`.
+fn error_chain(error: &Error) -> String {
+    let mut message = error.to_string();
+    let mut source = error.source();
+    while let Some(cause) = source {
+        message.push_str(&format!(": {}", cause));
+        source = cause.source();
+    }
+    message
 }
 
 fn main() {
-    let args = Args::parse();
-
-    let author: Author = toml::from_str(
-        fs::read_to_string(args.input_path)
-            .expect("couldn't read toml data file")
-            .as_str(),
-    )
-    .expect("couldn't parse toml data");
-
-    let mut tera = match Tera::new(
-        args.template_path
-            .to_str()
-            .expect("Template path must be present"),
-    ) {
-        Ok(t) => t,
-        Err(e) => {
-            print!("Parsing error(s): {}", e);
-            ::std::process::exit(1);
+    let matches = Cli::command().get_matches();
+    let mut cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    // clap 4.2 leaves an optional flatten out when it nests flattened args of
+    // its own, so read the build arguments directly.
+    if cli.command.is_none() && cli.build.is_none() {
+        cli.build = Some(BuildArgs::from_arg_matches(&matches).unwrap_or_else(|e| e.exit()));
+    }
+    logging::init(cli.quiet, cli.verbose);
+    if let Some(dir) = &cli.cache_dir {
+        // Before any threads start; the library reads it from there.
+        env::set_var(rsume::CACHE_DIR_VAR, dir);
+    }
+    let format = cli.message_format;
+    match (cli.command, cli.build) {
+        (
+            Some(Command::Migrate {
+                input_path,
+                output,
+                in_place,
+            }),
+            _,
+        ) => {
+            let output = in_place.then(|| input_path.clone()).or(output);
+            migrate(format, input_path, output)
+        }
+        (Some(Command::Render { render, output }), _) => render_only(format, *render, output),
+        (
+            Some(Command::Bench {
+                render,
+                runs,
+                warmup,
+                no_compile,
+            }),
+            _,
+        ) => bench(format, *render, runs, warmup, no_compile),
+        (
+            Some(Command::Compile {
+                tex_path,
+                output,
+                resource_dirs,
+                include_dirs,
+                synctex,
+                timeout,
+            }),
+            _,
+        ) => compile_only(
+            format,
+            cli.quiet,
+            tex_path,
+            CompileOptions {
+                resource_dirs,
+                include_dirs,
+                assets: Vec::new(),
+                output_dir: output,
+                synctex,
+                timeout: timeout.map(Duration::from_secs),
+                ..CompileOptions::default()
+            },
+        ),
+        (
+            Some(Command::CoverLetter {
+                build,
+                batch,
+                resume,
+            }),
+            _,
+        ) => cover_letters(format, cli.quiet, *build, batch, resume),
+        (
+            Some(Command::Context {
+                input_path,
+                template_path,
+                template,
+                data,
+            }),
+            _,
+        ) => context(format, input_path, template_path, &template, &data),
+        (
+            Some(Command::Show {
+                input_path,
+                template_path,
+                template,
+            }),
+            _,
+        ) => show(format, input_path, template_path, template),
+        (
+            Some(Command::Lint {
+                input_path,
+                strength,
+                disable,
+                max_grade,
+            }),
+            _,
+        ) => lint(
+            format,
+            input_path,
+            LintOptions {
+                strength,
+                disabled: disable,
+                max_grade: Some(max_grade),
+            },
+        ),
+        (
+            Some(Command::Stats {
+                input_path,
+                keywords,
+            }),
+            _,
+        ) => stats(format, input_path, keywords),
+        (Some(Command::SuggestSkills { input_path }), _) => suggest_skills(format, input_path),
+        (
+            Some(Command::Tailor {
+                input_path,
+                job,
+                variant_name,
+                output,
+            }),
+            _,
+        ) => tailor(format, input_path, job, variant_name, output),
+        (
+            Some(Command::Refresh {
+                input_path,
+                max_age,
+                output,
+                in_place,
+            }),
+            _,
+        ) => {
+            let output = in_place.then(|| input_path.clone()).or(output);
+            refresh(format, input_path, max_age, output)
+        }
+        (
+            Some(Command::Tag {
+                input_path,
+                tag,
+                note,
+            }),
+            _,
+        ) => tag_revision(format, input_path, tag, note),
+        (
+            Some(Command::DiffPdf {
+                old,
+                new,
+                output_dir,
+                mode,
+                dpi,
+            }),
+            _,
+        ) => diff_pdf(format, old, new, output_dir, DiffOptions { mode, dpi }),
+        (
+            Some(Command::Daemon {
+                socket,
+                max_data_size,
+                max_output_size,
+                compile_timeout,
+                root,
+                retries,
+                trusted,
+            }),
+            _,
+        ) => {
+            let limits = Limits {
+                max_data_size,
+                max_output_size,
+                root,
+                retries,
+            };
+            let rebuilder = if trusted {
+                Rebuilder::trusted(limits)
+            } else {
+                Rebuilder::untrusted(limits)
+            };
+            daemon(
+                format,
+                socket,
+                rebuilder.compile_timeout(Duration::from_secs(compile_timeout)),
+            )
+        }
+        (
+            Some(Command::Lsp {
+                template_path,
+                template_filename,
+                output_dir,
+            }),
+            _,
+        ) => {
+            let target =
+                template_path
+                    .zip(template_filename)
+                    .map(|(template_path, template_filename)| lsp::Target {
+                        template_path,
+                        template_filename,
+                        output_dir,
+                    });
+            lsp::serve(target)
+                .unwrap_or_else(|e| fail(format, format!("lost the editor connection: {}", e)));
+        }
+        (Some(Command::Bundle { command }), _) => match command {
+            BundleCommand::Export { dir, render } => export_bundle(format, dir, *render),
+            BundleCommand::Import { dir } => {
+                let count =
+                    rsume::import_bundle(&dir).unwrap_or_else(|e| fail(format, error_chain(&e)));
+                info!(files = count, "imported bundle");
+            }
+        },
+        (Some(Command::Import { command }), _) => match command {
+            ImportCommand::Csv { input_path, output } => import_csv(format, input_path, output),
+        },
+        (Some(Command::Template { command }), _) => match command {
+            TemplateCommand::Docs {
+                template_path,
+                template,
+            } => template_docs(format, template_path, template),
+            TemplateCommand::List => {
+                for builtin in BUILTINS {
+                    println!("builtin:{}\t{}", builtin.name, builtin.description);
+                }
+            }
+        },
+        (None, Some(args)) => build(format, cli.quiet, args),
+        (None, None) => unreachable!("clap requires the build arguments without a subcommand"),
+    }
+}
+
+fn render_only(format: MessageFormat, args: RenderArgs, output: Option<PathBuf>) {
+    // The messages go to stdout too, and the rendered template would break up
+    // their stream.
+    if format == MessageFormat::Json && output.is_none() {
+        fail(
+            format,
+            "render needs --output with --message-format json".to_string(),
+        );
+    }
+    let (resume, assets) = load(format, &args);
+    let templates =
+        load_templates(&args.template_path).unwrap_or_else(|e| fail(format, error_chain(&e)));
+    format.emit(&Message::RenderStarted {
+        template: &args.template_filename,
+    });
+    let rendered = templates
+        .render(&args.template_filename, &resume)
+        .unwrap_or_else(|e| fail(format, error_chain(&e)));
+    let Some(path) = output else {
+        print!("{}", rendered);
+        return;
+    };
+    let dir = path.parent().unwrap_or(Path::new(""));
+    fs::create_dir_all(dir)
+        .and_then(|()| fs::write(&path, rendered))
+        .map_err(|source| Error::Write {
+            path: path.clone(),
+            source,
+        })
+        .and_then(|()| assets.iter().try_for_each(|asset| asset.copy_into(dir)))
+        .unwrap_or_else(|e| fail(format, error_chain(&e)));
+    info!(path = %path.display(), "wrote the rendered template");
+    format.emit(&Message::Artifact { path });
+    format.emit(&Message::BuildFinished { success: true });
+}
+
+fn bench(format: MessageFormat, args: RenderArgs, runs: usize, warmup: usize, no_compile: bool) {
+    let time = |stage: &'static str, run: &mut dyn FnMut()| {
+        for _ in 0..warmup {
+            run();
         }
+        let samples = (0..runs)
+            .map(|_| {
+                let start = Instant::now();
+                run();
+                start.elapsed()
+            })
+            .collect();
+        info!(stage, runs, "timed");
+        StageTiming::new(stage, samples)
     };
-    tera.register_filter("escape_latex", escape_latex);
 
-    let rendered = tera
-        .render(
-            &args.template_filename,
-            &Context::from_serialize(&author)
-                .expect("couldn't convert author struct to tera context"),
+    let data = read(&args.input_path).unwrap_or_else(|e| fail(format, error_chain(&e)));
+    let mut stages = vec![time("parse", &mut || {
+        Resume::from_toml(&data).unwrap_or_else(|e| fail(format, error_chain(&e)));
+    })];
+    let (resume, assets) = load(format, &args);
+    let templates =
+        load_templates(&args.template_path).unwrap_or_else(|e| fail(format, error_chain(&e)));
+    let mut rendered = String::new();
+    stages.push(time("render", &mut || {
+        rendered = templates
+            .render(&args.template_filename, &resume)
+            .unwrap_or_else(|e| fail(format, error_chain(&e)));
+    }));
+    if !no_compile && !is_html(&args.template_filename) {
+        let output_dir = tempfile::tempdir().unwrap_or_else(|e| {
+            fail(
+                format,
+                format!("couldn't create a directory for the PDFs: {}", e),
+            )
+        });
+        let options = CompileOptions {
+            engine: templates.engine(),
+            resource_dirs: match builtin_name(&args.template_path) {
+                Some(_) => Vec::new(),
+                None => vec![template_dir(&args.template_path)],
+            },
+            include_dirs: Vec::new(),
+            assets,
+            output_dir: output_dir.path().to_path_buf(),
+            synctex: false,
+            ..CompileOptions::default()
+        };
+        let output_filename = resume.author.application.file_name(&args.template_filename);
+        stages.push(time("compile", &mut || {
+            compile(format, true, &output_filename, &rendered, &options).unwrap_or_else(|error| {
+                report_compile_error(
+                    format,
+                    &error,
+                    &rendered,
+                    &args.template_filename,
+                    templates.source(&args.template_filename),
+                    &resume.data,
+                )
+            });
+        }));
+    }
+
+    if format == MessageFormat::Json {
+        return format.emit(&Message::Bench { stages: &stages });
+    }
+    println!(
+        "{:<10}{:>6}{:>12}{:>12}{:>12}{:>12}",
+        "stage", "runs", "p50", "p90", "p99", "max"
+    );
+    for stage in &stages {
+        println!(
+            "{:<10}{:>6}{:>9.2} ms{:>9.2} ms{:>9.2} ms{:>9.2} ms",
+            stage.stage, stage.runs, stage.p50, stage.p90, stage.p99, stage.max
+        );
+    }
+}
+
+fn compile_only(
+    format: MessageFormat,
+    quiet: bool,
+    tex_path: PathBuf,
+    mut options: CompileOptions,
+) {
+    let content = read(&tex_path).unwrap_or_else(|e| fail(format, error_chain(&e)));
+    if options.resource_dirs.is_empty() {
+        options
+            .resource_dirs
+            .push(tex_path.parent().unwrap_or(Path::new("")).to_path_buf());
+    }
+    let tex_filename = tex_path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .into_owned();
+    format.emit(&Message::CompileStarted {
+        template: &tex_filename,
+    });
+    let compile_span = info_span!("compile", output = %options.output_dir.display()).entered();
+    let compiled =
+        compile(format, quiet, &tex_filename, &content, &options).unwrap_or_else(|error| {
+            let no_data = toml::Value::Table(Default::default());
+            report_compile_error(format, &error, &content, &tex_filename, None, &no_data)
+        });
+    info!(pages = compiled.pages, "wrote PDF");
+    drop(compile_span);
+
+    format.emit(&Message::Artifact {
+        path: options.output_path(&tex_filename, "pdf"),
+    });
+    if options.synctex {
+        format.emit(&Message::Artifact {
+            path: options.output_path(&tex_filename, "synctex.gz"),
+        });
+    }
+    format.emit(&Message::BuildFinished { success: true });
+}
+
+fn cover_letters(
+    format: MessageFormat,
+    quiet: bool,
+    args: BuildArgs,
+    batch: PathBuf,
+    resume: Option<String>,
+) {
+    let recipients = read(&batch)
+        .and_then(|source| Recipients::from_toml(&source))
+        .unwrap_or_else(|e| fail(format, error_chain(&e)))
+        .recipients;
+    info!(recipients = recipients.len(), "loaded recipients");
+    for recipient in recipients {
+        let mut letter = args.clone();
+        letter.render.recipient = recipient;
+        if let Some(resume) = &resume {
+            let mut tailored = letter.clone();
+            tailored.render.template_filename = resume.clone();
+            build(format, quiet, tailored);
+        }
+        build(format, quiet, letter);
+    }
+}
+
+fn migrate(format: MessageFormat, input_path: PathBuf, output: Option<PathBuf>) {
+    let migration = read(&input_path)
+        .and_then(|data| rsume::migrate(&data))
+        .unwrap_or_else(|e| fail(format, error_chain(&e)));
+    if migration.from == SCHEMA_VERSION {
+        info!(version = SCHEMA_VERSION, "already current");
+    } else {
+        info!(from = migration.from, to = SCHEMA_VERSION, "migrated");
+    }
+    match output {
+        Some(path) => fs::write(&path, migration.source)
+            .map_err(|source| Error::Write { path, source })
+            .unwrap_or_else(|e| fail(format, error_chain(&e))),
+        None => print!("{}", migration.source),
+    }
+}
+
+fn context(
+    format: MessageFormat,
+    input_path: PathBuf,
+    template_path: Option<PathBuf>,
+    template: &str,
+    data: &DataArgs,
+) {
+    let context = read(&input_path)
+        .and_then(|source| {
+            let mut resume = Resume::from_toml(&source)?;
+            resume.author.revision = rsume::latest_revision(&input_path, &source)?;
+            resume.author.git = rsume::git_info(&input_path);
+            data.apply(&mut resume.author)?;
+            let templates = match &template_path {
+                Some(path) => load_templates(path)?,
+                None => Templates::from_sources([])?,
+            };
+            templates.context(template, &resume)
+        })
+        .unwrap_or_else(|e| fail(format, error_chain(&e)));
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&context).expect("JSON values always serialize")
+    );
+}
+
+fn show(
+    format: MessageFormat,
+    input_path: PathBuf,
+    template_path: Option<PathBuf>,
+    template: String,
+) {
+    let text = read(&input_path)
+        .and_then(|data| Resume::from_toml(&data))
+        .and_then(|resume| {
+            let templates = match &template_path {
+                Some(path) => load_templates(path)?,
+                None => Templates::builtin("text")?,
+            };
+            templates.render(&template, &resume)
+        })
+        .unwrap_or_else(|e| fail(format, error_chain(&e)));
+    let color = io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none();
+    for (i, line) in text.lines().enumerate() {
+        if !color {
+            println!("{}", line);
+        } else if i == 0 {
+            println!("\x1b[1m{}\x1b[0m", line);
+        } else if line.chars().any(char::is_alphabetic) && !line.chars().any(char::is_lowercase) {
+            // Section headings are the all-caps lines.
+            println!("\x1b[1;36m{}\x1b[0m", line);
+        } else if let Some(bullet) = line.trim_start().strip_prefix('•') {
+            let indent = &line[..line.len() - line.trim_start().len()];
+            println!("{}\x1b[36m•\x1b[0m{}", indent, bullet);
+        } else if line.starts_with("  ") {
+            println!("\x1b[2m{}\x1b[0m", line);
+        } else {
+            println!("{}", line);
+        }
+    }
+}
+
+fn lint(format: MessageFormat, input_path: PathBuf, options: LintOptions) {
+    let findings = read(&input_path)
+        .and_then(|data| Resume::from_toml(&data))
+        .unwrap_or_else(|e| fail(format, error_chain(&e)))
+        .author
+        .lint(&options);
+    report(format, &findings);
+    info!(findings = findings.len(), "linted");
+}
+
+fn report(format: MessageFormat, findings: &[Finding]) {
+    for finding in findings {
+        match format {
+            MessageFormat::Human => {
+                warn!("{}: {} [{}]", finding.field, finding.message, finding.rule)
+            }
+            MessageFormat::Json => format.emit(&Message::Lint { finding }),
+        }
+    }
+}
+
+fn stats(format: MessageFormat, input_path: PathBuf, keywords: usize) {
+    let stats = read(&input_path)
+        .and_then(|data| Resume::from_toml(&data))
+        .unwrap_or_else(|e| fail(format, error_chain(&e)))
+        .author
+        .stats(keywords);
+    if format == MessageFormat::Json {
+        return format.emit(&Message::Stats { stats: &stats });
+    }
+    println!("Words per section");
+    for section in &stats.sections {
+        println!("  {:<14}{:>6}", section.name, section.words);
+    }
+    println!("\nBullets per role");
+    for role in &stats.roles {
+        let mut qualifiers = Vec::new();
+        if role.remote {
+            qualifiers.push("remote".to_string());
+        }
+        if let Some(kind) = role.employment_type {
+            qualifiers.push(kind.as_str().to_string());
+        }
+        let mut role_name = format!("{} at {}", role.position, role.company);
+        if !qualifiers.is_empty() {
+            role_name.push_str(&format!(" ({})", qualifiers.join(", ")));
+        }
+        println!(
+            "  {:<40}{:>3} bullets, {:.1} words each",
+            role_name, role.bullets, role.average_bullet_words
+        );
+    }
+    if stats.remote_roles > 0 {
+        println!(
+            "  {} of {} roles remote",
+            stats.remote_roles,
+            stats.roles.len()
+        );
+    }
+    println!(
+        "\nAverage bullet length: {:.1} words",
+        stats.average_bullet_words
+    );
+    if let Some(level) = stats.reading_level {
+        println!("Reading level: grade {:.1}", level);
+    }
+    if !stats.readability.is_empty() {
+        println!("\nReading level per text, hardest first");
+        for text in &stats.readability {
+            println!("  {:<32}{:>5.1}", text.field, text.grade);
+        }
+    }
+    if !stats.keywords.is_empty() {
+        println!("\nMost frequent keywords");
+        for (keyword, count) in &stats.keywords {
+            println!("  {:<20}{:>4}", keyword, count);
+        }
+    }
+}
+
+fn suggest_skills(format: MessageFormat, input_path: PathBuf) {
+    let suggestions = read(&input_path)
+        .and_then(|data| Resume::from_toml(&data))
+        .unwrap_or_else(|e| fail(format, error_chain(&e)))
+        .author
+        .suggest_skills();
+    if format == MessageFormat::Json {
+        return format.emit(&Message::SkillSuggestions {
+            suggestions: &suggestions,
+        });
+    }
+    if !suggestions.missing.is_empty() {
+        println!("Used but not listed as skills");
+        for suggestion in &suggestions.missing {
+            println!("  {:<20}{}", suggestion.skill, suggestion.fields.join(", "));
+        }
+    }
+    if !suggestions.unevidenced.is_empty() {
+        if !suggestions.missing.is_empty() {
+            println!();
+        }
+        println!("Listed but never shown in an experience");
+        for suggestion in &suggestions.unevidenced {
+            println!("  {:<20}{}", suggestion.skill, suggestion.fields.join(", "));
+        }
+    }
+}
+
+fn tailor(
+    format: MessageFormat,
+    input_path: PathBuf,
+    job: PathBuf,
+    variant_name: String,
+    output: Option<PathBuf>,
+) {
+    let author = read(&input_path)
+        .and_then(|data| Resume::from_toml(&data))
+        .unwrap_or_else(|e| fail(format, error_chain(&e)))
+        .author;
+    let posting = read(&job).unwrap_or_else(|e| fail(format, error_chain(&e)));
+    let variant = author.propose_variant(&posting);
+    let mut variants = toml::value::Table::new();
+    variants.insert(
+        variant_name,
+        toml::Value::try_from(&variant).expect("variants always serialize"),
+    );
+    let mut document = toml::value::Table::new();
+    document.insert("variants".into(), toml::Value::Table(variants));
+    let table = format!(
+        "# Proposed by `rsume tailor` for {}; edit before building with it\n{}",
+        job.display(),
+        toml::to_string(&document).expect("TOML values always serialize")
+    );
+    match output {
+        Some(path) => fs::write(&path, table)
+            .map_err(|source| Error::Write { path, source })
+            .unwrap_or_else(|e| fail(format, error_chain(&e))),
+        None => print!("{}", table),
+    }
+}
+
+fn export_bundle(format: MessageFormat, dir: PathBuf, args: RenderArgs) {
+    if is_html(&args.template_filename) {
+        fail(format, "HTML templates don't use TeX files".to_string());
+    }
+    let (resume, assets) = load(format, &args);
+    let templates =
+        load_templates(&args.template_path).unwrap_or_else(|e| fail(format, error_chain(&e)));
+    let rendered = templates
+        .render(&args.template_filename, &resume)
+        .unwrap_or_else(|e| fail(format, error_chain(&e)));
+    let options = CompileOptions {
+        engine: templates.engine(),
+        resource_dirs: match builtin_name(&args.template_path) {
+            Some(_) => Vec::new(),
+            None => vec![template_dir(&args.template_path)],
+        },
+        assets,
+        ..CompileOptions::default()
+    };
+    let mut tracing_status = TracingStatusBackend;
+    let mut json_status = JsonStatusBackend;
+    let status: &mut dyn status::StatusBackend = match format {
+        MessageFormat::Human => &mut tracing_status,
+        MessageFormat::Json => &mut json_status,
+    };
+    let output_filename = resume.author.application.file_name(&args.template_filename);
+    let files = rsume::export_bundle(&output_filename, &rendered, &options, &dir, status)
+        .unwrap_or_else(|error| {
+            report_compile_error(
+                format,
+                &error,
+                &rendered,
+                &args.template_filename,
+                templates.source(&args.template_filename),
+                &resume.data,
+            )
+        });
+    info!(files = files.len(), dir = %dir.display(), "exported bundle");
+}
+
+fn refresh(format: MessageFormat, input_path: PathBuf, max_age: u32, output: Option<PathBuf>) {
+    let refresh = read(&input_path)
+        .and_then(|data| rsume::refresh_stats(&data, max_age))
+        .unwrap_or_else(|e| fail(format, error_chain(&e)));
+    info!(projects = ?refresh.refreshed, "refreshed");
+    match output {
+        Some(path) => fs::write(&path, refresh.source)
+            .map_err(|source| Error::Write { path, source })
+            .unwrap_or_else(|e| fail(format, error_chain(&e))),
+        None => print!("{}", refresh.source),
+    }
+}
+
+fn tag_revision(format: MessageFormat, input_path: PathBuf, tag: String, note: Option<String>) {
+    let path = read(&input_path)
+        .and_then(|data| rsume::tag_revision(&input_path, &data, &tag, note.as_deref()))
+        .unwrap_or_else(|e| fail(format, error_chain(&e)));
+    info!(%tag, history = %path.display(), "tagged");
+}
+
+fn diff_pdf(
+    format: MessageFormat,
+    old: PathBuf,
+    new: PathBuf,
+    output_dir: PathBuf,
+    options: DiffOptions,
+) {
+    let diffs = diff_pdfs(&old, &new, &output_dir, &options)
+        .unwrap_or_else(|e| fail(format, error_chain(&e)));
+    let mut changed = false;
+    for diff in &diffs {
+        let Some(image) = &diff.image else {
+            continue;
+        };
+        changed = true;
+        let percent = 100.0 * diff.changed_pixels as f64 / diff.total_pixels.max(1) as f64;
+        match format {
+            MessageFormat::Human => println!(
+                "page {}: {:.2}% of pixels changed, see {}",
+                diff.page,
+                percent,
+                image.display()
+            ),
+            MessageFormat::Json => format.emit(&Message::PageChanged {
+                page: diff.page,
+                changed_pixels: diff.changed_pixels,
+                total_pixels: diff.total_pixels,
+                image: image.clone(),
+            }),
+        }
+    }
+    info!(pages = diffs.len(), "compared PDFs");
+    if changed {
+        ::std::process::exit(1);
+    }
+}
+
+#[cfg(unix)]
+fn daemon(format: MessageFormat, socket: PathBuf, rebuilder: Rebuilder) {
+    daemon::serve(&socket, rebuilder).unwrap_or_else(|e| fail(format, error_chain(&e)));
+}
+
+#[cfg(not(unix))]
+fn daemon(format: MessageFormat, _socket: PathBuf, _rebuilder: Rebuilder) {
+    fail(format, "rsume daemon needs Unix domain sockets".to_string());
+}
+
+fn import_csv(format: MessageFormat, input_path: PathBuf, output: Option<PathBuf>) {
+    let experiences = read(&input_path)
+        .and_then(|source| rsume::experiences_from_csv(&source))
+        .unwrap_or_else(|e| fail(format, error_chain(&e)));
+    match output {
+        Some(path) => fs::write(&path, experiences)
+            .map_err(|source| Error::Write { path, source })
+            .unwrap_or_else(|e| fail(format, error_chain(&e))),
+        None => print!("{}", experiences),
+    }
+}
+
+fn read(path: &Path) -> rsume::Result<String> {
+    fs::read_to_string(path).map_err(|source| Error::Read {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+fn template_docs(format: MessageFormat, template_path: Option<PathBuf>, template: Option<String>) {
+    let templates = template_path
+        .map(|path| load_templates(&path).unwrap_or_else(|e| fail(format, error_chain(&e))));
+    let used = match (&templates, &template) {
+        (Some(templates), Some(name)) => Some((
+            name.as_str(),
+            templates
+                .used_variables(name)
+                .unwrap_or_else(|| fail(format, format!("no template named {}", name))),
+        )),
+        _ => None,
+    };
+    print!(
+        "{}",
+        docs::template_reference(
+            used.as_ref().map(|(name, used)| (*name, used)),
+            templates.as_ref().and_then(Templates::manifest),
         )
-        .expect("rending template failed");
-
-    // File::create(Path::new("rendered.tex"))
-    //     .expect("cannot create file")
-    //     .write_all(rendered.as_bytes())
-    //     .expect("failed to write rendered template");
-
-    latex_to_pdf(
-        args.tex_root,
-        args.template_filename,
-        rendered,
-        args.output_root,
-    )
-    .expect("processing failed");
-}
-
-pub fn latex_to_pdf(
-    tex_root: PathBuf,
-    tex_filename: String,
-    content: String,
-    output_root: PathBuf,
-) -> Result<()> {
-    let mut status = status::NoopStatusBackend::default();
-
-    let auto_create_config_file = false;
-    let config = ctry!(config::PersistentConfig::open(auto_create_config_file);
-                       "failed to open the default configuration file");
-
-    let only_cached = false;
-    let bundle = ctry!(config.default_bundle(only_cached, &mut status);
-                       "failed to load the default resource bundle");
-
-    let format_cache_path = ctry!(config.format_cache_path();
-                                  "failed to set up the format cache");
-
-    {
-        // Looking forward to non-lexical lifetimes!
-        let mut sb = driver::ProcessingSessionBuilder::default();
-        sb.bundle(bundle)
-            .primary_input_buffer(content.as_bytes())
-            .filesystem_root(tex_root.as_path().parent().expect("filepath has no parent"))
-            .tex_input_name(&tex_filename)
-            .format_name("latex")
-            .format_cache_path(format_cache_path)
-            .keep_logs(false)
-            .keep_intermediates(false)
-            .print_stdout(false)
-            .output_format(driver::OutputFormat::Pdf)
-            .output_dir(output_root);
-
-        let mut sess =
-            ctry!(sb.create(&mut status); "failed to initialize the LaTeX processing session");
-        ctry!(sess.run(&mut status); "the LaTeX engine failed");
-        sess.into_file_data()
+    );
+}
+
+fn warning(format: MessageFormat, message: String) {
+    match format {
+        MessageFormat::Human => warn!("{}", message),
+        MessageFormat::Json => format.emit(&Message::Warning { message }),
+    }
+}
+
+/// Runs Tectonic with progress reporting suited to `format`.
+fn compile(
+    format: MessageFormat,
+    quiet: bool,
+    tex_filename: &str,
+    content: &str,
+    options: &CompileOptions,
+) -> rsume::Result<Compiled> {
+    let mut progress = (format == MessageFormat::Human && !quiet)
+        .then(|| ProgressStatusBackend::new(TracingStatusBackend, "compiling"));
+    let mut tracing_status = TracingStatusBackend;
+    let mut json_status = JsonStatusBackend;
+    let status: &mut dyn status::StatusBackend = match (&mut progress, format) {
+        (Some(progress), _) => progress,
+        (None, MessageFormat::Human) => &mut tracing_status,
+        (None, MessageFormat::Json) => &mut json_status,
     };
+    // The engine only notices the timeout when it opens a file.
+    let _watchdog = options.timeout.map(|timeout| {
+        watchdog::watch(timeout + watchdog::GRACE, move || {
+            fail(format, Error::TimedOut(timeout).to_string())
+        })
+    });
+    let result = compile_pdf(tex_filename, content, options, status);
+    if let Some(progress) = &progress {
+        progress.finish();
+    }
+    result
+}
 
-    Result::Ok(())
+/// Reports a failed compile, mapping the first TeX error back to the template
+/// and data where possible, and exits. `template_source` and `data` let the
+/// error point back past `rendered` to the template line and data fields
+/// involved, when there are any.
+fn report_compile_error(
+    format: MessageFormat,
+    error: &Error,
+    rendered: &str,
+    template_filename: &str,
+    template_source: Option<String>,
+    data: &toml::Value,
+) -> ! {
+    let log_error = match error {
+        Error::Compile { log_error, .. } => log_error.as_ref(),
+        _ => None,
+    };
+    match format {
+        MessageFormat::Human => {
+            error!("{}", error_chain(error));
+            if let Some(log_error) = log_error {
+                error!(
+                    "{}",
+                    sourcemap::describe(
+                        log_error,
+                        rendered,
+                        template_filename,
+                        template_source.as_deref(),
+                        data,
+                    )
+                    .trim_end()
+                );
+            }
+        }
+        MessageFormat::Json => {
+            let mut message = error_chain(error);
+            let mut spans = Vec::new();
+            let mut fields = Vec::new();
+            if let Some(log_error) = log_error {
+                message = format!("{}: {}", message, log_error.message);
+                if let Some(located) =
+                    sourcemap::locate(log_error, rendered, template_source.as_deref(), data)
+                {
+                    spans.push(Span {
+                        file: template_filename,
+                        line: located.line,
+                        label: "rendered",
+                        text: Some(located.rendered_line),
+                    });
+                    if let Some(line) = located.template_line {
+                        spans.push(Span {
+                            file: template_filename,
+                            line,
+                            label: "template",
+                            text: None,
+                        });
+                    }
+                    fields = located.fields;
+                }
+            }
+            format.emit(&Message::Error {
+                message,
+                spans,
+                fields,
+            });
+            format.emit(&Message::BuildFinished { success: false });
+        }
+    }
+    ::std::process::exit(1);
+}
+
+/// HTML templates skip the LaTeX stage: the rendered page and the assets it
+/// refers to are written straight to the output directory.
+fn build_html(
+    format: MessageFormat,
+    templates: &Templates,
+    template_filename: &str,
+    output_filename: &str,
+    resume: &Resume,
+    options: &CompileOptions,
+) -> Vec<PathBuf> {
+    let render_span = info_span!("render", template = %template_filename).entered();
+    format.emit(&Message::RenderStarted {
+        template: template_filename,
+    });
+    let rendered = templates
+        .render(template_filename, resume)
+        .unwrap_or_else(|e| fail(format, error_chain(&e)));
+    info!(bytes = rendered.len(), "rendered template");
+    drop(render_span);
+
+    let path = options.output_dir.join(output_filename);
+    fs::create_dir_all(&options.output_dir)
+        .and_then(|()| fs::write(&path, rendered))
+        .map_err(|source| Error::Write {
+            path: path.clone(),
+            source,
+        })
+        .and_then(|()| {
+            options
+                .assets
+                .iter()
+                .try_for_each(|asset| asset.copy_into(&options.output_dir))
+        })
+        .unwrap_or_else(|e| fail(format, error_chain(&e)));
+    info!(path = %path.display(), "wrote HTML");
+    let mut artifacts = vec![path];
+
+    if resume.author.html.json_resume {
+        let path = options.output_dir.join(JSON_RESUME_FILE);
+        let json = serde_json::to_string_pretty(&resume.to_json_resume())
+            .expect("JSON values always serialize");
+        fs::write(&path, json)
+            .map_err(|source| Error::Write {
+                path: path.clone(),
+                source,
+            })
+            .unwrap_or_else(|e| fail(format, error_chain(&e)));
+        info!(path = %path.display(), "wrote JSON Resume");
+        artifacts.push(path);
+    }
+    artifacts
+}
+
+/// Reports the artifacts of a successful build, the first being the main output,
+/// and logs tailored builds to the applications log.
+fn finish(
+    format: MessageFormat,
+    template: (&Path, &str),
+    options: &CompileOptions,
+    application: &Application,
+    variant: Option<&str>,
+    artifacts: Vec<PathBuf>,
+) {
+    if application.company.is_some() || application.role.is_some() {
+        let template = format!("{}/{}", template.0.display(), template.1);
+        let path = applications::record(
+            &options.output_dir,
+            application,
+            variant,
+            template,
+            &artifacts[0],
+        )
+        .unwrap_or_else(|e| fail(format, error_chain(&e)));
+        info!(path = %path.display(), "logged the application");
+    }
+    for path in artifacts {
+        format.emit(&Message::Artifact { path });
+    }
+    format.emit(&Message::BuildFinished { success: true });
+}
+
+/// Loads the data with the command line's overrides applied, returning it with
+/// the files it refers to.
+fn load(format: MessageFormat, args: &RenderArgs) -> (Resume, Vec<Asset>) {
+    let load_span = info_span!("load", path = %args.input_path.display()).entered();
+    let mut resume = read(&args.input_path)
+        .and_then(|data| {
+            let mut resume = Resume::from_toml(&data)?;
+            resume.author.revision = rsume::latest_revision(&args.input_path, &data)?;
+            resume.author.git = rsume::git_info(&args.input_path);
+            Ok(resume)
+        })
+        .unwrap_or_else(|e| fail(format, error_chain(&e)));
+    args.data
+        .apply(&mut resume.author)
+        .unwrap_or_else(|e| fail(format, error_chain(&e)));
+    report(format, &resume.author.theme.contrast());
+    if let Some(font) = &args.font {
+        resume.author.theme.font = Some(font.clone());
+    }
+    for path in &args.font_files {
+        // Relative to where rsume runs, unlike paths in the data file.
+        let path = fs::canonicalize(path).unwrap_or_else(|e| {
+            fail(
+                format,
+                format!("couldn't find font file {}: {}", path.display(), e),
+            )
+        });
+        resume
+            .author
+            .theme
+            .font_files
+            .push(path.to_string_lossy().into_owned());
+    }
+    resume
+        .fetch_avatar()
+        .unwrap_or_else(|e| fail(format, error_chain(&e)));
+    let assets = resume
+        .stage_assets(args.input_path.parent().unwrap_or(Path::new("")))
+        .unwrap_or_else(|e| fail(format, error_chain(&e)));
+    let recipient = args.recipient.clone();
+    resume.author.application = Application {
+        company: args.company.clone().or(recipient.company),
+        role: args.role.clone().or(recipient.role),
+        ..recipient
+    }
+    .or(&resume.author.application);
+    debug!(
+        experiences = resume.author.experiences.len(),
+        educations = resume.author.educations.len(),
+        "loaded resume data"
+    );
+    drop(load_span);
+    (resume, assets)
+}
+
+fn build(format: MessageFormat, quiet: bool, args: BuildArgs) {
+    let BuildArgs {
+        render: args,
+        output_root,
+        resource_dirs,
+        include_dirs,
+        synctex,
+        max_pages,
+        optimize,
+        sign,
+        cert,
+        accessible,
+        require_clean,
+        timeout,
+        retries,
+    } = args;
+    // Before the compile, so a wrong password doesn't waste one.
+    let signer = cert.filter(|_| sign).map(|cert| {
+        let password = env::var("RSUME_CERT_PASSWORD").unwrap_or_default();
+        Signer::from_pkcs12(&cert, &password).unwrap_or_else(|e| fail(format, error_chain(&e)))
+    });
+    // Built-in templates only use packages from the Tectonic bundle.
+    let resource_dirs = if resource_dirs.is_empty() && builtin_name(&args.template_path).is_none() {
+        vec![template_dir(&args.template_path)]
+    } else {
+        resource_dirs
+    };
+    let (mut resume, assets) = load(format, &args);
+    if let Some(git) = resume.author.git.as_ref().filter(|git| git.dirty) {
+        let message = format!(
+            "{} has uncommitted changes, so the output won't match commit {}",
+            args.input_path.display(),
+            git.short_sha
+        );
+        if require_clean {
+            fail(format, message);
+        }
+        warning(format, message);
+    }
+    if accessible {
+        resume.author.pdf.accessible = true;
+    }
+    if resume.author.pdf.accessible {
+        report(format, &resume.author.accessibility());
+        if !is_html(&args.template_filename) {
+            warning(
+                format,
+                "XeTeX can't write a tagged PDF, so only the language, title and image alternative text are set".to_string(),
+            );
+        }
+    }
+
+    let templates =
+        load_templates(&args.template_path).unwrap_or_else(|e| fail(format, error_chain(&e)));
+    debug!(templates = templates.len(), "loaded templates");
+
+    let options = CompileOptions {
+        engine: templates.engine(),
+        resource_dirs,
+        include_dirs,
+        assets,
+        output_dir: output_root,
+        synctex,
+        timeout: timeout.map(Duration::from_secs),
+        retries,
+        ..CompileOptions::default()
+    };
+    let output_filename = resume.author.application.file_name(&args.template_filename);
+    if is_html(&args.template_filename) {
+        if optimize || signer.is_some() {
+            warning(
+                format,
+                "--optimize and --sign only apply to PDFs".to_string(),
+            );
+        }
+        let artifacts = build_html(
+            format,
+            &templates,
+            &args.template_filename,
+            &output_filename,
+            &resume,
+            &options,
+        );
+        return finish(
+            format,
+            (&args.template_path, &args.template_filename),
+            &options,
+            &resume.author.application,
+            args.data.variant.as_deref(),
+            artifacts,
+        );
+    }
+
+    let initial_tighten = resume.author.tighten;
+    let pages = loop {
+        let render_span = info_span!("render", template = %args.template_filename).entered();
+        format.emit(&Message::RenderStarted {
+            template: &args.template_filename,
+        });
+        let rendered = templates
+            .render(&args.template_filename, &resume)
+            .unwrap_or_else(|e| fail(format, error_chain(&e)));
+        info!(bytes = rendered.len(), "rendered template");
+        drop(render_span);
+
+        format.emit(&Message::CompileStarted {
+            template: &args.template_filename,
+        });
+        let compile_span = info_span!("compile", output = %options.output_dir.display()).entered();
+        let compiled = compile(format, quiet, &output_filename, &rendered, &options)
+            .unwrap_or_else(|error| {
+                report_compile_error(
+                    format,
+                    &error,
+                    &rendered,
+                    &args.template_filename,
+                    templates.source(&args.template_filename),
+                    &resume.data,
+                )
+            });
+        info!(pages = compiled.pages, "wrote PDF");
+        drop(compile_span);
+
+        match (max_pages, compiled.pages) {
+            (Some(max), Some(pages)) if pages as u64 > max => {
+                if resume.author.tighten >= MAX_TIGHTEN {
+                    fail(
+                        format,
+                        format!(
+                            "the PDF has {} pages, more than --max-pages {}, even with the tightest spacing",
+                            pages, max
+                        ),
+                    );
+                }
+                resume.author.tighten += 1;
+                info!(
+                    pages,
+                    tighten = resume.author.tighten,
+                    "over the page limit, tightening spacing"
+                );
+            }
+            (Some(_), None) => {
+                warning(
+                    format,
+                    "couldn't read the page count, so --max-pages wasn't checked".to_string(),
+                );
+                break None;
+            }
+            (_, pages) => break pages,
+        }
+    };
+    if resume.author.tighten > initial_tighten {
+        let spacing = Spacing::for_level(resume.author.tighten);
+        warning(
+            format,
+            format!(
+                "tightened spacing to level {} to fit {} page(s): vertical spacing {:.0}%, margins {:.0}%",
+                resume.author.tighten,
+                pages.unwrap_or_default(),
+                spacing.vertical * 100.0,
+                spacing.margin * 100.0
+            ),
+        );
+    }
+
+    let pdf = options.output_path(&output_filename, "pdf");
+    if optimize {
+        let optimized = optimize_pdf(&pdf).unwrap_or_else(|e| fail(format, error_chain(&e)));
+        info!(
+            before = optimized.before,
+            after = optimized.after,
+            "optimized PDF"
+        );
+    }
+    if let Some(signer) = &signer {
+        sign_pdf(&pdf, signer).unwrap_or_else(|e| fail(format, error_chain(&e)));
+        info!(path = %pdf.display(), "signed PDF");
+    }
+    let mut artifacts = vec![pdf];
+    if options.synctex {
+        artifacts.push(options.output_path(&output_filename, "synctex.gz"));
+    }
+    finish(
+        format,
+        (&args.template_path, &args.template_filename),
+        &options,
+        &resume.author.application,
+        args.data.variant.as_deref(),
+        artifacts,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes_take_k_and_m_suffixes_without_overflowing() {
+        assert_eq!(parse_size("512"), Ok(512));
+        assert_eq!(parse_size(" 64k "), Ok(64 * 1024));
+        assert_eq!(parse_size("2M"), Ok(2 * 1024 * 1024));
+        assert!(parse_size("2G").is_err());
+        assert_eq!(
+            parse_size("18446744073709551615M"),
+            Err("`18446744073709551615M` is too large a size".to_string())
+        );
+    }
 }
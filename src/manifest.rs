@@ -0,0 +1,109 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::{Author, Error, Layout, Result};
+
+/// The file in a template directory declaring what its templates understand.
+pub const MANIFEST_FILE: &str = "manifest.toml";
+
+/// A template directory's `manifest.toml`, e.g.
+///
+/// ```toml
+/// [display]
+/// hide-department = "Leave out the department line"
+///
+/// [layout]
+/// skills = "sidebar"
+///
+/// [engine]
+/// reruns = 2
+/// ```
+#[derive(Debug, Default, Deserialize)]
+pub struct Manifest {
+    /// The keys `Experience.display` may contain, with what each one does
+    #[serde(default)]
+    pub display: BTreeMap<String, String>,
+    /// Where sections go unless the data says otherwise
+    #[serde(default)]
+    pub layout: Layout,
+    /// How Tectonic compiles the templates
+    #[serde(default)]
+    pub engine: Engine,
+}
+
+/// Tectonic's settings, for templates needing more than its defaults, such as
+/// an extra pass for a table of contents or a bibliography.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Engine {
+    /// Format the engine starts from, built from the bundle's
+    /// `tectonic-format-<name>.tex`; `latex` when unset
+    pub format: Option<String>,
+    /// Exactly how many times to rerun TeX after the first run; when unset,
+    /// Tectonic reruns until cross-references settle
+    pub reruns: Option<usize>,
+    pub pass: Pass,
+}
+
+impl Engine {
+    pub const DEFAULT_FORMAT: &'static str = "latex";
+
+    pub fn format(&self) -> &str {
+        self.format.as_deref().unwrap_or(Engine::DEFAULT_FORMAT)
+    }
+}
+
+/// Which programs a compile runs. There's no MakeIndex in Tectonic, so
+/// indexes have to be built in TeX, e.g. with `imakeidx`'s `noautomatic`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Pass {
+    /// TeX, then BibTeX if the `.aux` file asks for it, rerunning TeX as needed
+    #[default]
+    Default,
+    /// TeX once, without BibTeX or reruns
+    Tex,
+    /// BibTeX first, for templates whose first TeX run needs the `.bbl`
+    BibtexFirst,
+}
+
+impl Manifest {
+    /// Reads `manifest.toml` from a template directory; `None` if there isn't one.
+    pub fn load(dir: &Path) -> Result<Option<Manifest>> {
+        let path = dir.join(MANIFEST_FILE);
+        let source = match fs::read_to_string(&path) {
+            Ok(source) => source,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(source) => return Err(Error::Read { path, source }),
+        };
+        Manifest::from_toml(&source, path).map(Some)
+    }
+
+    /// Parses manifest source; `path` is only used in errors.
+    pub fn from_toml(source: &str, path: PathBuf) -> Result<Manifest> {
+        toml::from_str(source).map_err(|source| Error::Manifest { path, source })
+    }
+
+    /// Fails on the first display key the templates don't declare, which would
+    /// otherwise just render nothing.
+    pub fn validate(&self, author: &Author) -> Result<()> {
+        for (index, experience) in author.experiences.iter().enumerate() {
+            if let Some(key) = experience
+                .display
+                .iter()
+                .find(|key| !self.display.contains_key(*key))
+            {
+                return Err(Error::UnknownDisplayKey {
+                    key: key.clone(),
+                    field: format!("experiences[{}].display", index),
+                    declared: self.display.keys().cloned().collect(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,158 @@
+//! The small subset of Markdown that shows up in summaries and highlights,
+//! converted to LaTeX, HTML or plain text: paragraphs, `-`/`*` bullet lists, `**bold**`,
+//! `*emphasis*`, `` `code` `` and `[text](url)` links. Everything else passes
+//! through as escaped text.
+
+use crate::render::escape_latex_str;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Latex,
+    Html,
+    /// Markup dropped, links as `label (url)`, bullets as `•`
+    Text,
+}
+
+pub fn to_latex(source: &str) -> String {
+    convert(source, Format::Latex)
+}
+
+pub fn to_html(source: &str) -> String {
+    convert(source, Format::Html)
+}
+
+pub fn to_text(source: &str) -> String {
+    convert(source, Format::Text)
+}
+
+fn convert(source: &str, format: Format) -> String {
+    let mut blocks = Vec::new();
+    let mut paragraph: Vec<&str> = Vec::new();
+    let mut items: Vec<&str> = Vec::new();
+    for line in source.lines().map(str::trim) {
+        if let Some(item) = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")) {
+            flush_paragraph(&mut paragraph, &mut blocks, format);
+            items.push(item);
+        } else if line.is_empty() {
+            flush_paragraph(&mut paragraph, &mut blocks, format);
+            flush_items(&mut items, &mut blocks, format);
+        } else {
+            flush_items(&mut items, &mut blocks, format);
+            paragraph.push(line);
+        }
+    }
+    flush_paragraph(&mut paragraph, &mut blocks, format);
+    flush_items(&mut items, &mut blocks, format);
+    blocks.join("\n\n")
+}
+
+fn flush_paragraph(lines: &mut Vec<&str>, blocks: &mut Vec<String>, format: Format) {
+    if !lines.is_empty() {
+        let text = inline(&lines.join(" "), format);
+        blocks.push(match format {
+            Format::Latex | Format::Text => text,
+            Format::Html => format!("<p>{}</p>", text),
+        });
+        lines.clear();
+    }
+}
+
+fn flush_items(items: &mut Vec<&str>, blocks: &mut Vec<String>, format: Format) {
+    if !items.is_empty() {
+        let (open, item, close) = match format {
+            Format::Latex => ("\\begin{itemize}\n", "  \\item ", "\\end{itemize}"),
+            Format::Html => ("<ul>\n", "  <li>", "</ul>"),
+            Format::Text => ("", "• ", ""),
+        };
+        let mut list = String::from(open);
+        for text in items.iter() {
+            list.push_str(item);
+            list.push_str(&inline(text, format));
+            if format == Format::Html {
+                list.push_str("</li>");
+            }
+            list.push('\n');
+        }
+        list.push_str(close);
+        blocks.push(list.trim_end().to_string());
+        items.clear();
+    }
+}
+
+fn inline(text: &str, format: Format) -> String {
+    let wrap = |latex: &str, html: &str, inner: String| match format {
+        Format::Latex => format!("\\{}{{{}}}", latex, inner),
+        Format::Html => format!("<{1}>{0}</{1}>", inner, html),
+        Format::Text => inner,
+    };
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while !rest.is_empty() {
+        if let Some((inner, after)) = delimited(rest, "**", "**") {
+            out.push_str(&wrap("textbf", "strong", inline(inner, format)));
+            rest = after;
+        } else if let Some((inner, after)) = delimited(rest, "*", "*") {
+            out.push_str(&wrap("emph", "em", inline(inner, format)));
+            rest = after;
+        } else if let Some((inner, after)) = delimited(rest, "`", "`") {
+            out.push_str(&wrap("texttt", "code", escape(inner, format)));
+            rest = after;
+        } else if let Some((label, after)) = delimited(rest, "[", "](") {
+            match after.split_once(')') {
+                Some((url, after)) => {
+                    out.push_str(&match format {
+                        Format::Latex => format!("\\href{{{}}}{{{}}}", url, inline(label, format)),
+                        Format::Html => format!(
+                            "<a href=\"{}\">{}</a>",
+                            escape(url, format),
+                            inline(label, format)
+                        ),
+                        Format::Text if label == url => url.to_string(),
+                        Format::Text => format!("{} ({})", inline(label, format), url),
+                    });
+                    rest = after;
+                }
+                None => {
+                    out.push('[');
+                    rest = &rest[1..];
+                }
+            }
+        } else {
+            let c = rest.chars().next().unwrap_or_default();
+            out.push_str(&escape(c.encode_utf8(&mut [0; 4]), format));
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    out
+}
+
+fn escape(text: &str, format: Format) -> String {
+    match format {
+        Format::Latex => escape_latex_str(text),
+        Format::Html => escape_html(text),
+        Format::Text => text.to_string(),
+    }
+}
+
+pub fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#x27;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// `text` split around the first `open ... close` span, if it starts with `open`
+/// and the span isn't empty.
+fn delimited<'a>(text: &'a str, open: &str, close: &str) -> Option<(&'a str, &'a str)> {
+    let body = text.strip_prefix(open)?;
+    let end = body.find(close)?;
+    (end > 0).then(|| (&body[..end], &body[end + close.len()..]))
+}
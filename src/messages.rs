@@ -0,0 +1,110 @@
+use std::fmt::Arguments;
+use std::path::PathBuf;
+
+use anyhow::Error;
+use clap::ValueEnum;
+use serde::Serialize;
+use tectonic::status::{MessageKind, StatusBackend};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum MessageFormat {
+    #[default]
+    Human,
+    Json,
+}
+
+/// Machine-readable build events, one JSON object per line on stdout.
+#[derive(Serialize)]
+#[serde(tag = "reason", rename_all = "kebab-case")]
+pub enum Message<'a> {
+    RenderStarted {
+        template: &'a str,
+    },
+    CompileStarted {
+        template: &'a str,
+    },
+    Warning {
+        message: String,
+    },
+    Error {
+        message: String,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        spans: Vec<Span<'a>>,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        fields: Vec<String>,
+    },
+    Artifact {
+        path: PathBuf,
+    },
+    BuildFinished {
+        success: bool,
+    },
+    PageChanged {
+        page: usize,
+        changed_pixels: usize,
+        total_pixels: usize,
+        image: PathBuf,
+    },
+    Bench {
+        stages: &'a [crate::bench::StageTiming],
+    },
+    Stats {
+        #[serde(flatten)]
+        stats: &'a rsume::Stats,
+    },
+    Lint {
+        #[serde(flatten)]
+        finding: &'a rsume::Finding,
+    },
+    SkillSuggestions {
+        #[serde(flatten)]
+        suggestions: &'a rsume::SkillSuggestions,
+    },
+}
+
+#[derive(Serialize)]
+pub struct Span<'a> {
+    pub file: &'a str,
+    pub line: usize,
+    /// `rendered` for lines of the generated LaTeX, `template` for template source lines
+    pub label: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+}
+
+impl MessageFormat {
+    pub fn emit(self, message: &Message) {
+        if self == MessageFormat::Json {
+            println!(
+                "{}",
+                serde_json::to_string(message).expect("couldn't serialize message")
+            );
+        }
+    }
+}
+
+/// Forwards Tectonic's warnings and errors as JSON messages.
+pub struct JsonStatusBackend;
+
+impl StatusBackend for JsonStatusBackend {
+    fn report(&mut self, kind: MessageKind, args: Arguments, err: Option<&Error>) {
+        let mut message = args.to_string();
+        if let Some(err) = err {
+            for cause in err.chain() {
+                message.push_str(&format!(": {}", cause));
+            }
+        }
+        let message = match kind {
+            MessageKind::Note => return,
+            MessageKind::Warning => Message::Warning { message },
+            MessageKind::Error => Message::Error {
+                message,
+                spans: Vec::new(),
+                fields: Vec::new(),
+            },
+        };
+        MessageFormat::Json.emit(&message);
+    }
+
+    fn dump_error_logs(&mut self, _output: &[u8]) {}
+}
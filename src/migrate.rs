@@ -0,0 +1,135 @@
+use toml_edit::{value, Array, Document, Item, Value};
+
+use crate::model::default_schema_version;
+use crate::{Error, Result};
+
+/// The data format version this crate reads.
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// Upgrades from version `index + 1` to `index + 2`. Each step only touches the fields
+/// that changed, so comments and formatting elsewhere in the file survive.
+const MIGRATIONS: &[fn(&mut Document)] = &[keywords_to_list];
+
+/// The result of [`migrate`].
+#[derive(Debug)]
+pub struct Migration {
+    /// The schema version the data was written for
+    pub from: u32,
+    /// The upgraded data file
+    pub source: String,
+}
+
+/// Upgrades a resume data file to [`SCHEMA_VERSION`]. Data that is already current
+/// comes back unchanged.
+pub fn migrate(source: &str) -> Result<Migration> {
+    let mut document: Document = source.parse().map_err(Error::Edit)?;
+    let from = match document.get("schema_version").and_then(Item::as_integer) {
+        Some(v) => u32::try_from(v).map_err(|_| Error::InvalidSchema(v))?,
+        None => default_schema_version(),
+    };
+    if from > SCHEMA_VERSION {
+        return Err(Error::UnsupportedSchema(from));
+    }
+    if from == SCHEMA_VERSION {
+        return Ok(Migration {
+            from,
+            source: source.to_string(),
+        });
+    }
+    for step in &MIGRATIONS[from.max(1) as usize - 1..] {
+        step(&mut document);
+    }
+    document["schema_version"] = value(i64::from(SCHEMA_VERSION));
+    Ok(Migration {
+        from,
+        source: document.to_string(),
+    })
+}
+
+/// 1 → 2: `skills.keywords` went from a comma-separated string to a list.
+fn keywords_to_list(document: &mut Document) {
+    let Some(skills) = document
+        .get_mut("skills")
+        .and_then(Item::as_array_of_tables_mut)
+    else {
+        return;
+    };
+    for skill in skills.iter_mut() {
+        let Some(keywords) = skill.get("keywords").and_then(Item::as_str) else {
+            continue;
+        };
+        let list: Array = keywords
+            .split(',')
+            .map(str::trim)
+            .filter(|k| !k.is_empty())
+            .collect();
+        skill["keywords"] = Item::Value(Value::Array(list));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keywords_become_lists_and_comments_survive() {
+        let before = r#"# My resume
+name = "Jane Doe" # full name
+
+[[skills]]
+name = "Rust"
+# comma-separated
+keywords = "tokio, serde,, axum "
+
+[[skills]]
+name = "Go"
+keywords = ["already", "a list"]
+"#;
+        let after = r#"# My resume
+name = "Jane Doe" # full name
+schema_version = 2
+
+[[skills]]
+name = "Rust"
+# comma-separated
+keywords = ["tokio", "serde", "axum"]
+
+[[skills]]
+name = "Go"
+keywords = ["already", "a list"]
+"#;
+        let migration = migrate(before).expect("migrates");
+        assert_eq!(migration.from, 1);
+        assert_eq!(migration.source, after);
+    }
+
+    #[test]
+    fn data_without_skills_only_gets_its_version() {
+        let migration = migrate("schema_version = 1\nname = \"Jane\"\n").expect("migrates");
+        assert_eq!(migration.source, "schema_version = 2\nname = \"Jane\"\n");
+    }
+
+    #[test]
+    fn current_data_comes_back_unchanged() {
+        let source = "schema_version = 2 # current\n\n[[skills]]\nkeywords = \"a, b\"\n";
+        let migration = migrate(source).expect("migrates");
+        assert_eq!(migration.from, SCHEMA_VERSION);
+        assert_eq!(migration.source, source);
+    }
+
+    #[test]
+    fn newer_data_is_rejected() {
+        assert!(matches!(
+            migrate("schema_version = 99\n"),
+            Err(Error::UnsupportedSchema(99))
+        ));
+    }
+
+    #[test]
+    fn negative_versions_are_reported_as_written() {
+        assert!(matches!(
+            migrate("schema_version = -1\n"),
+            Err(Error::InvalidSchema(-1))
+        ));
+    }
+}
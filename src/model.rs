@@ -0,0 +1,1440 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::num::NonZeroU8;
+use std::ops::Deref;
+
+use chrono::NaiveDate;
+use schemars::JsonSchema;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use toml::value::Datetime;
+
+use crate::{EarlierExperience, Error, GitInfo, Result, Revision, SCHEMA_VERSION};
+
+/// Postal address of the author; every part is optional, and the
+/// `format_location` filter orders them the way `country_code`'s country
+/// does.
+#[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct Location {
+    pub address: Option<String>,
+    pub postal_code: Option<String>,
+    pub city: Option<String>,
+    /// Two-letter ISO 3166 country code
+    pub country_code: Option<String>,
+    /// State, province or county
+    pub region: Option<String>,
+}
+
+/// A profile on a social or code-hosting site, keyed by site name in `social`.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Social {
+    pub username: String,
+    pub url: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Company {
+    pub name: String,
+    /// Shown instead of `name`, e.g. `Square (now Block)` after a rename or
+    /// acquisition
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    /// Registered name for background checks, e.g. `Block, Inc.`; bundled
+    /// templates only show it as an HTML tooltip
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub legal_name: Option<String>,
+    /// e.g. `Berlin, Germany`; may be left out
+    #[serde(default)]
+    pub location: String,
+    /// Shown next to the company name, so without `alt` it's left out of what
+    /// assistive technology reads
+    #[serde(default)]
+    #[schemars(with = "Option<ImageSource>")]
+    pub logo: Option<Image>,
+}
+
+/// An image file with the text that stands in for it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "ImageSource")]
+pub struct Image {
+    /// Path relative to the data file, staged for `\includegraphics`; at most
+    /// 8 MB
+    pub path: String,
+    /// Read out instead of the image by assistive technology
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alt: Option<String>,
+    /// Shown under the image by templates with room for it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub caption: Option<String>,
+}
+
+/// An image as written in the data: a path, or a table describing it.
+#[derive(Deserialize, JsonSchema)]
+#[serde(untagged)]
+enum ImageSource {
+    Path(String),
+    Described {
+        path: String,
+        /// e.g. `Headshot of Jane Doe in front of a bookshelf`
+        #[serde(default)]
+        alt: Option<String>,
+        #[serde(default)]
+        caption: Option<String>,
+    },
+}
+
+impl From<ImageSource> for Image {
+    fn from(source: ImageSource) -> Self {
+        match source {
+            ImageSource::Path(path) => Image {
+                path,
+                alt: None,
+                caption: None,
+            },
+            ImageSource::Described { path, alt, caption } => Image { path, alt, caption },
+        }
+    }
+}
+
+/// An email address or phone number, with what it's for.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "ContactDetailSource")]
+pub struct ContactDetail {
+    pub value: String,
+    /// e.g. `work` or `personal`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+/// A contact detail as written in the data: the value, or a table with a
+/// label.
+#[derive(Deserialize, JsonSchema)]
+#[serde(untagged)]
+enum ContactDetailSource {
+    Value(String),
+    Labeled {
+        value: String,
+        /// e.g. `work` or `personal`
+        #[serde(default)]
+        label: Option<String>,
+    },
+}
+
+impl From<ContactDetailSource> for ContactDetail {
+    fn from(source: ContactDetailSource) -> Self {
+        match source {
+            ContactDetailSource::Value(value) => ContactDetail { value, label: None },
+            ContactDetailSource::Labeled { value, label } => ContactDetail { value, label },
+        }
+    }
+}
+
+/// One contact detail, as data written before lists were allowed has, or a
+/// list of them.
+#[derive(Deserialize, JsonSchema)]
+#[serde(untagged)]
+enum ContactDetailsSource {
+    One(ContactDetailSource),
+    Many(Vec<ContactDetailSource>),
+}
+
+fn contact_details<'de, D>(deserializer: D) -> std::result::Result<Vec<ContactDetail>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(match ContactDetailsSource::deserialize(deserializer)? {
+        ContactDetailsSource::One(detail) => vec![detail.into()],
+        ContactDetailsSource::Many(details) => details.into_iter().map(Into::into).collect(),
+    })
+}
+
+/// How to say the author's name: a respelling, a recording, or both.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "PronunciationSource")]
+pub struct Pronunciation {
+    /// e.g. `SHIV-awn`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    /// URL of a recording, linked from HTML output only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub audio: Option<String>,
+}
+
+/// A pronunciation as written in the data: the respelling, or a table that
+/// can also link a recording.
+#[derive(Deserialize, JsonSchema)]
+#[serde(untagged)]
+enum PronunciationSource {
+    Text(String),
+    Described {
+        #[serde(default)]
+        text: Option<String>,
+        /// e.g. `https://jane.dev/name.mp3`
+        #[serde(default)]
+        audio: Option<String>,
+    },
+}
+
+impl From<PronunciationSource> for Pronunciation {
+    fn from(source: PronunciationSource) -> Self {
+        match source {
+            PronunciationSource::Text(text) => Pronunciation {
+                text: Some(text),
+                audio: None,
+            },
+            PronunciationSource::Described { text, audio } => Pronunciation { text, audio },
+        }
+    }
+}
+
+/// When the author could start, e.g. `Available immediately`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "AvailabilitySource")]
+pub struct Availability {
+    /// Shown as written
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    /// Earliest start, rendered as `YYYY-MM-DD`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<NaiveDate>,
+    /// e.g. `3 months`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notice: Option<String>,
+}
+
+impl Availability {
+    /// e.g. `Available from 5 January 2026, notice period 3 months`, with
+    /// `text` first if there is one.
+    pub fn one_line(&self) -> String {
+        let mut parts = Vec::new();
+        parts.extend(self.text.clone());
+        parts.extend(
+            self.from
+                .map(|from| format!("Available from {}", from.format("%-d %B %Y"))),
+        );
+        if let Some(notice) = &self.notice {
+            let label = if parts.is_empty() { "Notice" } else { "notice" };
+            parts.push(format!("{} period {}", label, notice));
+        }
+        parts.join(", ")
+    }
+}
+
+/// Availability as written in the data: text, a date to start from, or a
+/// table that can also give a notice period.
+#[derive(Deserialize, JsonSchema)]
+#[serde(untagged)]
+enum AvailabilitySource {
+    Text(String),
+    #[schemars(with = "String")]
+    Date(Datetime),
+    Detailed {
+        #[serde(default)]
+        text: Option<String>,
+        /// TOML date
+        #[serde(default)]
+        #[schemars(with = "Option<String>")]
+        from: Option<Datetime>,
+        /// e.g. `3 months`
+        #[serde(default)]
+        notice: Option<String>,
+    },
+}
+
+impl TryFrom<AvailabilitySource> for Availability {
+    type Error = String;
+
+    fn try_from(source: AvailabilitySource) -> std::result::Result<Self, String> {
+        let date =
+            |datetime| date_of::<serde::de::value::Error>(datetime).map_err(|e| e.to_string());
+        Ok(match source {
+            AvailabilitySource::Text(text) => Availability {
+                text: Some(text),
+                from: None,
+                notice: None,
+            },
+            AvailabilitySource::Date(from) => Availability {
+                text: None,
+                from: Some(date(from)?),
+                notice: None,
+            },
+            AvailabilitySource::Detailed { text, from, notice } => Availability {
+                text,
+                from: from.map(date).transpose()?,
+                notice,
+            },
+        })
+    }
+}
+
+/// A bullet point of an experience. Templates see just the text; the tags
+/// pick which highlights a build includes, see `--tags`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(from = "HighlightSource")]
+pub struct Highlight {
+    pub text: String,
+    pub tags: Vec<String>,
+    pub variants: Vec<String>,
+}
+
+impl Highlight {
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+}
+
+impl Deref for Highlight {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.text
+    }
+}
+
+impl Serialize for Highlight {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.text)
+    }
+}
+
+/// A highlight as written in the data: plain text, or a table with tags.
+#[derive(Deserialize, JsonSchema)]
+#[serde(untagged)]
+enum HighlightSource {
+    Text(String),
+    Tagged {
+        text: String,
+        /// e.g. `["rust", "backend"]`
+        #[serde(default)]
+        tags: Vec<String>,
+        /// Like `Experience::variants`
+        #[serde(default)]
+        variants: Vec<String>,
+    },
+}
+
+impl From<HighlightSource> for Highlight {
+    fn from(source: HighlightSource) -> Self {
+        match source {
+            HighlightSource::Text(text) => Highlight {
+                text,
+                tags: Vec::new(),
+                variants: Vec::new(),
+            },
+            HighlightSource::Tagged {
+                text,
+                tags,
+                variants,
+            } => Highlight {
+                text,
+                tags,
+                variants,
+            },
+        }
+    }
+}
+
+/// A position held at a company.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Experience {
+    pub company: Company,
+    /// Department within the company
+    pub department: String,
+    /// Team within the department, e.g. `Payments Risk`
+    #[serde(default)]
+    pub team: Option<String>,
+    /// Job title
+    pub position: String,
+    pub website: String,
+    /// TOML date, rendered as `YYYY-MM-DD`
+    #[serde(deserialize_with = "datetime_to_date")]
+    #[schemars(with = "String")]
+    pub start_date: NaiveDate,
+    /// TOML date, absent while ongoing
+    #[serde(default)]
+    #[serde(deserialize_with = "datetime_to_option_date")]
+    #[schemars(with = "Option<String>")]
+    pub end_date: Option<NaiveDate>,
+    /// Whether this is ongoing; derived from `end_date` when left out
+    #[serde(default)]
+    pub current: bool,
+    /// Shown instead of the dates by bundled templates, e.g. `Summer 2020` or
+    /// `2018–present, part-time`; the dates still order and measure the entry
+    #[serde(default)]
+    pub date_display: Option<String>,
+    /// Template-specific keys controlling how the entry is shown
+    pub display: Vec<String>,
+    /// Bullet points describing the work
+    #[schemars(with = "Vec<HighlightSource>")]
+    pub highlights: Vec<Highlight>,
+    /// Languages, frameworks and tools used in the role
+    #[serde(default)]
+    pub technologies: Vec<String>,
+    /// Worked away from the company's offices
+    #[serde(default)]
+    pub remote: bool,
+    /// Shown by bundled templates unless it's `full-time`
+    #[serde(default)]
+    pub employment_type: Option<EmploymentType>,
+    /// Earlier titles at the same employer, most recent first, shown stacked
+    /// under `position`, which with the dates above is the latest
+    #[serde(default)]
+    pub roles: Vec<Role>,
+    /// Listed under `earlier_experiences` instead, like the roles `--since`
+    /// leaves out
+    #[serde(default)]
+    pub archive: bool,
+    /// Left out of builds with a `--variant` not among these, e.g.
+    /// `["backend", "fullstack"]`; builds without one keep every entry
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub variants: Vec<String>,
+    /// Client work within the position, e.g. placements through a
+    /// consultancy; see `consultant_profile`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub engagements: Vec<Engagement>,
+}
+
+/// Work for one client, listed on its own by consultant profiles.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Engagement {
+    pub client: String,
+    /// Defaults to the experience's `position`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+    /// TOML date, rendered as `YYYY-MM-DD`
+    #[serde(deserialize_with = "datetime_to_date")]
+    #[schemars(with = "String")]
+    pub start_date: NaiveDate,
+    /// TOML date, absent while ongoing
+    #[serde(default)]
+    #[serde(deserialize_with = "datetime_to_option_date")]
+    #[schemars(with = "Option<String>")]
+    pub end_date: Option<NaiveDate>,
+    /// Languages, frameworks and tools used, e.g. `["Rust", "Kafka"]`
+    #[serde(default)]
+    pub stack: Vec<String>,
+    /// What came of it; Markdown, like highlights
+    #[serde(default)]
+    pub outcomes: Vec<String>,
+}
+
+/// The terms of a position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum EmploymentType {
+    FullTime,
+    PartTime,
+    Contract,
+    Freelance,
+    Internship,
+}
+
+impl EmploymentType {
+    /// As written in the data, e.g. `full-time`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EmploymentType::FullTime => "full-time",
+            EmploymentType::PartTime => "part-time",
+            EmploymentType::Contract => "contract",
+            EmploymentType::Freelance => "freelance",
+            EmploymentType::Internship => "internship",
+        }
+    }
+}
+
+/// A title held before a promotion.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Role {
+    pub position: String,
+    /// TOML date, rendered as `YYYY-MM-DD`
+    #[serde(deserialize_with = "datetime_to_date")]
+    #[schemars(with = "String")]
+    pub start_date: NaiveDate,
+    #[serde(deserialize_with = "datetime_to_date")]
+    #[schemars(with = "String")]
+    pub end_date: NaiveDate,
+    /// Shown instead of the dates, like `Experience::date_display`
+    #[serde(default)]
+    pub date_display: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct GradePointAverage {
+    #[serde(default)]
+    pub major: Option<f64>,
+    pub overall: f64,
+    /// The maximum grade, e.g. `4.0` or `5.0`; `100` means a percentage
+    #[serde(default = "default_gpa_scale")]
+    pub scale: f64,
+}
+
+fn default_gpa_scale() -> f64 {
+    4.0
+}
+
+/// A degree or program at an institution.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Education {
+    pub institution: String,
+    pub website: String,
+    pub major: String,
+    pub minor: String,
+    /// TOML date, rendered as `YYYY-MM-DD`
+    #[serde(deserialize_with = "datetime_to_date")]
+    #[schemars(with = "String")]
+    pub start_date: NaiveDate,
+    /// TOML date, absent while ongoing
+    #[serde(default)]
+    #[serde(deserialize_with = "datetime_to_option_date")]
+    #[schemars(with = "Option<String>")]
+    pub end_date: Option<NaiveDate>,
+    /// Whether this is ongoing; derived from `end_date` when left out
+    #[serde(default)]
+    pub current: bool,
+    /// Shown instead of the dates by bundled templates, e.g. `Summer 2020` or
+    /// `2018–present, part-time`; the dates still order and measure the entry
+    #[serde(default)]
+    pub date_display: Option<String>,
+    #[serde(default)]
+    pub gpa: Option<GradePointAverage>,
+    pub achievements: Vec<String>,
+    /// May be left out
+    #[serde(default)]
+    pub location: String,
+    pub degree: String,
+    /// e.g. `cum laude`
+    pub latin_honors: String,
+    /// Notable courses taken
+    #[serde(default)]
+    pub courses: Vec<String>,
+    #[serde(default)]
+    pub thesis_title: Option<String>,
+    /// Thesis advisor
+    #[serde(default)]
+    pub advisor: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Skill {
+    pub name: String,
+    /// Proficiency, e.g. `Expert`
+    pub level: String,
+    /// Related tools or topics
+    pub keywords: Vec<String>,
+    /// Grouping such as `Languages` or `Tools`
+    pub category: String,
+    /// Like `Experience::variants`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub variants: Vec<String>,
+}
+
+/// How templates lay out the skills section, exposed as `skills_layout`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct SkillsLayout {
+    /// Columns to flow skills into in the main column
+    pub columns: NonZeroU8,
+    /// Categories listed first, in this order; skills in the others follow as
+    /// they are in the data
+    pub categories: Vec<String>,
+    /// Show each skill's level
+    pub show_levels: bool,
+}
+
+impl Default for SkillsLayout {
+    fn default() -> Self {
+        SkillsLayout {
+            columns: NonZeroU8::MIN,
+            categories: Vec::new(),
+            show_levels: true,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Project {
+    pub name: String,
+    pub website: String,
+    /// Source code URL; on GitHub, `rsume refresh` looks up its stars
+    pub source: String,
+    pub description: String,
+    /// Name on crates.io, whose version and downloads `rsume refresh` looks up
+    #[serde(default, rename = "crate", skip_serializing_if = "Option::is_none")]
+    pub crate_name: Option<String>,
+    /// Package name on npm, looked up like `crate`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub npm: Option<String>,
+    /// Written by `rsume refresh`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stats: Option<ProjectStats>,
+    /// Like `Experience::variants`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub variants: Vec<String>,
+}
+
+/// A patent or patent application.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Patent {
+    pub title: String,
+    /// Publication or application number, e.g. `US 10,123,456 B2`
+    #[serde(default)]
+    pub number: Option<String>,
+    #[serde(default)]
+    pub status: PatentStatus,
+    /// TOML date of filing or grant, rendered as `YYYY-MM-DD`
+    #[serde(default)]
+    #[serde(deserialize_with = "datetime_to_option_date")]
+    #[schemars(with = "Option<String>")]
+    pub date: Option<NaiveDate>,
+    /// In the order the patent lists them
+    #[serde(default)]
+    pub inventors: Vec<String>,
+    #[serde(default)]
+    pub url: Option<String>,
+}
+
+/// How far a patent has got.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum PatentStatus {
+    Filed,
+    #[default]
+    Pending,
+    Granted,
+}
+
+/// Research funding, for academic CVs.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Grant {
+    pub title: String,
+    /// e.g. `National Science Foundation`
+    #[serde(default)]
+    pub funder: String,
+    /// Left out of the template context with `hide_grant_amounts`; see the
+    /// `format_amount` filter
+    #[serde(default)]
+    pub amount: Option<Amount>,
+    /// As shown, e.g. `2021–2024`
+    #[serde(default)]
+    pub period: Option<String>,
+    /// e.g. `Principal investigator` or `Co-investigator`
+    #[serde(default)]
+    pub role: Option<String>,
+}
+
+/// A sum of money, e.g. `{ value = 450000, currency = "USD" }`.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Amount {
+    pub value: f64,
+    /// ISO 4217 code
+    pub currency: String,
+}
+
+/// A course taught, or a mentoring or supervision role.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Teaching {
+    /// Course name or code, or a program such as `Google Summer of Code`
+    pub course: String,
+    /// e.g. `Instructor`, `Teaching assistant` or `Mentor`
+    #[serde(default)]
+    pub role: Option<String>,
+    #[serde(default)]
+    pub institution: String,
+    /// When it ran, as shown, e.g. `Fall 2023` or `2019–2021`
+    #[serde(default)]
+    pub term: Option<String>,
+    /// Markdown
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// A talk or presentation given at a conference, meetup or seminar.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Talk {
+    pub title: String,
+    /// Conference, meetup or seminar series
+    #[serde(default)]
+    pub event: String,
+    /// TOML date, rendered as `YYYY-MM-DD`
+    #[serde(default)]
+    #[serde(deserialize_with = "datetime_to_option_date")]
+    #[schemars(with = "Option<String>")]
+    pub date: Option<NaiveDate>,
+    /// City, or `online`
+    #[serde(default)]
+    pub location: Option<String>,
+    #[serde(default)]
+    pub slides: Option<String>,
+    /// Recording of the talk
+    #[serde(default)]
+    pub video: Option<String>,
+}
+
+/// Numbers about a project from the sites it's published on, as of `refreshed`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ProjectStats {
+    /// GitHub stars
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stars: Option<u64>,
+    /// All-time crates.io downloads
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub downloads: Option<u64>,
+    /// npm downloads over the last month, the longest npm reports
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub monthly_downloads: Option<u64>,
+    /// Latest published version, from crates.io when it's on both registries
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// TOML date, rendered as `YYYY-MM-DD`
+    #[serde(deserialize_with = "datetime_to_date")]
+    #[schemars(with = "String")]
+    pub refreshed: NaiveDate,
+}
+
+/// A hobby or area of interest outside work.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Interest {
+    pub name: String,
+    #[serde(default)]
+    pub keywords: Vec<String>,
+}
+
+/// Someone who can vouch for the author.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Reference {
+    pub name: String,
+    /// e.g. `Manager at Acme`
+    pub relationship: String,
+    /// Email address or phone number
+    pub contact: String,
+}
+
+/// How much of `references` a build shows.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "kebab-case")]
+pub enum ReferenceVisibility {
+    /// Every reference with its contact details
+    Full,
+    /// Only a "References available upon request" line; `references` is empty
+    #[default]
+    OnRequest,
+    /// Nothing; `references` is empty
+    Hidden,
+}
+
+/// Which column of a two-column template a section goes in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum Column {
+    #[default]
+    Main,
+    Sidebar,
+}
+
+/// The resume's sections in resume order. Each is also exposed as a
+/// `has_<section>` flag, false when it's missing or empty.
+pub const SECTIONS: &[&str] = &[
+    "summary",
+    "experiences",
+    "educations",
+    "skills",
+    "languages",
+    "projects",
+    "publications",
+    "patents",
+    "teaching",
+    "grants",
+    "talks",
+    "interests",
+    "references",
+];
+
+/// A named tailoring of the data, selected per build with `--variant`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct Variant {
+    /// Keep at most this many highlights per experience
+    pub max_highlights: Option<usize>,
+    /// Highlights mentioning or tagged with one of these are kept ahead of the
+    /// others when trimming, e.g. `["rust", "distributed systems"]`
+    pub keywords: Vec<String>,
+    /// Leave out tagged highlights without one of these tags; untagged ones
+    /// always stay. Replaced by `--tags`
+    pub tags: Vec<String>,
+    /// Skill names listed first, in this order; the others follow as usual
+    pub skills: Vec<String>,
+    /// Replaces the summary; Markdown
+    pub summary: Option<String>,
+}
+
+/// Where each section goes in two-column templates; unset sections fall back to
+/// the template manifest's `[layout]`, then to the main column. Single-column
+/// templates ignore it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct Layout {
+    pub summary: Option<Column>,
+    pub experiences: Option<Column>,
+    pub educations: Option<Column>,
+    pub skills: Option<Column>,
+    pub languages: Option<Column>,
+    pub projects: Option<Column>,
+    pub publications: Option<Column>,
+    pub patents: Option<Column>,
+    pub teaching: Option<Column>,
+    pub grants: Option<Column>,
+    pub talks: Option<Column>,
+    pub interests: Option<Column>,
+    pub references: Option<Column>,
+}
+
+impl Layout {
+    /// This layout with its unset sections taken from `defaults`.
+    pub fn or(&self, defaults: &Layout) -> Layout {
+        Layout {
+            summary: self.summary.or(defaults.summary),
+            experiences: self.experiences.or(defaults.experiences),
+            educations: self.educations.or(defaults.educations),
+            skills: self.skills.or(defaults.skills),
+            languages: self.languages.or(defaults.languages),
+            projects: self.projects.or(defaults.projects),
+            publications: self.publications.or(defaults.publications),
+            patents: self.patents.or(defaults.patents),
+            teaching: self.teaching.or(defaults.teaching),
+            grants: self.grants.or(defaults.grants),
+            talks: self.talks.or(defaults.talks),
+            interests: self.interests.or(defaults.interests),
+            references: self.references.or(defaults.references),
+        }
+    }
+
+    /// The sections placed in `column`, in resume order.
+    pub fn sections(&self, column: Column) -> Vec<&'static str> {
+        [
+            ("summary", self.summary),
+            ("experiences", self.experiences),
+            ("educations", self.educations),
+            ("skills", self.skills),
+            ("languages", self.languages),
+            ("projects", self.projects),
+            ("publications", self.publications),
+            ("patents", self.patents),
+            ("teaching", self.teaching),
+            ("grants", self.grants),
+            ("talks", self.talks),
+            ("interests", self.interests),
+            ("references", self.references),
+        ]
+        .into_iter()
+        .filter(|(_, c)| c.unwrap_or_default() == column)
+        .map(|(section, _)| section)
+        .collect()
+    }
+}
+
+/// Section headings in place of the template's own wording, e.g.
+/// `experiences = "Work History"`. Templates read them as
+/// `headings.<section>`; unset ones are left out so templates can fall back
+/// with `default`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct Headings {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contact: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub objective: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub experiences: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub earlier_experiences: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub engagements: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub educations: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub skills: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub languages: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub projects: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub publications: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub patents: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub teaching: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grants: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub talks: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interests: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub references: Option<String>,
+}
+
+/// A paper, article or book.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Publication {
+    pub title: String,
+    /// In citation order
+    #[serde(default)]
+    pub authors: Vec<String>,
+    /// Journal, conference or publisher
+    #[serde(default)]
+    pub venue: String,
+    /// TOML date, rendered as `YYYY-MM-DD`
+    #[serde(default)]
+    #[serde(deserialize_with = "datetime_to_option_date")]
+    #[schemars(with = "Option<String>")]
+    pub date: Option<NaiveDate>,
+    #[serde(default)]
+    pub doi: Option<String>,
+    #[serde(default)]
+    pub url: Option<String>,
+}
+
+/// Options for long-form CV templates.
+#[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct Cv {
+    /// Print a table of contents, useful past ten or so pages
+    pub table_of_contents: bool,
+}
+
+/// A dated line at the end of each build, for profiles that circulate and go
+/// stale.
+#[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct Stamp {
+    /// Print `Generated on <date>`; the date is exposed as `generated_on`
+    pub generated: bool,
+    /// Also print `valid until` this many days after the build, exposed as
+    /// `valid_until`; implies `generated`
+    pub valid_days: Option<u32>,
+}
+
+/// Options for the PDF itself rather than the page content.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct Pdf {
+    /// Add an outline entry per section for navigation in PDF viewers
+    pub bookmarks: bool,
+    /// Mark up images with alternative text and the document with its title
+    /// for assistive technology; set by `--accessible`
+    pub accessible: bool,
+}
+
+impl Default for Pdf {
+    fn default() -> Self {
+        Pdf {
+            bookmarks: true,
+            accessible: false,
+        }
+    }
+}
+
+/// The tightest [`Author::tighten`] level.
+pub const MAX_TIGHTEN: u8 = 3;
+
+/// Factors templates scale their default spacing by, exposed as `spacing`.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Spacing {
+    /// Space between list items, entries and sections
+    pub vertical: f64,
+    pub margin: f64,
+}
+
+impl Spacing {
+    pub fn for_level(level: u8) -> Spacing {
+        let (vertical, margin) = match level {
+            0 => (1.0, 1.0),
+            1 => (0.8, 0.9),
+            2 => (0.6, 0.8),
+            _ => (0.45, 0.7),
+        };
+        Spacing { vertical, margin }
+    }
+}
+
+/// How the email address appears in HTML output.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum EmailObfuscation {
+    /// A plain `mailto:` link
+    #[default]
+    None,
+    /// Every character written as an HTML entity
+    Entities,
+    /// Assembled by a script, so it's absent from the page source
+    Script,
+}
+
+/// Options for HTML output only; PDFs are unaffected.
+#[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct Html {
+    pub obfuscate_email: EmailObfuscation,
+    pub hide_phone: bool,
+    /// Leave out the street address and postal code, keeping the city
+    pub hide_address: bool,
+    /// Also publish the data as `resume.json` in the JSON Resume format, for
+    /// widgets and other tools
+    pub json_resume: bool,
+}
+
+/// The job a build is tailored for, set per build with `--company` and `--role`
+/// or per recipient by `rsume cover-letter --batch`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct Application {
+    pub company: Option<String>,
+    pub role: Option<String>,
+    /// Who a cover letter is addressed to
+    pub addressee: Option<String>,
+    /// The recipient's postal address, on as many lines as needed
+    pub address: Option<String>,
+    /// A paragraph just for this application, before `cover_letter`; Markdown
+    pub note: Option<String>,
+}
+
+/// A `--batch` file for `rsume cover-letter`: one `[[recipients]]` table per letter.
+#[derive(Debug, Deserialize)]
+pub struct Recipients {
+    pub recipients: Vec<Application>,
+}
+
+impl Recipients {
+    pub fn from_toml(source: &str) -> Result<Recipients> {
+        toml::from_str(source).map_err(Error::Parse)
+    }
+}
+
+impl Application {
+    /// This application with its unset fields taken from `defaults`.
+    pub fn or(&self, defaults: &Application) -> Application {
+        Application {
+            company: self.company.clone().or_else(|| defaults.company.clone()),
+            role: self.role.clone().or_else(|| defaults.role.clone()),
+            addressee: self
+                .addressee
+                .clone()
+                .or_else(|| defaults.addressee.clone()),
+            address: self.address.clone().or_else(|| defaults.address.clone()),
+            note: self.note.clone().or_else(|| defaults.note.clone()),
+        }
+    }
+
+    /// `template_filename` with the company and role appended to its stem, e.g.
+    /// `resume-acme-backend-engineer.tex`, so outputs for different
+    /// applications don't overwrite each other.
+    pub fn file_name(&self, template_filename: &str) -> String {
+        let path = std::path::Path::new(template_filename);
+        let mut name = path
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned();
+        for part in [&self.company, &self.role].into_iter().flatten() {
+            let slug = slug(part);
+            if !slug.is_empty() {
+                name.push('-');
+                name.push_str(&slug);
+            }
+        }
+        match path.extension() {
+            Some(extension) => format!("{}.{}", name, extension.to_string_lossy()),
+            None => name,
+        }
+    }
+}
+
+/// Lowercase ASCII letters and digits, with every other run of characters
+/// collapsed to a single `-`.
+fn slug(text: &str) -> String {
+    text.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_ascii_lowercase)
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// A spoken language.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Language {
+    pub language: String,
+    /// e.g. `Native` or `C1`
+    #[serde(default)]
+    pub fluency: String,
+}
+
+/// Colors for templates that use them, as hex RGB without the `#`.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct Theme {
+    /// Headings and rules
+    pub accent_color: String,
+    pub sidebar_color: String,
+    pub sidebar_text_color: String,
+    /// Font family for templates that use `fontspec`, e.g. `Source Sans Pro`
+    pub font: Option<String>,
+    /// `.otf`/`.ttf` files relative to the data file, staged for the compile
+    pub font_files: Vec<String>,
+    /// OpenType features of the font set by `font` or `font_files`
+    pub font_features: FontFeatures,
+}
+
+/// OpenType features `fontspec` turns on, for fonts that have them.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct FontFeatures {
+    pub ligatures: Option<Ligatures>,
+    pub numerals: Option<Numerals>,
+    /// Extra space between small capitals, in thousandths of an em, e.g. 50
+    pub small_caps_letterspace: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum Ligatures {
+    /// Not even fi and fl
+    None,
+    /// fi, fl and the like, as fonts have by default
+    Common,
+    /// Discretionary ones too, such as ct and st
+    Rare,
+    /// Historic ones too, such as the long s
+    Historic,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum Numerals {
+    /// Old-style figures, with ascenders and descenders, that blend into text
+    Oldstyle,
+    /// Figures as tall as capitals
+    Lining,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            accent_color: "1F4E79".to_string(),
+            sidebar_color: "EEF2F7".to_string(),
+            sidebar_text_color: "1A202C".to_string(),
+            font: None,
+            font_files: Vec::new(),
+            font_features: FontFeatures::default(),
+        }
+    }
+}
+
+impl Theme {
+    /// Replaces the colors with a monochrome set that prints crisply on any
+    /// printer, keeping the fonts.
+    pub fn grayscale(&mut self) {
+        self.accent_color = "333333".to_string();
+        self.sidebar_color = "F0F0F0".to_string();
+        self.sidebar_text_color = "1A1A1A".to_string();
+    }
+}
+
+/// The top level of a resume data file; its fields are the template context.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Author {
+    /// Version of the data format, see `rsume migrate`
+    #[serde(default = "default_schema_version")]
+    pub schema_version: u32,
+    pub name: String,
+    /// An address, or a list of addresses and tables such as `{ value =
+    /// "jane@acme.com", label = "work" }`. Templates see the first as `email`
+    /// and all of them as `emails`
+    #[serde(default, deserialize_with = "contact_details")]
+    #[schemars(with = "Option<ContactDetailsSource>")]
+    pub email: Vec<ContactDetail>,
+    /// e.g. `she/her`, shown next to the name
+    #[serde(default)]
+    pub pronouns: Option<String>,
+    /// A respelling such as `SHIV-awn`, or a table with `text` and an `audio`
+    /// URL of a recording
+    #[serde(default)]
+    #[schemars(with = "Option<PronunciationSource>")]
+    pub name_pronunciation: Option<Pronunciation>,
+    /// One-line headline shown under the name
+    pub description: String,
+    /// Security clearance, e.g. `Active TS/SCI`; bundled templates show it
+    /// under the headline
+    #[serde(default)]
+    pub clearance: Option<String>,
+    /// e.g. `U.S. citizen` or `EU Blue Card`; shown like `clearance`
+    #[serde(default)]
+    pub work_authorization: Option<String>,
+    /// `Available immediately`, a date to start from, or a table with `text`,
+    /// `from` and a `notice` period such as `3 months`; shown with the
+    /// contact details, see the `format_availability` filter
+    #[serde(default)]
+    #[schemars(with = "Option<AvailabilitySource>")]
+    pub availability: Option<Availability>,
+    /// Short professional summary; Markdown, see the `markdown` filter
+    #[serde(default)]
+    pub summary: Option<String>,
+    /// The kind of role being sought; Markdown, see the `markdown` filter
+    #[serde(default)]
+    pub objective: Option<String>,
+    /// A number or a list, like `email`; templates see the first as `phone`
+    /// and all of them as `phones`
+    #[serde(default, deserialize_with = "contact_details")]
+    #[schemars(with = "Option<ContactDetailsSource>")]
+    pub phone: Vec<ContactDetail>,
+    pub website: String,
+    /// Photo; the alternative text defaults to `Photo of <name>`. A path of
+    /// `gravatar:<email>`, optionally with `?size=<px>`, or just an email
+    /// address uses that address's Gravatar
+    #[serde(default)]
+    #[schemars(with = "Option<ImageSource>")]
+    pub picture: Option<Image>,
+    /// BCP 47 tag of the document's language, e.g. `en-US`, set as the PDF and
+    /// HTML language so screen readers pick the right voice, and used for
+    /// hyphenation in LaTeX templates
+    #[serde(default)]
+    pub language: Option<String>,
+    #[serde(default)]
+    pub location: Location,
+    /// Profiles keyed by site name, e.g. `github`
+    #[serde(default)]
+    pub social: HashMap<String, Social>,
+    #[serde(default)]
+    pub experiences: Vec<Experience>,
+    /// Summaries of archived experiences, for a compact line after the
+    /// others; set with `archive` or `--since`, not in the data itself
+    #[serde(default, skip_deserializing)]
+    pub earlier_experiences: Vec<EarlierExperience>,
+    #[serde(default)]
+    pub educations: Vec<Education>,
+    #[serde(default)]
+    pub skills: Vec<Skill>,
+    #[serde(default)]
+    pub skills_layout: SkillsLayout,
+    #[serde(default)]
+    pub languages: Vec<Language>,
+    #[serde(default)]
+    pub projects: Vec<Project>,
+    #[serde(default)]
+    pub publications: Vec<Publication>,
+    #[serde(default)]
+    pub patents: Vec<Patent>,
+    #[serde(default)]
+    pub teaching: Vec<Teaching>,
+    #[serde(default)]
+    pub grants: Vec<Grant>,
+    #[serde(default)]
+    pub talks: Vec<Talk>,
+    #[serde(default)]
+    pub interests: Vec<Interest>,
+    /// Body of cover letters, after any per-application `note`; Markdown
+    #[serde(default)]
+    pub cover_letter: Option<String>,
+    /// Leave GPAs out of the template context, e.g. once experience speaks for itself
+    #[serde(default)]
+    pub hide_gpa: bool,
+    /// Leave grant amounts out of the template context, e.g. where funding
+    /// levels are confidential
+    #[serde(default)]
+    pub hide_grant_amounts: bool,
+    /// Organize around client work rather than employers: the `consultant`
+    /// template always does, and the `html` and `text` templates list
+    /// `engagements` instead of `experiences` with this set
+    #[serde(default)]
+    pub consultant_profile: bool,
+    #[serde(default)]
+    pub references: Vec<Reference>,
+    /// Overridden per build with `--references`
+    #[serde(default)]
+    pub references_visibility: ReferenceVisibility,
+    /// Also exposed as `main_sections` and `sidebar_sections` lists
+    #[serde(default)]
+    pub layout: Layout,
+    #[serde(default)]
+    pub headings: Headings,
+    #[serde(default)]
+    pub theme: Theme,
+    #[serde(default)]
+    pub cv: Cv,
+    #[serde(default)]
+    pub stamp: Stamp,
+    #[serde(default)]
+    pub pdf: Pdf,
+    #[serde(default)]
+    pub html: Html,
+    #[serde(default)]
+    pub application: Application,
+    /// The data's latest `rsume tag`, for stamping printed copies; set from
+    /// the history file beside the data, not in the data itself
+    #[serde(default, skip_deserializing)]
+    pub revision: Option<Revision>,
+    /// The commit the data file was built from, when it's in a git repository
+    #[serde(default, skip_deserializing)]
+    pub git: Option<GitInfo>,
+    /// Tailorings keyed by name, applied with `--variant <name>`
+    #[serde(default)]
+    pub variants: BTreeMap<String, Variant>,
+    /// Build-time switches for templates, set with `--define key=value`
+    #[serde(default)]
+    pub vars: BTreeMap<String, serde_json::Value>,
+    /// How far to tighten spacing, from 0 up to 3; raised automatically by `--max-pages`
+    #[serde(default)]
+    pub tighten: u8,
+}
+
+/// Files written before `schema_version` existed are version 1.
+pub(crate) fn default_schema_version() -> u32 {
+    1
+}
+
+/// The date part of a TOML date or datetime; a time on its own isn't a date.
+fn date_of<E: serde::de::Error>(datetime: Datetime) -> std::result::Result<NaiveDate, E> {
+    let date = datetime
+        .date
+        .ok_or_else(|| E::custom(format!("expected a date, found {}", datetime)))?;
+    NaiveDate::from_ymd_opt(date.year.into(), date.month.into(), date.day.into())
+        .ok_or_else(|| E::custom(format!("{} is not a calendar date", date)))
+}
+
+pub(crate) fn datetime_to_date<'de, D>(deserializer: D) -> std::result::Result<NaiveDate, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    date_of(Datetime::deserialize(deserializer)?)
+}
+
+fn datetime_to_option_date<'de, D>(
+    deserializer: D,
+) -> std::result::Result<Option<NaiveDate>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<Datetime>::deserialize(deserializer)?
+        .map(date_of)
+        .transpose()
+}
+
+/// A parsed resume data file. The raw TOML is kept alongside the typed data so
+/// errors can be traced back to the field that produced them.
+#[derive(Debug)]
+pub struct Resume {
+    pub author: Author,
+    pub data: toml::Value,
+}
+
+impl Resume {
+    pub fn from_toml(source: &str) -> Result<Resume> {
+        let data: toml::Value = source.parse().map_err(Error::Parse)?;
+        // Checked before the typed parse so an old file gets pointed at `rsume migrate`
+        // rather than a type error in whichever field changed.
+        let version = match data.get("schema_version").and_then(toml::Value::as_integer) {
+            Some(v) => u32::try_from(v).map_err(|_| Error::InvalidSchema(v))?,
+            None => default_schema_version(),
+        };
+        match version.cmp(&SCHEMA_VERSION) {
+            Ordering::Less => return Err(Error::OutdatedSchema(version)),
+            Ordering::Greater => return Err(Error::UnsupportedSchema(version)),
+            Ordering::Equal => {}
+        }
+        let mut author: Author = toml::from_str(source).map_err(Error::Parse)?;
+        derive_current(
+            &data,
+            "experiences",
+            author
+                .experiences
+                .iter_mut()
+                .map(|e| (e.end_date.is_some(), &mut e.current)),
+        )?;
+        derive_current(
+            &data,
+            "educations",
+            author
+                .educations
+                .iter_mut()
+                .map(|e| (e.end_date.is_some(), &mut e.current)),
+        )?;
+        author.normalize_urls()?;
+        Ok(Resume { author, data })
+    }
+}
+
+/// Sets each entry's `current` from whether it has an `end_date`, rejecting a
+/// `current` given in the data that says otherwise.
+fn derive_current<'a>(
+    data: &toml::Value,
+    section: &str,
+    entries: impl Iterator<Item = (bool, &'a mut bool)>,
+) -> Result<()> {
+    for (index, (ended, current)) in entries.enumerate() {
+        let given = data
+            .get(section)
+            .and_then(|entries| entries.get(index))
+            .and_then(|entry| entry.get("current"))
+            .and_then(toml::Value::as_bool);
+        if given == Some(ended) {
+            return Err(Error::Current {
+                field: format!("{}[{}]", section, index),
+                ended,
+            });
+        }
+        *current = !ended;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn application(company: Option<&str>, role: Option<&str>) -> Application {
+        Application {
+            company: company.map(str::to_string),
+            role: role.map(str::to_string),
+            ..Application::default()
+        }
+    }
+
+    #[test]
+    fn slugs_collapse_everything_but_ascii_alphanumerics() {
+        assert_eq!(slug("Backend Engineer"), "backend-engineer");
+        assert_eq!(slug("  AT&T -- R&D  "), "at-t-r-d");
+        assert_eq!(slug("Zürich"), "z-rich");
+        assert_eq!(slug("!!!"), "");
+    }
+
+    #[test]
+    fn file_names_gain_the_company_and_role() {
+        let both = application(Some("Acme, Inc."), Some("Backend Engineer"));
+        assert_eq!(
+            both.file_name("resume.tex"),
+            "resume-acme-inc-backend-engineer.tex"
+        );
+        assert_eq!(
+            application(None, Some("SRE")).file_name("cover-letter.tex"),
+            "cover-letter-sre.tex"
+        );
+        assert_eq!(
+            both.file_name("Makefile"),
+            "Makefile-acme-inc-backend-engineer"
+        );
+    }
+
+    #[test]
+    fn file_names_stay_put_without_an_application() {
+        assert_eq!(
+            application(None, None).file_name("resume.tex"),
+            "resume.tex"
+        );
+        assert_eq!(
+            application(Some("???"), None).file_name("resume.tex"),
+            "resume.tex"
+        );
+    }
+}
@@ -0,0 +1,336 @@
+//! Rewriting compiled PDFs smaller: uncompressed streams are deflated and
+//! every other object is packed into one compressed object stream, dropping
+//! the history of any incremental updates. xdvipdfmx already subsets fonts, so
+//! they are left as they are.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+
+use crate::pdf::{
+    dictionary, find, integer, key, members, reference, rfind, stream_data, trim, value, Trailer,
+};
+use crate::{Error, Result};
+
+/// File sizes around [`optimize_pdf`], in bytes.
+#[derive(Debug, Clone, Copy)]
+pub struct Optimized {
+    pub before: u64,
+    pub after: u64,
+}
+
+/// Rewrites the PDF at `path` in place, unless that wouldn't make it smaller.
+pub fn optimize_pdf(path: &Path) -> Result<Optimized> {
+    let optimize_error = |message: &str| Error::Optimize {
+        path: path.to_path_buf(),
+        message: message.to_string(),
+    };
+    let pdf = fs::read(path).map_err(|source| Error::Read {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let trailer = Trailer::find(&pdf)
+        .ok_or_else(|| optimize_error("couldn't find its cross-reference trailer"))?;
+    if trailer.encrypted {
+        return Err(optimize_error("it's encrypted"));
+    }
+    let objects = objects(&pdf);
+    if !objects.contains_key(&trailer.root.0) {
+        return Err(optimize_error("couldn't find its document catalog"));
+    }
+    let rewritten = write(&pdf, &trailer, &objects);
+    let before = pdf.len() as u64;
+    if rewritten.len() as u64 >= before {
+        return Ok(Optimized {
+            before,
+            after: before,
+        });
+    }
+    fs::write(path, &rewritten).map_err(|source| Error::Write {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    Ok(Optimized {
+        before,
+        after: rewritten.len() as u64,
+    })
+}
+
+struct Object {
+    generation: usize,
+    /// The dictionary or other value, without the stream
+    body: Vec<u8>,
+    stream: Option<Vec<u8>>,
+}
+
+/// The latest definition of every object, with object streams unpacked and
+/// cross-reference streams left out.
+fn objects(pdf: &[u8]) -> BTreeMap<usize, Object> {
+    let mut objects = BTreeMap::new();
+    let mut at = 0;
+    while let Some(found) = find(pdf, b" obj", at) {
+        at = found + b" obj".len();
+        let Some((number, generation)) = header(pdf, found) else {
+            continue;
+        };
+        let Some((object, used)) = parse(&pdf[at..], generation) else {
+            continue;
+        };
+        // Past this object's `endobj`, not one inside its stream data.
+        at += used;
+        at += find(pdf, b"endobj", at).map_or(0, |end| end + b"endobj".len() - at);
+        if is_type(&object.body, b"/XRef") {
+            continue;
+        }
+        if is_type(&object.body, b"/ObjStm") {
+            let packed = object
+                .stream
+                .as_deref()
+                .and_then(|data| members(&object.body, data))
+                .unwrap_or_default();
+            for (member, body) in packed {
+                objects.insert(
+                    member,
+                    Object {
+                        generation: 0,
+                        body,
+                        stream: None,
+                    },
+                );
+            }
+            continue;
+        }
+        objects.insert(number, object);
+    }
+    objects
+}
+
+/// The object after an `N G obj` header, and how many bytes of `rest` it
+/// took before its `endobj`.
+fn parse(rest: &[u8], generation: usize) -> Option<(Object, usize)> {
+    let start = rest.len() - skip_whitespace(rest).len();
+    let text = &rest[start..];
+    if text.starts_with(b"<<") {
+        let dict = dictionary(text)?;
+        let after = &text[dict.len()..];
+        if skip_whitespace(after).starts_with(b"stream") {
+            let (data, used) = stream_bytes(dict, after)?;
+            let object = Object {
+                generation,
+                body: dict.to_vec(),
+                stream: Some(data.to_vec()),
+            };
+            return Some((object, start + dict.len() + used));
+        }
+    }
+    let end = find(rest, b"endobj", 0)?;
+    let object = Object {
+        generation,
+        body: trim(&rest[..end]).to_vec(),
+        stream: None,
+    };
+    Some((object, end))
+}
+
+/// The object number and generation of the `N G obj` header whose ` obj` is
+/// at `at`.
+fn header(pdf: &[u8], at: usize) -> Option<(usize, usize)> {
+    if pdf
+        .get(at + b" obj".len())
+        .is_some_and(|c| c.is_ascii_alphanumeric())
+    {
+        return None;
+    }
+    let digits = |text: &[u8]| -> Option<(usize, usize)> {
+        let start = text
+            .iter()
+            .rposition(|c| !c.is_ascii_digit())
+            .map_or(0, |other| other + 1);
+        let value = std::str::from_utf8(&text[start..]).ok()?.parse().ok()?;
+        Some((value, start))
+    };
+    let text = &pdf[at.saturating_sub(24)..at];
+    let (generation, start) = digits(text)?;
+    let text = text[..start].strip_suffix(b" ")?;
+    let (number, start) = digits(text)?;
+    if start > 0 && !text[start - 1].is_ascii_whitespace() {
+        return None;
+    }
+    Some((number, generation))
+}
+
+/// The stream data after `dict`, and how many bytes of `after` it took up to
+/// the end of `endstream`.
+fn stream_bytes<'a>(dict: &[u8], after: &'a [u8]) -> Option<(&'a [u8], usize)> {
+    let data = stream_data(after)?;
+    let start = after.len() - data.len();
+    let direct = value(dict, b"/Length")
+        .filter(|length| reference(length).is_none())
+        .and_then(integer)
+        .filter(|length| {
+            data.get(*length..)
+                .is_some_and(|rest| skip_whitespace(rest).starts_with(b"endstream"))
+        });
+    let length = match direct {
+        Some(length) => length,
+        // An indirect or wrong length: up to `endstream`, less its line break.
+        None => {
+            let end = find(data, b"endstream", 0)?;
+            let mut data = &data[..end];
+            data = data.strip_suffix(b"\n").unwrap_or(data);
+            data = data.strip_suffix(b"\r").unwrap_or(data);
+            data.len()
+        }
+    };
+    let end = find(&data[length..], b"endstream", 0)? + b"endstream".len();
+    Some((&data[..length], start + length + end))
+}
+
+fn skip_whitespace(text: &[u8]) -> &[u8] {
+    let start = text
+        .iter()
+        .position(|c| !c.is_ascii_whitespace())
+        .unwrap_or(text.len());
+    &text[start..]
+}
+
+fn is_type(body: &[u8], kind: &[u8]) -> bool {
+    value(body, b"/Type").is_some_and(|value| value.starts_with(kind))
+}
+
+fn deflate(data: &[u8]) -> Vec<u8> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::best());
+    encoder
+        .write_all(data)
+        .and_then(|_| encoder.finish())
+        .expect("writing to memory doesn't fail")
+}
+
+/// `dict` with its `/Length` set to `length`, and `/Filter /FlateDecode`
+/// added when `compressed`.
+fn with_length(dict: &[u8], length: usize, compressed: bool) -> Vec<u8> {
+    let mut out = dict.to_vec();
+    if let Some(at) = key(&out, b"/Length") {
+        let after = at + b"/Length".len();
+        let start = out.len() - skip_whitespace(&out[after..]).len();
+        let value = &out[start..];
+        let used = match reference(value) {
+            Some(_) => find(value, b"R", 0).map_or(0, |r| r + 1),
+            None => value.iter().take_while(|c| c.is_ascii_digit()).count(),
+        };
+        out.drain(at..start + used);
+    }
+    let close = rfind(&out, b">>").unwrap_or(out.len());
+    let mut entries = format!("/Length {}", length);
+    if compressed {
+        entries.push_str(" /Filter /FlateDecode");
+    }
+    entries.push(' ');
+    out.splice(close..close, entries.into_bytes());
+    out
+}
+
+fn write(pdf: &[u8], trailer: &Trailer, objects: &BTreeMap<usize, Object>) -> Vec<u8> {
+    let version = pdf
+        .get(5..8)
+        .and_then(|version| std::str::from_utf8(version).ok())
+        .filter(|version| *version > "1.5")
+        .unwrap_or("1.5");
+    let mut out = format!("%PDF-{}\n", version).into_bytes();
+    // Marks the file as binary for transfer tools.
+    out.extend_from_slice(b"%\xe2\xe3\xcf\xd3\n");
+
+    let last = objects.keys().next_back().copied().unwrap_or(0);
+    let packed_stream = last + 1;
+    let xref = last + 2;
+    // Kind 1 is at an offset with a generation, kind 2 in the object stream
+    // at an index.
+    let mut entries: BTreeMap<usize, (u8, usize, usize)> = BTreeMap::new();
+    let mut packed_header = String::new();
+    let mut packed_body = Vec::new();
+    let mut packed = 0;
+    for (number, object) in objects {
+        match &object.stream {
+            Some(data) => {
+                entries.insert(*number, (1, out.len(), object.generation));
+                let compressed = value(&object.body, b"/Filter").is_none();
+                let data = if compressed {
+                    deflate(data)
+                } else {
+                    data.clone()
+                };
+                out.extend_from_slice(format!("{} {} obj\n", number, object.generation).as_bytes());
+                out.extend_from_slice(&with_length(&object.body, data.len(), compressed));
+                out.extend_from_slice(b"\nstream\n");
+                out.extend_from_slice(&data);
+                out.extend_from_slice(b"\nendstream\nendobj\n");
+            }
+            None if object.generation == 0 => {
+                entries.insert(*number, (2, packed_stream, packed));
+                packed += 1;
+                packed_header.push_str(&format!("{} {} ", number, packed_body.len()));
+                packed_body.extend_from_slice(&object.body);
+                packed_body.push(b'\n');
+            }
+            None => {
+                entries.insert(*number, (1, out.len(), object.generation));
+                out.extend_from_slice(format!("{} {} obj\n", number, object.generation).as_bytes());
+                out.extend_from_slice(&object.body);
+                out.extend_from_slice(b"\nendobj\n");
+            }
+        }
+    }
+
+    let first = packed_header.len();
+    let mut data = packed_header.into_bytes();
+    data.extend_from_slice(&packed_body);
+    let data = deflate(&data);
+    entries.insert(packed_stream, (1, out.len(), 0));
+    out.extend_from_slice(
+        format!(
+            "{} 0 obj\n<< /Type /ObjStm /N {} /First {} /Length {} /Filter /FlateDecode >>\nstream\n",
+            packed_stream,
+            packed,
+            first,
+            data.len()
+        )
+        .as_bytes(),
+    );
+    out.extend_from_slice(&data);
+    out.extend_from_slice(b"\nendstream\nendobj\n");
+
+    let start = out.len();
+    entries.insert(xref, (1, start, 0));
+    let mut table = Vec::new();
+    for number in 0..=xref {
+        let (kind, field, generation) =
+            entries
+                .get(&number)
+                .copied()
+                .unwrap_or((0, 0, if number == 0 { 65535 } else { 0 }));
+        table.push(kind);
+        table.extend_from_slice(&(field as u32).to_be_bytes());
+        table.extend_from_slice(&(generation as u16).to_be_bytes());
+    }
+    let table = deflate(&table);
+    out.extend_from_slice(
+        format!(
+            "{} 0 obj\n<< /Type /XRef /Size {} /Root {} {} R /W [1 4 2] /Length {} /Filter /FlateDecode",
+            xref,
+            xref + 1,
+            trailer.root.0,
+            trailer.root.1,
+            table.len()
+        )
+        .as_bytes(),
+    );
+    out.extend_from_slice(&trailer.carried);
+    out.extend_from_slice(b" >>\nstream\n");
+    out.extend_from_slice(&table);
+    out.extend_from_slice(format!("\nendstream\nendobj\nstartxref\n{}\n%%EOF\n", start).as_bytes());
+    out
+}
@@ -0,0 +1,226 @@
+//! Just enough PDF parsing to post-process what Tectonic writes: finding the
+//! trailer and objects, including those packed into compressed object streams.
+
+use std::io::Read;
+
+use flate2::read::ZlibDecoder;
+
+/// What rewriting or updating a PDF needs from its last trailer.
+pub(crate) struct Trailer {
+    /// Offset of the last cross-reference section, for `/Prev`
+    pub offset: usize,
+    /// Whether that section is a cross-reference stream rather than a table
+    pub stream: bool,
+    pub size: usize,
+    pub root: (usize, usize),
+    /// `/Info` and `/ID`, copied as written
+    pub carried: Vec<u8>,
+    pub encrypted: bool,
+}
+
+impl Trailer {
+    pub fn find(pdf: &[u8]) -> Option<Trailer> {
+        let startxref = rfind(pdf, b"startxref")?;
+        let offset = integer(&pdf[startxref + b"startxref".len()..])?;
+        let section = pdf.get(offset..)?;
+        let stream = !section.starts_with(b"xref");
+        let dictionary = if stream {
+            dictionary(section)?
+        } else {
+            let trailer = find(section, b"trailer", 0)?;
+            dictionary(&section[trailer..])?
+        };
+        let size = integer(value(dictionary, b"/Size")?)?;
+        let root = reference(value(dictionary, b"/Root")?)?;
+        let mut carried = Vec::new();
+        if let Some(info) = value(dictionary, b"/Info").and_then(reference) {
+            carried.extend_from_slice(format!(" /Info {} {} R", info.0, info.1).as_bytes());
+        }
+        if let Some(id) = value(dictionary, b"/ID") {
+            let end = find(id, b"]", 0)?;
+            carried.extend_from_slice(b" /ID ");
+            carried.extend_from_slice(&id[..=end]);
+        }
+        Some(Trailer {
+            offset,
+            stream,
+            size,
+            root,
+            carried,
+            encrypted: value(dictionary, b"/Encrypt").is_some(),
+        })
+    }
+}
+
+/// The latest definition of object `number`: written out directly, or
+/// packed into a compressed object stream.
+pub(crate) fn object(pdf: &[u8], number: usize, generation: usize) -> Option<Vec<u8>> {
+    let header = format!("{} {} obj", number, generation);
+    let mut search = pdf.len();
+    while let Some(at) = rfind(&pdf[..search], header.as_bytes()) {
+        if at == 0 || !pdf[at - 1].is_ascii_digit() {
+            let body = &pdf[at + header.len()..];
+            let end = find(body, b"endobj", 0)?;
+            return Some(trim(&body[..end]).to_vec());
+        }
+        search = at;
+    }
+    object_in_streams(pdf, number)
+}
+
+fn object_in_streams(pdf: &[u8], number: usize) -> Option<Vec<u8>> {
+    let mut from = 0;
+    while let Some(at) = find(pdf, b"/ObjStm", from) {
+        from = at + 1;
+        let Some(open) = rfind(&pdf[..at], b" obj") else {
+            continue;
+        };
+        let Some(dictionary) = dictionary(&pdf[open..]) else {
+            continue;
+        };
+        let after = &pdf[open + find(&pdf[open..], b"<<", 0)? + dictionary.len()..];
+        let Some(data) = stream_data(after) else {
+            continue;
+        };
+        let found = members(dictionary, data)
+            .into_iter()
+            .flatten()
+            .find(|(member, _)| *member == number);
+        if let Some((_, body)) = found {
+            return Some(body);
+        }
+    }
+    None
+}
+
+/// The raw data of the stream whose dictionary `after` follows, up to where
+/// a compressed stream stops decoding.
+pub(crate) fn stream_data(after: &[u8]) -> Option<&[u8]> {
+    let stream = find(after, b"stream", 0)?;
+    let mut data = &after[stream + b"stream".len()..];
+    data = data.strip_prefix(b"\r").unwrap_or(data);
+    data = data.strip_prefix(b"\n").unwrap_or(data);
+    Some(data)
+}
+
+/// Decodes Flate data, ignoring anything after the end of the compressed stream.
+pub(crate) fn inflate(data: &[u8]) -> Option<Vec<u8>> {
+    let mut out = Vec::new();
+    match ZlibDecoder::new(data).read_to_end(&mut out) {
+        Err(_) if out.is_empty() => None,
+        _ => Some(out),
+    }
+}
+
+/// The objects packed into an object stream, given its dictionary and
+/// compressed data.
+pub(crate) fn members(dictionary: &[u8], data: &[u8]) -> Option<Vec<(usize, Vec<u8>)>> {
+    let count = value(dictionary, b"/N").and_then(integer)?;
+    let first = value(dictionary, b"/First").and_then(integer)?;
+    let objects = inflate(data)?;
+    let numbers: Vec<usize> = String::from_utf8_lossy(objects.get(..first)?)
+        .split_whitespace()
+        .filter_map(|n| n.parse().ok())
+        .collect();
+    let mut members = Vec::new();
+    for (i, pair) in numbers.chunks(2).take(count).enumerate() {
+        let (&number, &offset) = (pair.first()?, pair.get(1)?);
+        let end = numbers
+            .get(2 * i + 3)
+            .map_or(objects.len(), |next| first + next);
+        members.push((number, trim(objects.get(first + offset..end)?).to_vec()));
+    }
+    Some(members)
+}
+
+/// The `<< ... >>` dictionary `text` starts with, after whitespace and an
+/// optional `N G obj` header.
+pub(crate) fn dictionary(text: &[u8]) -> Option<&[u8]> {
+    let start = find(text, b"<<", 0)?;
+    let mut depth = 0;
+    let mut i = start;
+    while i + 1 < text.len() {
+        match &text[i..i + 2] {
+            b"<<" => {
+                depth += 1;
+                i += 2;
+            }
+            b">>" => {
+                depth -= 1;
+                i += 2;
+                if depth == 0 {
+                    return Some(&text[start..i]);
+                }
+            }
+            _ => i += 1,
+        }
+    }
+    None
+}
+
+/// Where `key` appears in `dictionary` as a whole name, so `/Length` doesn't
+/// match `/Length1`.
+pub(crate) fn key(dictionary: &[u8], key: &[u8]) -> Option<usize> {
+    let mut from = 0;
+    while let Some(at) = find(dictionary, key, from) {
+        let next = dictionary.get(at + key.len());
+        if next.is_none_or(|c| !c.is_ascii_alphanumeric()) {
+            return Some(at);
+        }
+        from = at + 1;
+    }
+    None
+}
+
+/// What follows `name` in `dictionary`.
+pub(crate) fn value<'a>(dictionary: &'a [u8], name: &[u8]) -> Option<&'a [u8]> {
+    key(dictionary, name).map(|at| trim(&dictionary[at + name.len()..]))
+}
+
+pub(crate) fn integer(text: &[u8]) -> Option<usize> {
+    let text = trim(text);
+    let end = text
+        .iter()
+        .position(|c| !c.is_ascii_digit())
+        .unwrap_or(text.len());
+    std::str::from_utf8(&text[..end]).ok()?.parse().ok()
+}
+
+/// An indirect reference `N G R`.
+pub(crate) fn reference(text: &[u8]) -> Option<(usize, usize)> {
+    let mut parts = std::str::from_utf8(text.get(..text.len().min(32))?)
+        .ok()?
+        .split_whitespace();
+    let number = parts.next()?.parse().ok()?;
+    let generation = parts.next()?.parse().ok()?;
+    parts
+        .next()?
+        .starts_with('R')
+        .then_some((number, generation))
+}
+
+pub(crate) fn trim(text: &[u8]) -> &[u8] {
+    let start = text
+        .iter()
+        .position(|c| !c.is_ascii_whitespace())
+        .unwrap_or(text.len());
+    let end = text
+        .iter()
+        .rposition(|c| !c.is_ascii_whitespace())
+        .map_or(start, |end| end + 1);
+    &text[start..end]
+}
+
+pub(crate) fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    haystack
+        .get(from..)?
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|at| at + from)
+}
+
+pub(crate) fn rfind(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .rposition(|window| window == needle)
+}
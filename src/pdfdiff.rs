@@ -0,0 +1,313 @@
+//! Visual comparison of two PDFs, page by page, for reviewing template
+//! changes without opening both files. Pages are rasterized by Poppler's
+//! `pdftoppm` and the difference images are written as PNG.
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+
+use crate::{Error, Result};
+
+/// Channels may differ by this much before a pixel counts as changed, so
+/// anti-aliasing noise doesn't.
+const TOLERANCE: u8 = 32;
+
+/// Width in pixels of the gutter between side-by-side panels.
+const GUTTER: usize = 8;
+
+/// How each page's difference image is laid out.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum DiffMode {
+    /// The old page, the new page and the overlay next to each other
+    #[default]
+    SideBySide,
+    /// One page: unchanged content faded, removed content red, added content green
+    Overlay,
+}
+
+#[derive(Debug, Clone)]
+pub struct DiffOptions {
+    pub mode: DiffMode,
+    /// Rasterizing resolution
+    pub dpi: u32,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        DiffOptions {
+            mode: DiffMode::default(),
+            dpi: 72,
+        }
+    }
+}
+
+/// How one page changed.
+#[derive(Debug, Clone)]
+pub struct PageDiff {
+    /// From 1
+    pub page: usize,
+    pub changed_pixels: usize,
+    pub total_pixels: usize,
+    /// The difference image, written only for pages that changed
+    pub image: Option<PathBuf>,
+}
+
+/// Compares `old` and `new` page by page, writing `page-<n>.png` into
+/// `output_dir` for every page that differs. A page only one of them has is
+/// compared against a blank one.
+pub fn diff_pdfs(
+    old: &Path,
+    new: &Path,
+    output_dir: &Path,
+    options: &DiffOptions,
+) -> Result<Vec<PageDiff>> {
+    let old_pages = rasterize(old, options.dpi)?;
+    let new_pages = rasterize(new, options.dpi)?;
+    fs::create_dir_all(output_dir).map_err(|source| Error::Write {
+        path: output_dir.to_path_buf(),
+        source,
+    })?;
+    let mut diffs = Vec::new();
+    for page in 0..old_pages.len().max(new_pages.len()) {
+        let (old, new) = match (old_pages.get(page), new_pages.get(page)) {
+            (Some(old), Some(new)) => (old.clone(), new.clone()),
+            (Some(old), None) => (old.clone(), Raster::blank(old.width, old.height)),
+            (None, Some(new)) => (Raster::blank(new.width, new.height), new.clone()),
+            (None, None) => unreachable!("page is below one of the page counts"),
+        };
+        let (overlay, changed_pixels) = overlay(&old, &new);
+        let image = if changed_pixels == 0 {
+            None
+        } else {
+            let image = match options.mode {
+                DiffMode::SideBySide => beside(&[&old, &new, &overlay]),
+                DiffMode::Overlay => overlay.clone(),
+            };
+            let path = output_dir.join(format!("page-{}.png", page + 1));
+            fs::write(&path, image.png()).map_err(|source| Error::Write {
+                path: path.clone(),
+                source,
+            })?;
+            Some(path)
+        };
+        diffs.push(PageDiff {
+            page: page + 1,
+            changed_pixels,
+            total_pixels: overlay.width * overlay.height,
+            image,
+        });
+    }
+    Ok(diffs)
+}
+
+/// An RGB image, row by row.
+#[derive(Debug, Clone)]
+struct Raster {
+    width: usize,
+    height: usize,
+    pixels: Vec<[u8; 3]>,
+}
+
+impl Raster {
+    fn blank(width: usize, height: usize) -> Raster {
+        Raster {
+            width,
+            height,
+            pixels: vec![[255; 3]; width * height],
+        }
+    }
+
+    /// White outside the image.
+    fn get(&self, x: usize, y: usize) -> [u8; 3] {
+        if x < self.width && y < self.height {
+            self.pixels[y * self.width + x]
+        } else {
+            [255; 3]
+        }
+    }
+
+    /// Parses a binary PPM (`P6`) as `pdftoppm` writes it.
+    fn from_ppm(data: &[u8]) -> Option<Raster> {
+        let mut fields = Vec::new();
+        let mut position = 0;
+        while fields.len() < 4 {
+            while data.get(position)?.is_ascii_whitespace() {
+                position += 1;
+            }
+            if data[position] == b'#' {
+                while *data.get(position)? != b'\n' {
+                    position += 1;
+                }
+                continue;
+            }
+            let start = position;
+            while !data.get(position)?.is_ascii_whitespace() {
+                position += 1;
+            }
+            fields.push(std::str::from_utf8(&data[start..position]).ok()?);
+        }
+        let (width, height): (usize, usize) = (fields[1].parse().ok()?, fields[2].parse().ok()?);
+        if fields[0] != "P6" || fields[3] != "255" {
+            return None;
+        }
+        let pixels = data
+            .get(position + 1..position + 1 + width * height * 3)?
+            .chunks_exact(3)
+            .map(|rgb| [rgb[0], rgb[1], rgb[2]])
+            .collect();
+        Some(Raster {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    fn png(&self) -> Vec<u8> {
+        let mut header = Vec::with_capacity(13);
+        header.extend((self.width as u32).to_be_bytes());
+        header.extend((self.height as u32).to_be_bytes());
+        // 8-bit RGB, deflate, no filtering, not interlaced.
+        header.extend([8, 2, 0, 0, 0]);
+
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        for row in self.pixels.chunks(self.width.max(1)) {
+            let mut line = Vec::with_capacity(1 + row.len() * 3);
+            line.push(0);
+            line.extend(row.iter().flatten());
+            encoder
+                .write_all(&line)
+                .expect("writing to a Vec doesn't fail");
+        }
+        let data = encoder.finish().expect("writing to a Vec doesn't fail");
+
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        for (kind, body) in [(b"IHDR", &header), (b"IDAT", &data), (b"IEND", &Vec::new())] {
+            png.extend((body.len() as u32).to_be_bytes());
+            let start = png.len();
+            png.extend(kind);
+            png.extend(body);
+            let crc = crc32(&png[start..]);
+            png.extend(crc.to_be_bytes());
+        }
+        png
+    }
+}
+
+/// Each page of the PDF at `path` at `dpi`.
+fn rasterize(path: &Path, dpi: u32) -> Result<Vec<Raster>> {
+    let diff_error = |message: String| Error::DiffPdf {
+        path: path.to_path_buf(),
+        message,
+    };
+    let dir = tempfile::tempdir()
+        .map_err(|e| diff_error(format!("couldn't create a working directory: {}", e)))?;
+    let output = Command::new("pdftoppm")
+        .arg("-r")
+        .arg(dpi.to_string())
+        .arg(path)
+        .arg(dir.path().join("page"))
+        .output()
+        .map_err(|e| {
+            diff_error(format!(
+                "couldn't run pdftoppm, which comes with Poppler: {}",
+                e
+            ))
+        })?;
+    if !output.status.success() {
+        return Err(diff_error(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    // `page-1.ppm`, or `page-01.ppm` and so on with ten pages or more.
+    let mut files: Vec<PathBuf> = fs::read_dir(dir.path())
+        .map_err(|source| Error::Read {
+            path: dir.path().to_path_buf(),
+            source,
+        })?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .collect();
+    files.sort();
+    files
+        .iter()
+        .map(|file| {
+            let data = fs::read(file).map_err(|source| Error::Read {
+                path: file.clone(),
+                source,
+            })?;
+            Raster::from_ppm(&data).ok_or_else(|| {
+                diff_error(format!("pdftoppm wrote an unreadable {}", file.display()))
+            })
+        })
+        .collect()
+}
+
+/// `new` drawn over `old`, and how many pixels differ between them.
+fn overlay(old: &Raster, new: &Raster) -> (Raster, usize) {
+    let (width, height) = (old.width.max(new.width), old.height.max(new.height));
+    let mut image = Raster::blank(width, height);
+    let mut changed = 0;
+    for y in 0..height {
+        for x in 0..width {
+            let (a, b) = (old.get(x, y), new.get(x, y));
+            let differs = a.iter().zip(b).any(|(a, b)| a.abs_diff(b) > TOLERANCE);
+            image.pixels[y * width + x] = if !differs {
+                // Faded to a light gray so the changes stand out.
+                let gray = 191 + luminance(b) / 4;
+                [gray; 3]
+            } else if luminance(a) < luminance(b) {
+                changed += 1;
+                [220, 40, 40]
+            } else {
+                changed += 1;
+                [30, 160, 60]
+            };
+        }
+    }
+    (image, changed)
+}
+
+fn luminance([r, g, b]: [u8; 3]) -> u8 {
+    ((r as u32 * 299 + g as u32 * 587 + b as u32 * 114) / 1000) as u8
+}
+
+/// `panels` left to right on a gray background.
+fn beside(panels: &[&Raster]) -> Raster {
+    let width = panels.iter().map(|panel| panel.width).sum::<usize>()
+        + GUTTER * (panels.len().saturating_sub(1));
+    let height = panels.iter().map(|panel| panel.height).max().unwrap_or(0);
+    let mut image = Raster {
+        width,
+        height,
+        pixels: vec![[128; 3]; width * height],
+    };
+    let mut left = 0;
+    for panel in panels {
+        for y in 0..panel.height {
+            let row = &panel.pixels[y * panel.width..(y + 1) * panel.width];
+            image.pixels[y * width + left..y * width + left + panel.width].copy_from_slice(row);
+        }
+        left += panel.width + GUTTER;
+    }
+    image
+}
+
+/// The CRC-32 PNG chunks end with.
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
@@ -0,0 +1,47 @@
+use std::fmt::Arguments;
+use std::time::Duration;
+
+use anyhow::Error;
+use indicatif::{ProgressBar, ProgressStyle};
+use tectonic::status::{MessageKind, StatusBackend};
+
+/// Shows a spinner with the elapsed time while Tectonic runs and uses its notes
+/// ("downloading ...", "Running TeX ...") as the spinner message, so a first build
+/// that fetches the bundle doesn't look hung. Messages are still passed on to
+/// `inner`.
+pub struct ProgressStatusBackend<B> {
+    inner: B,
+    bar: ProgressBar,
+}
+
+impl<B: StatusBackend> ProgressStatusBackend<B> {
+    pub fn new(inner: B, message: &'static str) -> Self {
+        let bar = ProgressBar::new_spinner();
+        bar.set_style(
+            ProgressStyle::with_template("{spinner} {msg} [{elapsed}]")
+                .expect("invalid progress template"),
+        );
+        bar.set_message(message);
+        bar.enable_steady_tick(Duration::from_millis(100));
+        ProgressStatusBackend { inner, bar }
+    }
+
+    pub fn finish(&self) {
+        self.bar.finish_and_clear();
+    }
+}
+
+impl<B: StatusBackend> StatusBackend for ProgressStatusBackend<B> {
+    fn report(&mut self, kind: MessageKind, args: Arguments, err: Option<&Error>) {
+        if kind == MessageKind::Note {
+            self.bar.set_message(args.to_string());
+        }
+        let inner = &mut self.inner;
+        self.bar.suspend(|| inner.report(kind, args, err));
+    }
+
+    fn dump_error_logs(&mut self, output: &[u8]) {
+        let inner = &mut self.inner;
+        self.bar.suspend(|| inner.dump_error_logs(output));
+    }
+}
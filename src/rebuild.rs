@@ -0,0 +1,178 @@
+//! Builds for long-running modes, `rsume daemon` and `rsume lsp`: templates
+//! stay parsed and the Tectonic bundle open from one build to the next.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::Duration;
+
+use rsume::{is_html, template_dir, CompileOptions, Compiler, Error, Limits, Resume, Templates};
+use serde::Deserialize;
+use tracing::error;
+
+use crate::logging::TracingStatusBackend;
+use crate::watchdog;
+use crate::{builtin_name, load_templates, read};
+
+/// What to build, as a daemon request takes it.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Request {
+    pub input_path: PathBuf,
+    /// As on the command line, e.g. `builtin:sidebar`
+    pub template_path: PathBuf,
+    pub template_filename: String,
+    pub output_dir: PathBuf,
+    /// Parse the templates again, after editing them
+    #[serde(default)]
+    pub reload: bool,
+}
+
+#[derive(Default)]
+pub struct Rebuilder {
+    /// Opened by the first PDF build
+    compiler: Option<Compiler>,
+    /// Keyed by template path
+    templates: HashMap<PathBuf, Templates>,
+    /// Only `retries` applies to trusted data: see [`Rebuilder::untrusted`]
+    limits: Limits,
+    untrusted: bool,
+    compile_timeout: Option<Duration>,
+}
+
+impl Rebuilder {
+    /// Builds with `limits` on the data and the rendered output, request paths
+    /// kept inside `limits.root`, data file references kept inside the data's
+    /// directory, TeX markup refused in the data, no git info or Gravatar
+    /// download, and `get_env` disabled.
+    pub fn untrusted(limits: Limits) -> Rebuilder {
+        Rebuilder {
+            limits,
+            untrusted: true,
+            ..Rebuilder::default()
+        }
+    }
+
+    /// Builds without limits, for data you wrote yourself, retrying compiles
+    /// as `limits.retries` says.
+    pub fn trusted(limits: Limits) -> Rebuilder {
+        Rebuilder {
+            limits,
+            ..Rebuilder::default()
+        }
+    }
+
+    /// Fails a compile that takes longer than `timeout`, and exits the process
+    /// if the engine doesn't stop soon after, since it can't be stopped any
+    /// other way.
+    pub fn compile_timeout(mut self, timeout: Duration) -> Rebuilder {
+        self.compile_timeout = Some(timeout);
+        self
+    }
+
+    /// Builds `request`, returning the files written.
+    pub fn build(&mut self, request: &Request) -> rsume::Result<Vec<PathBuf>> {
+        if self.untrusted {
+            let limits = &self.limits;
+            limits.check_path("input_path", &request.input_path)?;
+            if builtin_name(&request.template_path).is_none() {
+                limits.check_template_path(&request.template_path)?;
+            }
+            limits.check_path("output_dir", &request.output_dir)?;
+            limits.check_data_file(&request.input_path)?;
+        }
+        let data = read(&request.input_path)?;
+        let mut resume = Resume::from_toml(&data)?;
+        let base = request.input_path.parent().unwrap_or(Path::new(""));
+        if self.untrusted {
+            resume.reject_markup()?;
+            resume.confine_files(base)?;
+        }
+        resume.author.revision = rsume::latest_revision(&request.input_path, &data)?;
+        // Untrusted data doesn't get to run git on the host or decide what it
+        // requests from Gravatar.
+        if !self.untrusted {
+            resume.author.git = rsume::git_info(&request.input_path);
+            resume.fetch_avatar()?;
+        }
+        let assets = resume.stage_assets(base)?;
+
+        if request.reload {
+            self.templates.remove(&request.template_path);
+        }
+        if !self.templates.contains_key(&request.template_path) {
+            let mut templates = load_templates(&request.template_path)?;
+            if self.untrusted {
+                templates.deny_environment();
+            }
+            self.templates
+                .insert(request.template_path.clone(), templates);
+        }
+        let templates = &self.templates[&request.template_path];
+        let output_filename = resume
+            .author
+            .application
+            .file_name(&request.template_filename);
+        let rendered = templates.render(&request.template_filename, &resume)?;
+        if self.untrusted {
+            self.limits
+                .check_output(&request.template_filename, &rendered)?;
+            self.limits.check_path(
+                "template_filename",
+                &request.output_dir.join(&output_filename),
+            )?;
+        }
+
+        fs::create_dir_all(&request.output_dir).map_err(|source| Error::Write {
+            path: request.output_dir.clone(),
+            source,
+        })?;
+        if is_html(&request.template_filename) {
+            let path = request.output_dir.join(&output_filename);
+            fs::write(&path, rendered).map_err(|source| Error::Write {
+                path: path.clone(),
+                source,
+            })?;
+            for asset in &assets {
+                asset.copy_into(&request.output_dir)?;
+            }
+            return Ok(vec![path]);
+        }
+        let options = CompileOptions {
+            engine: templates.engine(),
+            resource_dirs: match builtin_name(&request.template_path) {
+                Some(_) => Vec::new(),
+                None => vec![template_dir(&request.template_path)],
+            },
+            include_dirs: Vec::new(),
+            assets,
+            output_dir: request.output_dir.clone(),
+            synctex: false,
+            timeout: self.compile_timeout,
+            retries: self.limits.retries,
+            ..CompileOptions::default()
+        };
+        if self.compiler.is_none() {
+            self.compiler = Some(Compiler::new(&mut TracingStatusBackend)?);
+        }
+        let compiler = self.compiler.as_ref().expect("opened above");
+        let _watchdog = self.compile_timeout.map(|timeout| {
+            let input_path = request.input_path.clone();
+            watchdog::watch(timeout + watchdog::GRACE, move || {
+                error!(
+                    "compiling {} took longer than {} seconds; exiting, since the compile can't be stopped",
+                    input_path.display(),
+                    timeout.as_secs()
+                );
+                process::exit(1);
+            })
+        });
+        compiler.compile_pdf(
+            &output_filename,
+            &rendered,
+            &options,
+            &mut TracingStatusBackend,
+        )?;
+        Ok(vec![options.output_path(&output_filename, "pdf")])
+    }
+}
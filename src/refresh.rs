@@ -0,0 +1,210 @@
+//! `rsume refresh`: GitHub stars and crates.io and npm versions and downloads
+//! for projects, looked
+//! up when asked rather than on every build and kept in the data file as
+//! `stats`, so builds stay offline and reproducible.
+
+use chrono::Local;
+use reqwest::blocking::{Client, RequestBuilder};
+use serde_json::Value as Json;
+use toml_edit::{value, Document, InlineTable, Item, Table};
+use url::Url;
+
+use crate::{Error, Result, Resume};
+
+/// Read for a token to raise GitHub's rate limit for unauthenticated requests.
+pub const GITHUB_TOKEN_VAR: &str = "GITHUB_TOKEN";
+
+/// The result of [`refresh_stats`].
+#[derive(Debug)]
+pub struct Refresh {
+    /// The data file with the new stats
+    pub source: String,
+    /// Names of the projects looked up
+    pub refreshed: Vec<String>,
+}
+
+/// Looks up the stats of every project with a GitHub `source`, a `crate` or
+/// an `npm` package, except those refreshed fewer than `max_age` days ago, and
+/// writes them into `source` as each project's `stats`. Comments and
+/// formatting elsewhere in the file survive.
+pub fn refresh_stats(source: &str, max_age: u32) -> Result<Refresh> {
+    let resume = Resume::from_toml(source)?;
+    let mut document: Document = source.parse().map_err(Error::Edit)?;
+    let today = Local::now().date_naive();
+    let client = Client::builder()
+        .user_agent(concat!("rsume/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .map_err(|e| Error::Refresh(e.to_string()))?;
+    let token = std::env::var(GITHUB_TOKEN_VAR).ok();
+    let mut refreshed = Vec::new();
+    for (index, project) in resume.author.projects.iter().enumerate() {
+        let repository = github_repository(&project.source);
+        if repository.is_none() && project.crate_name.is_none() && project.npm.is_none() {
+            continue;
+        }
+        let fresh = project
+            .stats
+            .as_ref()
+            .is_some_and(|stats| (today - stats.refreshed).num_days() < i64::from(max_age));
+        if fresh {
+            continue;
+        }
+        let refresh_error =
+            |message: String| Error::Refresh(format!("{}: {}", project.name, message));
+        let stars = repository
+            .map(|repository| {
+                let mut request =
+                    client.get(format!("https://api.github.com/repos/{}", repository));
+                if let Some(token) = &token {
+                    request = request.bearer_auth(token);
+                }
+                fetch(request).and_then(|answer| answer.number(&["stargazers_count"]))
+            })
+            .transpose()
+            .map_err(refresh_error)?;
+        let krate = project
+            .crate_name
+            .as_ref()
+            .map(|name| -> std::result::Result<_, String> {
+                let answer =
+                    fetch(client.get(format!("https://crates.io/api/v1/crates/{}", name)))?;
+                // Crates with only pre-releases have no stable version.
+                let version = answer
+                    .string(&["crate", "max_stable_version"])
+                    .or_else(|_| answer.string(&["crate", "max_version"]))?;
+                Ok((version, answer.number(&["crate", "downloads"])?))
+            })
+            .transpose()
+            .map_err(refresh_error)?;
+        let npm = project
+            .npm
+            .as_ref()
+            .map(|name| -> std::result::Result<_, String> {
+                // Scoped names keep their slash in the downloads API only.
+                let latest = fetch(client.get(format!(
+                    "https://registry.npmjs.org/{}/latest",
+                    name.replace('/', "%2F")
+                )))?;
+                let downloads = fetch(client.get(format!(
+                    "https://api.npmjs.org/downloads/point/last-month/{}",
+                    name
+                )))?;
+                Ok((
+                    latest.string(&["version"])?,
+                    downloads.number(&["downloads"])?,
+                ))
+            })
+            .transpose()
+            .map_err(refresh_error)?;
+
+        let mut stats = InlineTable::new();
+        if let Some(stars) = stars {
+            stats.insert("stars", (stars as i64).into());
+        }
+        let mut version = None;
+        if let Some((crate_version, downloads)) = krate {
+            stats.insert("downloads", (downloads as i64).into());
+            version = Some(crate_version);
+        }
+        if let Some((npm_version, downloads)) = npm {
+            stats.insert("monthly_downloads", (downloads as i64).into());
+            version.get_or_insert(npm_version);
+        }
+        if let Some(version) = version {
+            stats.insert("version", version.into());
+        }
+        let date: toml_edit::Datetime = today
+            .to_string()
+            .parse()
+            .expect("dates format as YYYY-MM-DD");
+        stats.insert("refreshed", date.into());
+        set_stats(&mut document, index, stats);
+        refreshed.push(project.name.clone());
+    }
+    Ok(Refresh {
+        source: document.to_string(),
+        refreshed,
+    })
+}
+
+/// `owner/repository` for a GitHub URL.
+fn github_repository(source: &str) -> Option<String> {
+    let url = Url::parse(source).ok()?;
+    if !matches!(url.host_str()?, "github.com" | "www.github.com") {
+        return None;
+    }
+    let mut segments = url.path_segments()?.filter(|segment| !segment.is_empty());
+    let owner = segments.next()?;
+    let repository = segments.next()?;
+    Some(format!(
+        "{}/{}",
+        owner,
+        repository.strip_suffix(".git").unwrap_or(repository)
+    ))
+}
+
+/// A registry's JSON answer, with where it came from for errors.
+struct Answer {
+    url: String,
+    json: Json,
+}
+
+impl Answer {
+    fn field(&self, path: &[&str]) -> std::result::Result<&Json, String> {
+        path.iter()
+            .try_fold(&self.json, |json, key| json.get(key))
+            .ok_or_else(|| format!("{} answered without `{}`", self.url, path.join(".")))
+    }
+
+    fn number(&self, path: &[&str]) -> std::result::Result<u64, String> {
+        let field = self.field(path)?;
+        field
+            .as_u64()
+            .ok_or_else(|| format!("{} answered `{}` = {}", self.url, path.join("."), field))
+    }
+
+    fn string(&self, path: &[&str]) -> std::result::Result<String, String> {
+        let field = self.field(path)?;
+        field
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| format!("{} answered `{}` = {}", self.url, path.join("."), field))
+    }
+}
+
+fn fetch(request: RequestBuilder) -> std::result::Result<Answer, String> {
+    let response = request
+        .send()
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| e.to_string())?;
+    let url = response.url().to_string();
+    let text = response.text().map_err(|e| e.to_string())?;
+    let json = serde_json::from_str(&text).map_err(|e| format!("{} answered with {}", url, e))?;
+    Ok(Answer { url, json })
+}
+
+/// Replaces project `index`'s `stats`, as a `[projects.stats]` table for
+/// `[[projects]]` and inline for projects written inline.
+fn set_stats(document: &mut Document, index: usize, stats: InlineTable) {
+    match document.get_mut("projects") {
+        Some(Item::ArrayOfTables(projects)) => {
+            if let Some(project) = projects.get_mut(index) {
+                let mut table = Table::new();
+                for (key, item) in stats.iter() {
+                    table.insert(key, value(item.clone()));
+                }
+                project.insert("stats", Item::Table(table));
+            }
+        }
+        Some(item) => {
+            if let Some(project) = item
+                .as_array_mut()
+                .and_then(|projects| projects.get_mut(index))
+                .and_then(toml_edit::Value::as_inline_table_mut)
+            {
+                project.insert("stats", stats.into());
+            }
+        }
+        None => {}
+    }
+}
@@ -0,0 +1,864 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{Days, Local, NaiveDate};
+use tera::ast::{Expr, ExprVal, Node};
+use tera::{try_get_value, Context, Tera, Value};
+
+use crate::assets::Font;
+use crate::builtin::partials;
+use crate::markdown::{self, Format};
+use crate::{contact, emoji, functions, html, hyphenation, timeline};
+use crate::{
+    Amount, Author, Availability, Column, EarlierExperienceSummary, Engine, Error,
+    GradePointAverage, Location, Manifest, ReferenceVisibility, Result, Resume, Spacing, SECTIONS,
+};
+
+/// A directory of Tera templates with rsume's filters registered.
+pub struct Templates {
+    tera: Tera,
+    manifest: Option<Manifest>,
+}
+
+impl Templates {
+    /// Loads every template matching a glob such as `templates/**/*.tex`, along with
+    /// the `manifest.toml` in the directory the glob starts from.
+    ///
+    /// Templates can `extends`, `include` and `import` each other, anything under
+    /// the directory's `partials/`, and the bundled [`PARTIALS`](crate::PARTIALS),
+    /// which a file of the same name in `partials/` replaces.
+    pub fn new(template_path: &Path) -> Result<Templates> {
+        let glob = template_path
+            .to_str()
+            .ok_or_else(|| Error::TemplatePath(template_path.to_path_buf()))?;
+        // Parsed without resolving `extends` until every template is loaded.
+        let mut tera = Tera::parse(glob).map_err(Error::Templates)?;
+        let dir = template_dir(template_path);
+        let mut files = Vec::new();
+        partial_files(&dir, Path::new(PARTIALS_DIR), &mut files).map_err(|source| Error::Read {
+            path: dir.join(PARTIALS_DIR),
+            source,
+        })?;
+        tera.extend(&partials()).map_err(Error::Templates)?;
+        tera.add_template_files(files).map_err(Error::Templates)?;
+        let mut templates = Templates::with_tera(tera);
+        templates.manifest = Manifest::load(&dir)?;
+        Ok(templates)
+    }
+
+    /// Builds templates from in-memory `(name, source)` pairs, for callers without a
+    /// filesystem such as a browser-based editor.
+    pub fn from_sources<'a, I>(sources: I) -> Result<Templates>
+    where
+        I: IntoIterator<Item = (&'a str, &'a str)>,
+    {
+        let mut tera = partials();
+        tera.add_raw_templates(sources).map_err(Error::Templates)?;
+        Ok(Templates::with_tera(tera))
+    }
+
+    pub(crate) fn with_tera(mut tera: Tera) -> Templates {
+        for filter in FILTERS {
+            tera.register_filter(filter.name, filter.filter);
+        }
+        for function in FUNCTIONS {
+            tera.register_function(function.name, function.function);
+        }
+        Templates {
+            tera,
+            manifest: None,
+        }
+    }
+
+    /// Declares what the templates accept, for sources without a `manifest.toml`.
+    pub fn with_manifest(mut self, manifest: Manifest) -> Templates {
+        self.manifest = Some(manifest);
+        self
+    }
+
+    pub fn manifest(&self) -> Option<&Manifest> {
+        self.manifest.as_ref()
+    }
+
+    /// The manifest's `[engine]`, or Tectonic's defaults without one.
+    pub fn engine(&self) -> Engine {
+        self.manifest
+            .as_ref()
+            .map(|manifest| manifest.engine.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.tera.get_template_names()
+    }
+
+    pub fn len(&self) -> usize {
+        self.names().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn render(&self, name: &str, resume: &Resume) -> Result<String> {
+        let render_error = |source| Error::Render {
+            name: name.to_string(),
+            source,
+        };
+        let context = Context::from_value(self.context(name, resume)?).map_err(render_error)?;
+        let rendered = self.tera.render(name, &context).map_err(render_error)?;
+        Ok(require_packages(rendered))
+    }
+
+    /// The context `render` gives template `name`: the data after the manifest's
+    /// layout, privacy options and computed fields such as `spacing` are applied.
+    pub fn context(&self, name: &str, resume: &Resume) -> Result<Value> {
+        if let Some(manifest) = &self.manifest {
+            manifest.validate(&resume.author)?;
+        }
+        let mut context = context(&resume.author, self.manifest.as_ref());
+        if let Some(language) = &resume.author.language {
+            match hyphenation::babel_language(language) {
+                Some(babel) => context["babel_language"] = babel.into(),
+                None if name.ends_with(".tex") => {
+                    return Err(Error::UnsupportedLanguage(language.clone()))
+                }
+                None => {}
+            }
+        }
+        if html::is_html(name) {
+            html::apply(&mut context, &resume.author.html);
+        }
+        contact::apply(&mut context);
+        Ok(context)
+    }
+
+    /// Replaces Tera's `get_env` with one that fails, so templates rendering
+    /// untrusted data can't be steered into printing the host's environment.
+    pub fn deny_environment(&mut self) {
+        self.tera
+            .register_function("get_env", |_: &HashMap<String, Value>| {
+                Err(tera::Error::msg(
+                    "Function `get_env` is disabled for untrusted data",
+                ))
+            });
+    }
+
+    /// The unrendered source of a template, if it was loaded from a file.
+    pub fn source(&self, name: &str) -> Option<String> {
+        let path = self.tera.get_template(name).ok()?.path.as_ref()?;
+        fs::read_to_string(path).ok()
+    }
+
+    /// The context paths a template reads, with loop variables resolved to their
+    /// container, e.g. `experiences[].company.name`.
+    pub fn used_variables(&self, name: &str) -> Option<BTreeSet<String>> {
+        let template = self.tera.get_template(name).ok()?;
+        let mut used = BTreeSet::new();
+        walk_nodes(&template.ast, &HashMap::new(), &mut used);
+        Some(used)
+    }
+}
+
+/// The template context for `author`. Privacy controls are enforced here rather
+/// than left to templates, so hidden data never reaches an export.
+fn context(author: &Author, manifest: Option<&Manifest>) -> Value {
+    // Plain data with string keys always serializes.
+    let mut context = serde_json::to_value(author).unwrap_or_default();
+    for (field, all) in [("email", "emails"), ("phone", "phones")] {
+        let details = context[field].take();
+        context[field] = details[0]["value"].as_str().unwrap_or_default().into();
+        context[all] = details;
+    }
+    context["font"] = serde_json::to_value(Font::new(&author.theme)).unwrap_or_default();
+    context["spacing"] =
+        serde_json::to_value(Spacing::for_level(author.tighten)).unwrap_or_default();
+    let layout = match manifest {
+        Some(manifest) => author.layout.or(&manifest.layout),
+        None => author.layout.clone(),
+    };
+    for (column, name) in [(Column::Main, "main"), (Column::Sidebar, "sidebar")] {
+        let sections = layout.sections(column);
+        for section in &sections {
+            context["layout"][section] = name.into();
+        }
+        context[format!("{}_sections", name)] = sections.into();
+    }
+    if author.references.is_empty() {
+        // Nothing to offer on request either.
+        context["references_visibility"] = "hidden".into();
+    } else if author.references_visibility != ReferenceVisibility::Full {
+        context["references"] = Value::Array(Vec::new());
+    }
+    for section in SECTIONS {
+        let present = match &context[section] {
+            Value::Array(items) => !items.is_empty(),
+            Value::String(text) => !text.trim().is_empty(),
+            _ => false,
+        };
+        context[format!("has_{}", section)] = present.into();
+    }
+    if let Some(summary) = EarlierExperienceSummary::new(&author.earlier_experiences) {
+        context["earlier_experience_summary"] = serde_json::to_value(summary).unwrap_or_default();
+        // Listed under the experiences heading.
+        context["has_experiences"] = true.into();
+    }
+    let engagements = author.engagements();
+    context["has_engagements"] = (!engagements.is_empty()).into();
+    context["engagements"] = serde_json::to_value(engagements).unwrap_or_default();
+    let categories = &author.skills_layout.categories;
+    if let Some(skills) = context["skills"].as_array_mut() {
+        // Stable, so order within a category and among unlisted ones is kept.
+        skills.sort_by_key(|skill| {
+            categories
+                .iter()
+                .position(|category| skill["category"] == category.as_str())
+                .unwrap_or(categories.len())
+        });
+    }
+    if author.hide_grant_amounts {
+        if let Some(grants) = context["grants"].as_array_mut() {
+            for grant in grants {
+                grant["amount"] = Value::Null;
+            }
+        }
+    }
+    if author.stamp.generated || author.stamp.valid_days.is_some() {
+        let today = Local::now().date_naive();
+        context["generated_on"] = today.to_string().into();
+        if let Some(days) = author.stamp.valid_days {
+            context["valid_until"] = (today + Days::new(days.into())).to_string().into();
+        }
+    }
+    if author.hide_gpa {
+        if let Some(educations) = context["educations"].as_array_mut() {
+            for education in educations {
+                education["gpa"] = Value::Null;
+            }
+        }
+    }
+    context
+}
+
+/// A filter rsume registers on top of Tera's builtins.
+pub struct FilterDoc {
+    pub name: &'static str,
+    pub description: &'static str,
+    filter: fn(&Value, &HashMap<String, Value>) -> tera::Result<Value>,
+}
+
+pub const FILTERS: &[FilterDoc] = &[
+    FilterDoc {
+        name: "emoji",
+        description: "Replaces common emoji such as 🚀 with their FontAwesome icon and drops the rest, since LaTeX fonts have no glyphs for them; `strip=true` drops them all, e.g. before `escape_latex` or for plain text",
+        filter: emoji::emoji,
+    },
+    FilterDoc {
+        name: "escape_latex",
+        description: "Escapes `&`, `%`, `#` and `$` so the value can be used in LaTeX text, and swaps emoji for FontAwesome icons like the `emoji` filter",
+        filter: escape_latex,
+    },
+    FilterDoc {
+        name: "format_amount",
+        description: "Formats an `amount` table with its currency's symbol and thousands separators, e.g. `$450,000` or `CHF 1,200`; `compact=true` shortens it to e.g. `$450k` or `€1.2M`, `to=\"html\"` or `to=\"text\"` skips LaTeX escaping",
+        filter: format_amount,
+    },
+    FilterDoc {
+        name: "format_availability",
+        description: "Writes an `availability` table as one line, e.g. `Available from 5 January 2026, notice period 3 months`; `to=\"html\"` or `to=\"text\"` skips LaTeX escaping",
+        filter: format_availability,
+    },
+    FilterDoc {
+        name: "format_date",
+        description: "Formats a date with `strftime` codes, `format=\"%b %Y\"` by default, e.g. `Sep 2020`",
+        filter: format_date,
+    },
+    FilterDoc {
+        name: "format_gpa",
+        description: "Formats a `gpa` table as e.g. `3.85/4.0` or `92\\%`; `field=\"major\"` picks the major GPA, `precision` sets the decimals, `to=\"html\"` or `to=\"text\"` skips LaTeX escaping",
+        filter: format_gpa,
+    },
+    FilterDoc {
+        name: "format_location",
+        description: "Joins a `location` table into one line in the country's usual order, e.g. `San Francisco, CA, USA`; `address=true` adds the street and postal code, `country=false` drops the country, `to=\"html\"` or `to=\"text\"` skips LaTeX escaping",
+        filter: format_location,
+    },
+    FilterDoc {
+        name: "markdown",
+        description: "Converts paragraphs, bullet lists, `**bold**`, `*emphasis*`, `` `code` `` and links to escaped LaTeX, or with `to=\"html\"` to HTML to mark `safe`, or with `to=\"text\"` to plain text",
+        filter: markdown,
+    },
+];
+
+/// A function rsume registers on top of Tera's builtins.
+pub struct FunctionDoc {
+    pub name: &'static str,
+    pub signature: &'static str,
+    pub description: &'static str,
+    function: fn(&HashMap<String, Value>) -> tera::Result<Value>,
+}
+
+pub const FUNCTIONS: &[FunctionDoc] = &[
+    FunctionDoc {
+        name: "icon",
+        signature: "icon(name)",
+        description: "A FontAwesome glyph such as `email`, `phone`, `location`, `website` or any FontAwesome 5 name like `github`; the package is loaded automatically",
+        function: icon,
+    },
+    FunctionDoc {
+        name: "qr",
+        signature: "qr(text, size=\"2cm\")",
+        description: "A QR code of `text`, such as a `contact_block` link or the `vcard`; the package is loaded automatically",
+        function: contact::qr,
+    },
+    FunctionDoc {
+        name: "timeline",
+        signature: "timeline(experiences, width=16, color=\"black!60\")",
+        description: "A TikZ picture of the experiences as bars along a time axis `width` cm wide, with overlapping roles stacked; TikZ is loaded automatically",
+        function: timeline::timeline,
+    },
+    FunctionDoc {
+        name: "today",
+        signature: "today(format=\"%Y-%m-%d\")",
+        description: "The local date, formatted with `strftime` codes",
+        function: functions::today,
+    },
+    FunctionDoc {
+        name: "years_since",
+        signature: "years_since(date, until=today())",
+        description: "Whole years from a `YYYY-MM-DD` date, e.g. for `{{ years_since(date=experiences[0].start_date) }}+ years`",
+        function: functions::years_since,
+    },
+    FunctionDoc {
+        name: "pluralize",
+        signature: "pluralize(count, singular, plural=singular + \"s\")",
+        description: "`singular` when `count` is 1, otherwise `plural`",
+        function: functions::pluralize,
+    },
+];
+
+/// Tera's own functions, available in every template.
+pub const BUILTIN_FUNCTIONS: &[(&str, &str)] = &[
+    ("range(end, start=0, step_by=1)", "An array of integers"),
+    (
+        "now(timestamp=false, utc=false)",
+        "The current date and time",
+    ),
+    ("throw(message)", "Fails the render with `message`"),
+    ("get_random(end, start=0)", "A random integer"),
+    ("get_env(name, default)", "An environment variable"),
+];
+
+// Loop variables map to the path of the container they iterate, with `[]` appended.
+type Scope = HashMap<String, String>;
+
+fn walk_nodes(nodes: &[Node], scope: &Scope, used: &mut BTreeSet<String>) {
+    for node in nodes {
+        match node {
+            Node::VariableBlock(_, expr) => walk_expr(expr, scope, used),
+            Node::Set(_, set) => walk_expr(&set.value, scope, used),
+            Node::FilterSection(_, section, _) => {
+                walk_args(&section.filter.args, scope, used);
+                walk_nodes(&section.body, scope, used);
+            }
+            Node::Block(_, block, _) => walk_nodes(&block.body, scope, used),
+            Node::MacroDefinition(_, definition, _) => walk_nodes(&definition.body, scope, used),
+            Node::Forloop(_, forloop, _) => {
+                walk_expr(&forloop.container, scope, used);
+                let mut inner = scope.clone();
+                if let ExprVal::Ident(container) = &forloop.container.val {
+                    let container = resolve(container, scope);
+                    match &forloop.key {
+                        Some(key) => {
+                            inner.insert(key.clone(), String::new());
+                            inner.insert(forloop.value.clone(), format!("{}.*", container));
+                        }
+                        None => {
+                            inner.insert(forloop.value.clone(), format!("{}[]", container));
+                        }
+                    }
+                }
+                walk_nodes(&forloop.body, &inner, used);
+                if let Some(body) = &forloop.empty_body {
+                    walk_nodes(body, scope, used);
+                }
+            }
+            Node::If(condition, _) => {
+                for (_, expr, body) in &condition.conditions {
+                    walk_expr(expr, scope, used);
+                    walk_nodes(body, scope, used);
+                }
+                if let Some((_, body)) = &condition.otherwise {
+                    walk_nodes(body, scope, used);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn walk_expr(expr: &Expr, scope: &Scope, used: &mut BTreeSet<String>) {
+    match &expr.val {
+        ExprVal::Ident(ident) => {
+            let path = resolve(ident, scope);
+            if !path.is_empty() && !path.starts_with("loop.") && path != "loop" {
+                used.insert(path);
+            }
+        }
+        ExprVal::Math(math) => {
+            walk_expr(&math.lhs, scope, used);
+            walk_expr(&math.rhs, scope, used);
+        }
+        ExprVal::Logic(logic) => {
+            walk_expr(&logic.lhs, scope, used);
+            walk_expr(&logic.rhs, scope, used);
+        }
+        ExprVal::In(test) => {
+            walk_expr(&test.lhs, scope, used);
+            walk_expr(&test.rhs, scope, used);
+        }
+        ExprVal::Test(test) => {
+            walk_expr(&Expr::new(ExprVal::Ident(test.ident.clone())), scope, used);
+            for arg in &test.args {
+                walk_expr(arg, scope, used);
+            }
+        }
+        ExprVal::FunctionCall(call) => walk_args(&call.args, scope, used),
+        ExprVal::MacroCall(call) => walk_args(&call.args, scope, used),
+        ExprVal::Array(items) => {
+            for item in items {
+                walk_expr(item, scope, used);
+            }
+        }
+        ExprVal::StringConcat(concat) => {
+            for value in &concat.values {
+                walk_expr(&Expr::new(value.clone()), scope, used);
+            }
+        }
+        _ => {}
+    }
+    for filter in &expr.filters {
+        walk_args(&filter.args, scope, used);
+    }
+}
+
+fn walk_args(args: &HashMap<String, Expr>, scope: &Scope, used: &mut BTreeSet<String>) {
+    for arg in args.values() {
+        walk_expr(arg, scope, used);
+    }
+}
+
+fn resolve(ident: &str, scope: &Scope) -> String {
+    let (head, rest) = match ident.find(['.', '[']) {
+        Some(index) => ident.split_at(index),
+        None => (ident, ""),
+    };
+    match scope.get(head) {
+        Some(container) if container.is_empty() => String::new(),
+        Some(container) => format!("{}{}", container, rest),
+        None => ident.to_string(),
+    }
+}
+
+/// Where a template directory keeps templates shared by the others.
+const PARTIALS_DIR: &str = "partials";
+
+/// Every file under `dir/name`, recursively, with its name relative to `dir`.
+fn partial_files(
+    dir: &Path,
+    name: &Path,
+    files: &mut Vec<(PathBuf, Option<String>)>,
+) -> std::io::Result<()> {
+    let path = dir.join(name);
+    if !path.is_dir() {
+        return Ok(());
+    }
+    for entry in fs::read_dir(&path)? {
+        let entry = entry?;
+        let name = name.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            partial_files(dir, &name, files)?;
+        } else {
+            // Tera names templates with forward slashes on every platform.
+            let template_name = name
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            files.push((entry.path(), Some(template_name)));
+        }
+    }
+    Ok(())
+}
+
+/// The directory a template glob such as `templates/**/*.tex` is rooted at.
+pub fn template_dir(template_path: &Path) -> PathBuf {
+    let mut dir = PathBuf::new();
+    for component in template_path.components() {
+        if component
+            .as_os_str()
+            .to_string_lossy()
+            .contains(['*', '?', '[', '{'])
+        {
+            return non_empty_dir(dir);
+        }
+        dir.push(component);
+    }
+    non_empty_dir(dir.parent().map(Path::to_path_buf).unwrap_or_default())
+}
+
+fn non_empty_dir(dir: PathBuf) -> PathBuf {
+    if dir.as_os_str().is_empty() {
+        PathBuf::from(".")
+    } else {
+        dir
+    }
+}
+
+/// Friendlier names for the icons contact lines use; anything else is passed to
+/// `\faIcon` as a FontAwesome 5 name.
+const ICON_ALIASES: &[(&str, &str)] = &[
+    ("email", "envelope"),
+    ("location", "map-marker-alt"),
+    ("website", "globe"),
+    ("web", "globe"),
+    ("phone", "phone"),
+    ("linkedin", "linkedin"),
+    ("stackoverflow", "stack-overflow"),
+];
+
+fn icon(args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let name = match args.get("name") {
+        Some(name) => try_get_value!("icon", "name", String, name),
+        None => return Err(tera::Error::msg("Function `icon` requires a `name`")),
+    };
+    let name = name.to_lowercase();
+    let name = ICON_ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == name)
+        .map_or(name.as_str(), |(_, icon)| icon);
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(tera::Error::msg(format!(
+            "Function `icon` got `{}`, which isn't a FontAwesome name",
+            name
+        )));
+    }
+    Ok(Value::String(format!("\\faIcon{{{}}}", name)))
+}
+
+/// Packages the output of rsume's functions needs, keyed by a command they emit.
+const REQUIRED_PACKAGES: &[(&str, &str)] = &[
+    ("\\faIcon{", "fontawesome5"),
+    ("\\begin{tikzpicture}", "tikz"),
+    ("\\qrcode[", "qrcode"),
+];
+
+/// Loads the packages rsume's functions need right after `\documentclass`,
+/// unless the template loaded them itself.
+fn require_packages(rendered: String) -> String {
+    let missing: String = REQUIRED_PACKAGES
+        .iter()
+        .filter(|(command, package)| {
+            rendered.contains(command) && !rendered.contains(&format!("{{{}}}", package))
+        })
+        .map(|(_, package)| format!("\\usepackage{{{}}}\n", package))
+        .collect();
+    if missing.is_empty() {
+        return rendered;
+    }
+    let Some(start) = rendered.find("\\documentclass") else {
+        return rendered;
+    };
+    let end = rendered[start..]
+        .find('\n')
+        .map_or(rendered.len(), |end| start + end + 1);
+    let mut out = String::with_capacity(rendered.len() + missing.len() + 1);
+    out.push_str(&rendered[..end]);
+    if !out.ends_with('\n') {
+        out.push('\n');
+    }
+    out.push_str(&missing);
+    out.push_str(&rendered[end..]);
+    out
+}
+
+fn escape_latex(value: &Value, _: &HashMap<String, Value>) -> tera::Result<Value> {
+    let input = try_get_value!("escape_latex", "value", String, value);
+    Ok(Value::String(escape_latex_str(&input)))
+}
+
+fn format_gpa(value: &Value, args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let gpa: GradePointAverage = serde_json::from_value(value.clone())
+        .map_err(|e| tera::Error::chain("Filter `format_gpa` expects a gpa table", e))?;
+    let field = args
+        .get("field")
+        .and_then(Value::as_str)
+        .unwrap_or("overall");
+    let score = match field {
+        "overall" => Some(gpa.overall),
+        "major" => gpa.major,
+        _ => {
+            return Err(tera::Error::msg(format!(
+                "Filter `format_gpa` got field `{}`, expected `overall` or `major`",
+                field
+            )))
+        }
+    };
+    let Some(score) = score else {
+        return Ok(Value::String(String::new()));
+    };
+    let percentage = gpa.scale == 100.0;
+    let precision = match args.get("precision") {
+        Some(precision) => try_get_value!("format_gpa", "precision", usize, precision),
+        None if percentage => 0,
+        None => 2,
+    };
+    Ok(Value::String(if percentage {
+        match output_format("format_gpa", args)? {
+            Format::Latex => format!("{:.*}\\%", precision, score),
+            Format::Html | Format::Text => format!("{:.*}%", precision, score),
+        }
+    } else {
+        format!("{:.*}/{:?}", precision, score, gpa.scale)
+    }))
+}
+
+/// Symbols written before the number; other currencies get their code.
+const CURRENCY_SYMBOLS: &[(&str, &str)] = &[
+    ("USD", "$"),
+    ("EUR", "€"),
+    ("GBP", "£"),
+    ("JPY", "¥"),
+    ("CNY", "¥"),
+    ("INR", "₹"),
+    ("CAD", "CA$"),
+    ("AUD", "A$"),
+];
+
+fn format_amount(value: &Value, args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let amount: Amount = serde_json::from_value(value.clone())
+        .map_err(|e| tera::Error::chain("Filter `format_amount` expects an amount table", e))?;
+    let compact = match args.get("compact") {
+        Some(compact) => try_get_value!("format_amount", "compact", bool, compact),
+        None => false,
+    };
+    let number = if compact {
+        let units = [(1e9, "B"), (1e6, "M"), (1e3, "k"), (1.0, "")];
+        let size = amount.value.abs();
+        let mut unit = units
+            .iter()
+            .position(|&(unit, _)| size >= unit)
+            .unwrap_or(units.len() - 1);
+        // So 999,950 is 1M rather than 1000k.
+        while unit > 0 && (size / units[unit].0 * 10.0).round() >= 10_000.0 {
+            unit -= 1;
+        }
+        let (unit, suffix) = units[unit];
+        format!("{}{}", trim_decimal(amount.value / unit), suffix)
+    } else {
+        grouped(amount.value)
+    };
+    let currency = amount.currency.to_ascii_uppercase();
+    let formatted = match CURRENCY_SYMBOLS.iter().find(|(code, _)| *code == currency) {
+        Some((_, symbol)) => format!("{}{}", symbol, number),
+        None => format!("{} {}", currency, number),
+    };
+    Ok(Value::String(match output_format("format_amount", args)? {
+        Format::Latex => escape_latex_str(&formatted),
+        Format::Html | Format::Text => formatted,
+    }))
+}
+
+/// One decimal at most, and none when it's zero.
+fn trim_decimal(value: f64) -> String {
+    let rounded = format!("{:.1}", value);
+    rounded
+        .strip_suffix(".0")
+        .map(str::to_string)
+        .unwrap_or(rounded)
+}
+
+/// With thousands separators, and cents only when there are any.
+fn grouped(value: f64) -> String {
+    let cents = (value.abs() * 100.0).round() as u64;
+    let (whole, cents) = (cents / 100, cents % 100);
+    let digits = whole.to_string();
+    let mut out = String::new();
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            out.push(',');
+        }
+        out.push(digit);
+    }
+    if cents > 0 {
+        out.push_str(&format!(".{:02}", cents));
+    }
+    if value < 0.0 {
+        out.insert(0, '-');
+    }
+    out
+}
+
+fn format_availability(value: &Value, args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let expected = "Filter `format_availability` expects an `availability` table";
+    let field = |name: &str| value.get(name).and_then(Value::as_str);
+    if !value.is_object() {
+        return Err(tera::Error::msg(expected));
+    }
+    let availability = Availability {
+        text: field("text").map(str::to_string),
+        from: field("from")
+            .map(str::parse::<NaiveDate>)
+            .transpose()
+            .map_err(|e| tera::Error::chain(expected, e))?,
+        notice: field("notice").map(str::to_string),
+    };
+    let line = availability.one_line();
+    Ok(Value::String(
+        match output_format("format_availability", args)? {
+            Format::Latex => escape_latex_str(&line),
+            // Tera escapes it on output.
+            Format::Html | Format::Text => line,
+        },
+    ))
+}
+
+fn format_date(value: &Value, args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let date: NaiveDate = serde_json::from_value(value.clone())
+        .map_err(|e| tera::Error::chain("Filter `format_date` expects a date", e))?;
+    let format = match args.get("format") {
+        Some(format) => try_get_value!("format_date", "format", String, format),
+        None => "%b %Y".to_string(),
+    };
+    strftime("Filter `format_date`", date, &format).map(Value::String)
+}
+
+/// `date` formatted as `format` says. chrono reports a malformed `format`,
+/// or one asking for a time of day, only as a `fmt::Error`, which `to_string`
+/// would turn into a panic.
+pub(crate) fn strftime(what: &str, date: NaiveDate, format: &str) -> tera::Result<String> {
+    let mut out = String::new();
+    write!(out, "{}", date.format(format)).map_err(|_| {
+        tera::Error::msg(format!(
+            "{} got format=`{}`, which isn't a date format such as `%b %Y`",
+            what, format
+        ))
+    })?;
+    Ok(out)
+}
+
+fn format_location(value: &Value, args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let location: Location = serde_json::from_value(value.clone())
+        .map_err(|e| tera::Error::chain("Filter `format_location` expects a location table", e))?;
+    let flag = |name: &str, default: bool| match args.get(name) {
+        Some(value) => Ok(try_get_value!("format_location", name, bool, value)),
+        None => Ok(default),
+    };
+    let line = location.one_line(flag("address", false)?, flag("country", true)?);
+    Ok(Value::String(
+        match output_format("format_location", args)? {
+            Format::Latex => escape_latex_str(&line),
+            // Tera escapes it on output.
+            Format::Html | Format::Text => line,
+        },
+    ))
+}
+
+fn markdown(value: &Value, args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let input = try_get_value!("markdown", "value", String, value);
+    Ok(Value::String(match output_format("markdown", args)? {
+        Format::Latex => markdown::to_latex(&input),
+        Format::Html => markdown::to_html(&input),
+        Format::Text => markdown::to_text(&input),
+    }))
+}
+
+/// The `to` argument of filters that can produce LaTeX, HTML or plain text.
+fn output_format(filter: &str, args: &HashMap<String, Value>) -> tera::Result<Format> {
+    match args.get("to").and_then(Value::as_str) {
+        None | Some("latex") => Ok(Format::Latex),
+        Some("html") => Ok(Format::Html),
+        Some("text") => Ok(Format::Text),
+        Some(other) => Err(tera::Error::msg(format!(
+            "Filter `{}` got to=\"{}\", expected \"latex\", \"html\" or \"text\"",
+            filter, other
+        ))),
+    }
+}
+
+pub fn escape_latex_str(input: &str) -> String {
+    let mut output = String::with_capacity(input.len() * 2);
+    for c in input.chars() {
+        match c {
+            '&' | '%' | '#' | '$' => output.push_str(format!("\\{}", c).as_str()),
+            c if emoji::is_emoji(c) => output.push_str(&emoji::to_latex(c)),
+            _ => output.push(c),
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn amount(value: f64, currency: &str, args: &[(&str, Value)]) -> tera::Result<Value> {
+        let args = args
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.clone()))
+            .collect();
+        format_amount(&json!({"value": value, "currency": currency}), &args)
+    }
+
+    #[test]
+    fn amounts_are_grouped_with_cents_only_when_there_are_any() {
+        assert_eq!(amount(450000.0, "usd", &[]).unwrap(), "\\$450,000");
+        assert_eq!(amount(1234.5, "EUR", &[]).unwrap(), "€1,234.50");
+        assert_eq!(grouped(-1234.0), "-1,234");
+        assert_eq!(grouped(1_000_000.004), "1,000,000");
+        assert_eq!(grouped(0.0), "0");
+    }
+
+    #[test]
+    fn compact_amounts_keep_one_decimal_at_most() {
+        let compact = [("compact", json!(true)), ("to", json!("text"))];
+        assert_eq!(amount(2_500_000.0, "USD", &compact).unwrap(), "$2.5M");
+        assert_eq!(amount(150_000.0, "USD", &compact).unwrap(), "$150k");
+        assert_eq!(amount(3e9, "USD", &compact).unwrap(), "$3B");
+        assert_eq!(amount(999.0, "USD", &compact).unwrap(), "$999");
+        assert_eq!(amount(999_950.0, "USD", &compact).unwrap(), "$1M");
+        assert_eq!(amount(999.96, "USD", &compact).unwrap(), "$1k");
+        assert_eq!(amount(-999_950.0, "USD", &compact).unwrap(), "$-1M");
+        assert_eq!(trim_decimal(1.96), "2");
+    }
+
+    #[test]
+    fn unknown_currencies_are_written_as_their_code() {
+        assert_eq!(
+            amount(12000.0, "chf", &[("to", json!("html"))]).unwrap(),
+            "CHF 12,000"
+        );
+    }
+
+    #[test]
+    fn bad_date_formats_are_errors() {
+        let args = |format: &str| HashMap::from([("format".to_string(), json!(format))]);
+        let date = json!("2021-03-04");
+        assert_eq!(
+            format_date(&date, &args("%d %B %Y")).unwrap(),
+            "04 March 2021"
+        );
+        assert!(format_date(&date, &args("%Q")).is_err());
+        assert!(format_date(&date, &args("%H:%M")).is_err());
+    }
+
+    #[test]
+    fn amounts_need_an_amount_table() {
+        assert!(format_amount(&json!(12), &HashMap::new()).is_err());
+        assert!(amount(1.0, "USD", &[("to", json!("pdf"))]).is_err());
+    }
+}
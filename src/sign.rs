@@ -0,0 +1,243 @@
+//! Signing compiled PDFs so recipients can check they weren't altered. An
+//! incremental update appends an invisible signature field holding a detached
+//! PKCS#7 signature over every byte of the file except the signature itself.
+
+use std::fs;
+use std::path::Path;
+
+use chrono::Utc;
+use openssl::pkcs12::Pkcs12;
+use openssl::pkcs7::{Pkcs7, Pkcs7Flags};
+use openssl::pkey::{PKey, Private};
+use openssl::stack::Stack;
+use openssl::x509::X509;
+
+use crate::pdf::{find, object, rfind, Trailer};
+use crate::{Error, Result};
+
+/// Bytes reserved for the DER signature; enough for a certificate chain of
+/// a few intermediates.
+const SIGNATURE_SPACE: usize = 16 * 1024;
+
+/// Width of each patched `/ByteRange` number.
+const RANGE_WIDTH: usize = 10;
+
+/// A private key and its certificate chain, loaded from a PKCS#12 (`.p12` or
+/// `.pfx`) file.
+pub struct Signer {
+    key: PKey<Private>,
+    certificate: X509,
+    chain: Stack<X509>,
+}
+
+impl Signer {
+    pub fn from_pkcs12(path: &Path, password: &str) -> Result<Signer> {
+        let der = fs::read(path).map_err(|source| Error::Read {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let certificate_error = |message: String| Error::Certificate {
+            path: path.to_path_buf(),
+            message,
+        };
+        let parsed = Pkcs12::from_der(&der)
+            .and_then(|pkcs12| pkcs12.parse2(password))
+            .map_err(|e| certificate_error(e.to_string()))?;
+        let (Some(key), Some(certificate)) = (parsed.pkey, parsed.cert) else {
+            return Err(certificate_error(
+                "it needs both a private key and a certificate".to_string(),
+            ));
+        };
+        let chain = match parsed.ca {
+            Some(chain) => chain,
+            None => Stack::new().map_err(|e| certificate_error(e.to_string()))?,
+        };
+        Ok(Signer {
+            key,
+            certificate,
+            chain,
+        })
+    }
+}
+
+/// Signs the PDF at `path` in place.
+pub fn sign_pdf(path: &Path, signer: &Signer) -> Result<()> {
+    let sign_error = |message: &str| Error::Sign {
+        path: path.to_path_buf(),
+        message: message.to_string(),
+    };
+    let mut pdf = fs::read(path).map_err(|source| Error::Read {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let trailer = Trailer::find(&pdf)
+        .ok_or_else(|| sign_error("couldn't find its cross-reference trailer"))?;
+    if trailer.encrypted {
+        return Err(sign_error("it's encrypted"));
+    }
+    let catalog = object(&pdf, trailer.root.0, trailer.root.1)
+        .ok_or_else(|| sign_error("couldn't find its document catalog"))?;
+    if find(&catalog, b"/AcroForm", 0).is_some() {
+        return Err(sign_error(
+            "it already has a form, which signing doesn't merge with",
+        ));
+    }
+    let close =
+        rfind(&catalog, b">>").ok_or_else(|| sign_error("its catalog isn't a dictionary"))?;
+
+    let signature = trailer.size;
+    let field = trailer.size + 1;
+    if !pdf.ends_with(b"\n") {
+        pdf.push(b'\n');
+    }
+    let mut offsets = Vec::new();
+
+    offsets.push((signature, pdf.len()));
+    pdf.extend_from_slice(
+        format!(
+            "{} 0 obj\n<< /Type /Sig /Filter /Adobe.PPKLite /SubFilter /adbe.pkcs7.detached /M (D:{}Z) /ByteRange [0 ",
+            signature,
+            Utc::now().format("%Y%m%d%H%M%S")
+        )
+        .as_bytes(),
+    );
+    let byte_range = pdf.len();
+    pdf.extend_from_slice(format!("{}] /Contents ", " ".repeat(3 * RANGE_WIDTH + 2)).as_bytes());
+    let contents_start = pdf.len();
+    pdf.push(b'<');
+    pdf.extend(std::iter::repeat_n(b'0', 2 * SIGNATURE_SPACE));
+    pdf.push(b'>');
+    let contents_end = pdf.len();
+    pdf.extend_from_slice(b" >>\nendobj\n");
+
+    offsets.push((field, pdf.len()));
+    pdf.extend_from_slice(
+        format!(
+            "{} 0 obj\n<< /Type /Annot /Subtype /Widget /FT /Sig /T (Signature1) /V {} 0 R /F 132 /Rect [0 0 0 0] >>\nendobj\n",
+            field, signature
+        )
+        .as_bytes(),
+    );
+
+    offsets.push((trailer.root.0, pdf.len()));
+    pdf.extend_from_slice(format!("{} {} obj\n", trailer.root.0, trailer.root.1).as_bytes());
+    pdf.extend_from_slice(&catalog[..close]);
+    pdf.extend_from_slice(
+        format!("/AcroForm << /Fields [{} 0 R] /SigFlags 3 >> ", field).as_bytes(),
+    );
+    pdf.extend_from_slice(&catalog[close..]);
+    pdf.extend_from_slice(b"\nendobj\n");
+
+    write_xref(&mut pdf, &trailer, offsets);
+
+    let range = format!(
+        "{} {} {}",
+        contents_start,
+        contents_end,
+        pdf.len() - contents_end
+    );
+    pdf[byte_range..byte_range + range.len()].copy_from_slice(range.as_bytes());
+
+    let mut signed = pdf[..contents_start].to_vec();
+    signed.extend_from_slice(&pdf[contents_end..]);
+    let der = Pkcs7::sign(
+        &signer.certificate,
+        &signer.key,
+        &signer.chain,
+        &signed,
+        Pkcs7Flags::DETACHED | Pkcs7Flags::BINARY,
+    )
+    .and_then(|pkcs7| pkcs7.to_der())
+    .map_err(|e| sign_error(&e.to_string()))?;
+    if der.len() > SIGNATURE_SPACE {
+        return Err(sign_error(
+            "the signature is larger than the space reserved for it",
+        ));
+    }
+    let hex: String = der.iter().map(|byte| format!("{:02X}", byte)).collect();
+    pdf[contents_start + 1..contents_start + 1 + hex.len()].copy_from_slice(hex.as_bytes());
+
+    fs::write(path, pdf).map_err(|source| Error::Write {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+/// Appends a cross-reference section for the objects at `offsets`, in the
+/// same form as the file's own, and the trailer pointing back to it.
+fn write_xref(pdf: &mut Vec<u8>, trailer: &Trailer, mut offsets: Vec<(usize, usize)>) {
+    let start = pdf.len();
+    if trailer.stream {
+        // The stream lists itself, after the objects appended before it.
+        let xref = trailer.size + 2;
+        offsets.push((xref, start));
+        offsets.sort_unstable();
+        let mut data = Vec::new();
+        for (_, offset) in &offsets {
+            data.push(1);
+            data.extend_from_slice(&(*offset as u32).to_be_bytes());
+            data.extend_from_slice(&0u16.to_be_bytes());
+        }
+        let index: Vec<String> = runs(&offsets)
+            .iter()
+            .map(|(first, count)| format!("{} {}", first, count))
+            .collect();
+        pdf.extend_from_slice(
+            format!(
+                "{} 0 obj\n<< /Type /XRef /Size {} /Root {} {} R /Prev {} /Index [{}] /W [1 4 2] /Length {}",
+                xref,
+                xref + 1,
+                trailer.root.0,
+                trailer.root.1,
+                trailer.offset,
+                index.join(" "),
+                data.len()
+            )
+            .as_bytes(),
+        );
+        pdf.extend_from_slice(&trailer.carried);
+        pdf.extend_from_slice(b" >>\nstream\n");
+        pdf.extend_from_slice(&data);
+        pdf.extend_from_slice(b"\nendstream\nendobj\n");
+    } else {
+        offsets.sort_unstable();
+        pdf.extend_from_slice(b"xref\n");
+        let mut entries = offsets.iter();
+        for (first, count) in runs(&offsets) {
+            pdf.extend_from_slice(format!("{} {}\n", first, count).as_bytes());
+            for (number, offset) in entries.by_ref().take(count) {
+                let generation = if *number == trailer.root.0 {
+                    trailer.root.1
+                } else {
+                    0
+                };
+                pdf.extend_from_slice(format!("{:010} {:05} n \n", offset, generation).as_bytes());
+            }
+        }
+        pdf.extend_from_slice(
+            format!(
+                "trailer\n<< /Size {} /Root {} {} R /Prev {}",
+                trailer.size + 2,
+                trailer.root.0,
+                trailer.root.1,
+                trailer.offset
+            )
+            .as_bytes(),
+        );
+        pdf.extend_from_slice(&trailer.carried);
+        pdf.extend_from_slice(b" >>\n");
+    }
+    pdf.extend_from_slice(format!("startxref\n{}\n%%EOF\n", start).as_bytes());
+}
+
+/// Consecutive object numbers in sorted `offsets` as `(first, count)`.
+fn runs(offsets: &[(usize, usize)]) -> Vec<(usize, usize)> {
+    let mut runs: Vec<(usize, usize)> = Vec::new();
+    for (number, _) in offsets {
+        match runs.last_mut() {
+            Some((first, count)) if *first + *count == *number => *count += 1,
+            _ => runs.push((*number, 1)),
+        }
+    }
+    runs
+}
@@ -0,0 +1,166 @@
+//! Keeping `skills` in step with the experiences: tools the highlights and
+//! technologies mention that aren't listed, and listed skills nothing backs up.
+
+use serde::Serialize;
+
+use crate::variant::words;
+use crate::Author;
+
+/// Tools looked for in highlights besides every experience's `technologies`.
+const TECHNOLOGIES: &str = include_str!("technologies.txt");
+
+/// A skill and the fields that mention or list it.
+#[derive(Debug, Serialize)]
+pub struct SkillSuggestion {
+    pub skill: String,
+    /// E.g. `experiences[0].technologies[2]` or `skills[1].keywords[0]`
+    pub fields: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SkillSuggestions {
+    /// Used in experiences but missing from `skills`, most mentioned first
+    pub missing: Vec<SkillSuggestion>,
+    /// Listed in `skills` but not mentioned by any experience
+    pub unevidenced: Vec<SkillSuggestion>,
+}
+
+impl Author {
+    pub fn suggest_skills(&self) -> SkillSuggestions {
+        // Highlights are matched by case, so `Go` isn't found in "go live".
+        let highlights: Vec<(String, String, String)> = self
+            .experiences
+            .iter()
+            .enumerate()
+            .flat_map(|(i, e)| {
+                e.highlights.iter().enumerate().map(move |(j, highlight)| {
+                    (
+                        format!("experiences[{}].highlights[{}]", i, j),
+                        cased_words(highlight.as_str()),
+                        words(highlight.as_str()),
+                    )
+                })
+            })
+            .collect();
+        let technologies: Vec<(String, &str)> = self
+            .experiences
+            .iter()
+            .enumerate()
+            .flat_map(|(i, e)| {
+                e.technologies
+                    .iter()
+                    .enumerate()
+                    .map(move |(j, technology)| {
+                        (
+                            format!("experiences[{}].technologies[{}]", i, j),
+                            technology.as_str(),
+                        )
+                    })
+            })
+            .collect();
+
+        let listed: Vec<(String, &str)> = self
+            .skills
+            .iter()
+            .enumerate()
+            .flat_map(|(i, skill)| {
+                // A skill with keywords is usually a category such as `Languages`.
+                let terms: Vec<(String, &str)> = if skill.keywords.is_empty() {
+                    vec![(format!("skills[{}].name", i), skill.name.as_str())]
+                } else {
+                    skill
+                        .keywords
+                        .iter()
+                        .enumerate()
+                        .map(|(j, keyword)| {
+                            (format!("skills[{}].keywords[{}]", i, j), keyword.as_str())
+                        })
+                        .collect()
+                };
+                terms
+            })
+            .collect();
+        let is_listed = |term: &str| listed.iter().any(|(_, skill)| words(skill) == words(term));
+
+        let mut candidates: Vec<&str> = technologies.iter().map(|(_, t)| *t).collect();
+        for technology in known_technologies() {
+            candidates.push(technology);
+        }
+        let mut missing: Vec<SkillSuggestion> = Vec::new();
+        for candidate in candidates {
+            let term = words(candidate);
+            if term.trim().is_empty()
+                || is_listed(candidate)
+                || missing.iter().any(|found| words(&found.skill) == term)
+            {
+                continue;
+            }
+            let cased = cased_words(candidate);
+            let mut fields: Vec<String> = technologies
+                .iter()
+                .filter(|(_, technology)| words(technology) == term)
+                .map(|(field, _)| field.clone())
+                .collect();
+            fields.extend(
+                highlights
+                    .iter()
+                    .filter(|(_, text, _)| text.contains(&cased))
+                    .map(|(field, _, _)| field.clone()),
+            );
+            if !fields.is_empty() {
+                missing.push(SkillSuggestion {
+                    skill: candidate.to_string(),
+                    fields,
+                });
+            }
+        }
+        missing.sort_by(|a, b| {
+            b.fields
+                .len()
+                .cmp(&a.fields.len())
+                .then_with(|| a.skill.cmp(&b.skill))
+        });
+
+        let unevidenced = listed
+            .iter()
+            .filter(|(_, skill)| {
+                let term = words(skill);
+                !term.trim().is_empty()
+                    && !technologies
+                        .iter()
+                        .any(|(_, technology)| words(technology) == term)
+                    && !highlights.iter().any(|(_, _, text)| text.contains(&term))
+            })
+            .map(|(field, skill)| SkillSuggestion {
+                skill: skill.to_string(),
+                fields: vec![field.clone()],
+            })
+            .collect();
+
+        SkillSuggestions {
+            missing,
+            unevidenced,
+        }
+    }
+}
+
+/// The bundled list of well-known tools, languages and platforms.
+pub(crate) fn known_technologies() -> impl Iterator<Item = &'static str> {
+    TECHNOLOGIES
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+}
+
+/// Like [`words`], keeping the case.
+fn cased_words(text: &str) -> String {
+    let mut out = String::from(" ");
+    for word in text
+        .split(|c: char| !(c.is_alphanumeric() || matches!(c, '+' | '#')))
+        .filter(|word| !word.is_empty())
+    {
+        out.push_str(word);
+        out.push(' ');
+    }
+    out
+}
@@ -0,0 +1,232 @@
+use std::fmt::Write;
+use std::ops::Range;
+
+use toml::Value;
+
+use crate::render::escape_latex_str;
+
+/// The first error reported in a TeX log, e.g.
+///
+/// ```text
+/// ! Undefined control sequence.
+/// l.42 \foo
+/// ```
+#[derive(Debug, Clone)]
+pub struct TexLogError {
+    pub message: String,
+    pub line: Option<usize>,
+}
+
+pub fn parse_log(log: &str) -> Option<TexLogError> {
+    let mut lines = log.lines();
+    let message = lines.find_map(|line| line.strip_prefix("! "))?.to_string();
+    let line = lines.find_map(|line| {
+        let digits: String = line
+            .strip_prefix("l.")?
+            .chars()
+            .take_while(char::is_ascii_digit)
+            .collect();
+        digits.parse().ok()
+    });
+    Some(TexLogError { message, line })
+}
+
+/// The page count from the engine's `Output written on resume.xdv (2 pages, ...)` line.
+pub fn parse_page_count(log: &str) -> Option<usize> {
+    let line = log
+        .lines()
+        .find(|line| line.starts_with("Output written on "))?;
+    let (_, counts) = line.split_once(" (")?;
+    counts.split(' ').next()?.parse().ok()
+}
+
+/// Where in the rendered output, template and data a LaTeX error most likely came from.
+pub struct Located {
+    pub line: usize,
+    pub rendered_line: String,
+    pub template_line: Option<usize>,
+    pub fields: Vec<String>,
+}
+
+pub fn locate(
+    error: &TexLogError,
+    rendered: &str,
+    template_source: Option<&str>,
+    data: &Value,
+) -> Option<Located> {
+    let line = error.line?;
+    let rendered_line = rendered.lines().nth(line.saturating_sub(1))?;
+    Some(Located {
+        line,
+        rendered_line: rendered_line.trim().to_string(),
+        template_line: template_source.and_then(|s| template_line(s, rendered_line)),
+        fields: data_fields(data, rendered_line),
+    })
+}
+
+/// Explains a LaTeX error in terms of the rendered line, the template line that most
+/// likely produced it and the data fields whose values appear in it.
+pub fn describe(
+    error: &TexLogError,
+    rendered: &str,
+    template_name: &str,
+    template_source: Option<&str>,
+    data: &Value,
+) -> String {
+    let mut out = String::new();
+    let Some(located) = locate(error, rendered, template_source, data) else {
+        let _ = writeln!(out, "LaTeX error: {}", error.message);
+        return out;
+    };
+    let _ = writeln!(
+        out,
+        "LaTeX error at line {} of the rendered {}: {}",
+        located.line, template_name, error.message
+    );
+    let _ = writeln!(out, "  rendered: {}", located.rendered_line);
+    if let Some(template_line) = located.template_line {
+        let _ = writeln!(out, "  template: {}:{}", template_name, template_line);
+    }
+    for field in located.fields {
+        let _ = writeln!(out, "  contains: {}", field);
+    }
+    out
+}
+
+/// Finds the template line whose literal text (everything outside Tera tags) best
+/// matches the rendered line. Returns a 1-based line number.
+fn template_line(source: &str, rendered_line: &str) -> Option<usize> {
+    let mut best = None;
+    let mut best_score = 0;
+    for (index, line) in source.lines().enumerate() {
+        let literals = literal_fragments(line);
+        let score: usize = literals.iter().map(|l| l.len()).sum();
+        if score > best_score && contains_in_order(rendered_line, &literals) {
+            best = Some(index + 1);
+            best_score = score;
+        }
+    }
+    best
+}
+
+fn literal_fragments(line: &str) -> Vec<&str> {
+    let mut fragments = Vec::new();
+    let mut rest = line;
+    while let Some(start) = ["{{", "{%", "{#"]
+        .iter()
+        .filter_map(|open| rest.find(open))
+        .min()
+    {
+        fragments.push(&rest[..start]);
+        let close = match &rest[start..start + 2] {
+            "{{" => "}}",
+            "{%" => "%}",
+            _ => "#}",
+        };
+        match rest[start..].find(close) {
+            Some(end) => rest = &rest[start + end + 2..],
+            None => {
+                rest = "";
+                break;
+            }
+        }
+    }
+    fragments.push(rest);
+    fragments
+        .into_iter()
+        .map(str::trim)
+        .filter(|f| !f.is_empty())
+        .collect()
+}
+
+fn contains_in_order(haystack: &str, needles: &[&str]) -> bool {
+    let mut rest = haystack;
+    for needle in needles {
+        match rest.find(needle) {
+            Some(index) => rest = &rest[index + needle.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+/// Paths like `experiences[0].highlights[2]` of the string values that appear,
+/// raw or LaTeX-escaped, in the rendered line.
+fn data_fields(data: &Value, rendered_line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    collect_fields(data, String::new(), rendered_line, &mut fields);
+    fields
+}
+
+fn collect_fields(value: &Value, path: String, rendered_line: &str, fields: &mut Vec<String>) {
+    match value {
+        Value::String(s)
+            if s.trim().len() >= 3
+                && (rendered_line.contains(s.trim())
+                    || rendered_line.contains(&escape_latex_str(s.trim()))) =>
+        {
+            fields.push(path);
+        }
+        Value::Array(items) => {
+            for (index, item) in items.iter().enumerate() {
+                collect_fields(item, format!("{}[{}]", path, index), rendered_line, fields);
+            }
+        }
+        Value::Table(table) => {
+            for (key, item) in table {
+                let path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                collect_fields(item, path, rendered_line, fields);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Where in `source`, the text of the data file parsed as `data`, the value at
+/// `field` such as `experiences[0].highlights[2]` is written. Found by
+/// searching for the value, or for its key when it's empty or not a string,
+/// so it's the first place that text appears.
+pub fn data_span(source: &str, data: &Value, field: &str) -> Option<Range<usize>> {
+    let mut value = Some(data);
+    let mut key = "";
+    for part in field.split('.') {
+        let (name, indices) = part.split_once('[').unwrap_or((part, ""));
+        if !name.is_empty() {
+            key = name;
+            value = value.and_then(|value| value.get(name));
+        }
+        for index in indices.split('[') {
+            if let Ok(index) = index.trim_end_matches(']').parse::<usize>() {
+                value = value.and_then(|value| value.get(index));
+            }
+        }
+    }
+    // A highlight with tags is a table of its text and tags.
+    let text = value
+        .and_then(|value| value.get("text").or(Some(value)))
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|text| !text.is_empty());
+    if let Some(text) = text {
+        let first_line = text.lines().next().unwrap_or(text);
+        if let Some(start) = source.find(first_line) {
+            return Some(start..start + first_line.len());
+        }
+    }
+    let mut offset = 0;
+    for line in source.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        if let Some(rest) = trimmed.strip_prefix(key) {
+            if !key.is_empty() && rest.trim_start().starts_with('=') {
+                let start = offset + line.len() - trimmed.len();
+                return Some(start..start + key.len());
+            }
+        }
+        offset += line.len();
+    }
+    None
+}
@@ -0,0 +1,297 @@
+//! Counts for spotting bloated sections, computed from the data rather than a
+//! rendered template.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::{Author, EmploymentType, Highlight};
+
+/// Words too common to say anything about a resume.
+const STOP_WORDS: &[&str] = &[
+    "a", "about", "across", "after", "all", "an", "and", "are", "as", "at", "be", "by", "for",
+    "from", "has", "have", "in", "into", "is", "it", "its", "of", "on", "or", "our", "over", "per",
+    "that", "the", "their", "this", "to", "up", "was", "were", "which", "while", "with",
+];
+
+#[derive(Debug, Serialize)]
+pub struct Stats {
+    /// Words per non-empty section, in resume order
+    pub sections: Vec<SectionStats>,
+    pub roles: Vec<RoleStats>,
+    /// How many roles were remote
+    pub remote_roles: usize,
+    /// Mean words per experience highlight
+    pub average_bullet_words: f64,
+    /// Flesch–Kincaid grade level of the prose: summary, objective and highlights
+    pub reading_level: Option<f64>,
+    /// The grade level of each piece of prose, hardest first
+    pub readability: Vec<Readability>,
+    /// The most frequent words, most frequent first
+    pub keywords: Vec<(String, usize)>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SectionStats {
+    pub name: &'static str,
+    pub words: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Readability {
+    /// E.g. `summary` or `experiences[0].highlights[2]`
+    pub field: String,
+    pub grade: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RoleStats {
+    pub position: String,
+    pub company: String,
+    pub remote: bool,
+    pub employment_type: Option<EmploymentType>,
+    pub bullets: usize,
+    pub average_bullet_words: f64,
+}
+
+impl Author {
+    /// Computes [`Stats`], listing at most `keywords` keywords.
+    pub fn stats(&self, keywords: usize) -> Stats {
+        let highlights: Vec<&str> = self
+            .experiences
+            .iter()
+            .flat_map(|e| e.highlights.iter().map(Highlight::as_str))
+            .collect();
+        let sections: Vec<(&'static str, Vec<&str>)> = vec![
+            (
+                "summary",
+                self.summary
+                    .iter()
+                    .chain(&self.objective)
+                    .map(String::as_str)
+                    .collect(),
+            ),
+            (
+                "experiences",
+                self.experiences
+                    .iter()
+                    .flat_map(|e| {
+                        [e.position.as_str()]
+                            .into_iter()
+                            .chain(e.highlights.iter().map(Highlight::as_str))
+                            .chain(e.technologies.iter().map(String::as_str))
+                    })
+                    .collect(),
+            ),
+            (
+                "educations",
+                self.educations
+                    .iter()
+                    .flat_map(|e| e.achievements.iter().chain(&e.courses).map(String::as_str))
+                    .collect(),
+            ),
+            (
+                "skills",
+                self.skills
+                    .iter()
+                    .flat_map(|s| {
+                        [s.name.as_str()]
+                            .into_iter()
+                            .chain(s.keywords.iter().map(String::as_str))
+                    })
+                    .collect(),
+            ),
+            (
+                "projects",
+                self.projects
+                    .iter()
+                    .map(|p| p.description.as_str())
+                    .collect(),
+            ),
+            (
+                "publications",
+                self.publications.iter().map(|p| p.title.as_str()).collect(),
+            ),
+            (
+                "patents",
+                self.patents.iter().map(|p| p.title.as_str()).collect(),
+            ),
+            (
+                "teaching",
+                self.teaching
+                    .iter()
+                    .flat_map(|t| std::iter::once(&t.course).chain(&t.description))
+                    .map(String::as_str)
+                    .collect(),
+            ),
+            (
+                "talks",
+                self.talks.iter().map(|t| t.title.as_str()).collect(),
+            ),
+            (
+                "interests",
+                self.interests.iter().map(|i| i.name.as_str()).collect(),
+            ),
+        ];
+
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for (_, texts) in &sections {
+            for word in texts.iter().flat_map(|text| words(text)) {
+                let word = word.to_lowercase();
+                if word.chars().count() > 2 && !STOP_WORDS.contains(&word.as_str()) {
+                    *counts.entry(word).or_default() += 1;
+                }
+            }
+        }
+        let mut frequent: Vec<(String, usize)> =
+            counts.into_iter().filter(|(_, count)| *count > 1).collect();
+        frequent.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        frequent.truncate(keywords);
+
+        let prose = self.prose();
+        let mut readability: Vec<Readability> = prose
+            .iter()
+            .filter_map(|(field, text)| {
+                reading_level(&[text]).map(|grade| Readability {
+                    field: field.clone(),
+                    grade,
+                })
+            })
+            .collect();
+        readability.sort_by(|a, b| b.grade.total_cmp(&a.grade));
+        let prose: Vec<&str> = prose.into_iter().map(|(_, text)| text).collect();
+
+        Stats {
+            sections: sections
+                .iter()
+                .map(|(name, texts)| SectionStats {
+                    name,
+                    words: texts.iter().map(|text| words(text).count()).sum(),
+                })
+                .filter(|section| section.words > 0)
+                .collect(),
+            roles: self
+                .experiences
+                .iter()
+                .map(|e| RoleStats {
+                    position: e.position.clone(),
+                    company: e.company.name.clone(),
+                    remote: e.remote,
+                    employment_type: e.employment_type,
+                    bullets: e.highlights.len(),
+                    average_bullet_words: average_words(e.highlights.iter().map(Highlight::as_str)),
+                })
+                .collect(),
+            remote_roles: self.experiences.iter().filter(|e| e.remote).count(),
+            average_bullet_words: average_words(highlights.iter().copied()),
+            reading_level: reading_level(&prose),
+            readability,
+            keywords: frequent,
+        }
+    }
+}
+
+impl Author {
+    /// The summary, objective and experience highlights keyed by field.
+    pub(crate) fn prose(&self) -> Vec<(String, &str)> {
+        let mut prose: Vec<(String, &str)> = Vec::new();
+        if let Some(summary) = &self.summary {
+            prose.push(("summary".to_string(), summary));
+        }
+        if let Some(objective) = &self.objective {
+            prose.push(("objective".to_string(), objective));
+        }
+        for (i, e) in self.experiences.iter().enumerate() {
+            for (j, highlight) in e.highlights.iter().enumerate() {
+                prose.push((
+                    format!("experiences[{}].highlights[{}]", i, j),
+                    highlight.as_str(),
+                ));
+            }
+        }
+        prose
+    }
+}
+
+/// Words with surrounding punctuation and Markdown markers trimmed.
+fn words(text: &str) -> impl Iterator<Item = &str> {
+    text.split_whitespace()
+        .map(|word| word.trim_matches(|c: char| !c.is_alphanumeric()))
+        .filter(|word| !word.is_empty())
+}
+
+fn average_words<'a>(texts: impl Iterator<Item = &'a str>) -> f64 {
+    let (count, total) = texts.fold((0, 0), |(count, total), text| {
+        (count + 1, total + words(text).count())
+    });
+    if count == 0 {
+        0.0
+    } else {
+        total as f64 / count as f64
+    }
+}
+
+/// The sentences of `text`; each bullet counts as one even without a full stop.
+pub(crate) fn sentences(text: &str) -> impl Iterator<Item = &str> {
+    text.split(['.', '!', '?', '\n'])
+        .map(str::trim)
+        .filter(|sentence| words(sentence).next().is_some())
+}
+
+/// The Flesch–Kincaid grade level of `texts` taken together.
+pub(crate) fn reading_level(texts: &[&str]) -> Option<f64> {
+    let mut sentence_count = 0;
+    let mut word_count = 0;
+    let mut syllable_count = 0;
+    for text in texts {
+        sentence_count += sentences(text).count();
+        for word in words(text) {
+            word_count += 1;
+            syllable_count += syllables(word);
+        }
+    }
+    (word_count > 0 && sentence_count > 0).then(|| {
+        0.39 * word_count as f64 / sentence_count as f64
+            + 11.8 * syllable_count as f64 / word_count as f64
+            - 15.59
+    })
+}
+
+/// Vowel groups, less a silent final `e`; good enough for a grade estimate.
+fn syllables(word: &str) -> usize {
+    let word = word.to_lowercase();
+    let is_vowel = |c: char| "aeiouy".contains(c);
+    let mut count = 0;
+    let mut previous = false;
+    for c in word.chars() {
+        let vowel = is_vowel(c);
+        if vowel && !previous {
+            count += 1;
+        }
+        previous = vowel;
+    }
+    if word.ends_with('e') && !word.ends_with("le") && count > 1 {
+        count -= 1;
+    }
+    count.max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing;
+
+    #[test]
+    fn experience_technologies_count_as_keywords() {
+        let mut author = testing::resume("", &["Ran Kubernetes clusters"]).author;
+        author.experiences[0].technologies =
+            vec!["Kubernetes".to_string(), "Terraform".to_string()];
+        let stats = author.stats(5);
+        assert_eq!(stats.keywords, [("kubernetes".to_string(), 2)]);
+        let experiences = stats
+            .sections
+            .iter()
+            .find(|section| section.name == "experiences")
+            .unwrap();
+        assert_eq!(experiences.words, 6);
+    }
+}
@@ -0,0 +1,29 @@
+//! Data for unit tests.
+
+use crate::Resume;
+
+/// Jane Doe's resume with one experience, at Acme, listing `highlights`, and
+/// `extra` top-level keys such as `summary = "..."`.
+pub(crate) fn resume(extra: &str, highlights: &[&str]) -> Resume {
+    let source = format!(
+        r#"
+        schema_version = 2
+        name = "Jane Doe"
+        description = "Engineer"
+        website = ""
+        {}
+
+        [[experiences]]
+        department = ""
+        position = "Engineer"
+        website = ""
+        start_date = 2020-01-01
+        display = []
+        highlights = {:?}
+        [experiences.company]
+        name = "Acme"
+        "#,
+        extra, highlights
+    );
+    Resume::from_toml(&source).expect("valid data")
+}
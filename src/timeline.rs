@@ -0,0 +1,141 @@
+//! The `timeline()` template function: experiences drawn as TikZ bars along a
+//! time axis, positioned by their real dates.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use tera::{try_get_value, Value};
+
+use crate::render::escape_latex_str;
+
+struct Span {
+    label: String,
+    start: f64,
+    end: f64,
+}
+
+pub fn timeline(args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let experiences = match args.get("experiences") {
+        Some(experiences) => try_get_value!("timeline", "experiences", Vec<Value>, experiences),
+        None => {
+            return Err(tera::Error::msg(
+                "Function `timeline` requires `experiences`",
+            ))
+        }
+    };
+    let width = match args.get("width") {
+        Some(width) => try_get_value!("timeline", "width", f64, width),
+        None => 16.0,
+    };
+    let color = match args.get("color") {
+        Some(color) => try_get_value!("timeline", "color", String, color),
+        None => "black!60".to_string(),
+    };
+
+    let today = today();
+    let mut spans = Vec::new();
+    for experience in &experiences {
+        let date = |key: &str| experience[key].as_str().and_then(fractional_year);
+        let Some(start) = date("start_date") else {
+            continue;
+        };
+        // The whole tenure, from the first role before any promotions.
+        let start = experience["roles"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|role| role["start_date"].as_str().and_then(fractional_year))
+            .fold(start, f64::min);
+        let company = &experience["company"];
+        spans.push(Span {
+            label: company["display_name"]
+                .as_str()
+                .or(company["name"].as_str())
+                .unwrap_or_default()
+                .to_string(),
+            start,
+            end: date("end_date").unwrap_or(today).max(start),
+        });
+    }
+    if spans.is_empty() {
+        return Ok(Value::String(String::new()));
+    }
+    spans.sort_by(|a, b| a.start.total_cmp(&b.start));
+
+    let first = spans[0].start.floor();
+    let last = spans
+        .iter()
+        .map(|s| s.end)
+        .fold(first + 1.0, f64::max)
+        .ceil();
+    let scale = width / (last - first);
+    let x = |year: f64| (year - first) * scale;
+
+    let mut out = String::from("\\begin{tikzpicture}\n");
+    let _ = writeln!(out, "\\draw[thick] (0,0) -- ({:.2},0);", width);
+    let years = (last - first) as u32;
+    let step = match years {
+        0..=12 => 1,
+        13..=30 => 2,
+        _ => 5,
+    };
+    for year in (0..=years).step_by(step) {
+        let _ = writeln!(
+            out,
+            "\\draw ({0:.2},-0.08) -- ({0:.2},0.08) node[below=3pt] {{\\scriptsize {1}}};",
+            x(first + f64::from(year)),
+            first as u32 + year
+        );
+    }
+    // Overlapping roles go in separate lanes above the axis.
+    let mut lanes: Vec<f64> = Vec::new();
+    for span in &spans {
+        let lane = match lanes.iter().position(|end| *end <= span.start) {
+            Some(lane) => {
+                lanes[lane] = span.end;
+                lane
+            }
+            None => {
+                lanes.push(span.end);
+                lanes.len() - 1
+            }
+        };
+        let y = 0.3 + lane as f64 * 0.6;
+        let _ = writeln!(
+            out,
+            "\\fill[{}] ({:.2},{:.2}) rectangle ({:.2},{:.2});",
+            color,
+            x(span.start),
+            y,
+            x(span.end),
+            y + 0.2
+        );
+        let _ = writeln!(
+            out,
+            "\\node[anchor=south west,inner sep=1pt] at ({:.2},{:.2}) {{\\scriptsize {}}};",
+            x(span.start),
+            y + 0.2,
+            escape_latex_str(&span.label)
+        );
+    }
+    out.push_str("\\end{tikzpicture}");
+    Ok(Value::String(out))
+}
+
+/// `2019-07-01` as `2019.5`; the time of day in a full datetime is ignored.
+fn fractional_year(date: &str) -> Option<f64> {
+    let mut parts = date.get(..10)?.splitn(3, '-');
+    let year: f64 = parts.next()?.parse().ok()?;
+    let month: f64 = parts.next()?.parse().ok()?;
+    let day: f64 = parts.next()?.parse().ok()?;
+    Some(year + (month - 1.0) / 12.0 + (day - 1.0) / 365.0)
+}
+
+/// Today as a fractional year, for roles without an end date.
+fn today() -> f64 {
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0.0, |d| d.as_secs_f64() / 86_400.0);
+    1970.0 + days / 365.2425
+}
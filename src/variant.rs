@@ -0,0 +1,318 @@
+//! Tailored builds from one data file: a [`Variant`] trims the resume before
+//! it reaches the templates.
+
+use std::collections::BTreeSet;
+
+use crate::skills::known_technologies;
+use crate::{Author, Error, Highlight, Result, Skill, Variant};
+
+/// How many matched keywords the proposed summary names.
+const SUMMARY_KEYWORDS: usize = 5;
+
+impl Author {
+    /// The variant declared as `variants.<name>`, or an empty one for a name
+    /// only entries' `variants` use.
+    pub fn variant(&self, name: &str) -> Result<Variant> {
+        if let Some(variant) = self.variants.get(name) {
+            return Ok(variant.clone());
+        }
+        if self
+            .entry_variants()
+            .any(|variants| in_variant(variants, name))
+        {
+            return Ok(Variant::default());
+        }
+        Err(Error::UnknownVariant {
+            name: name.to_string(),
+            declared: self
+                .variants
+                .keys()
+                .chain(self.entry_variants().flatten())
+                .cloned()
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .collect(),
+        })
+    }
+
+    /// Drops the experiences, highlights, projects and skills whose `variants`
+    /// leave out `name`.
+    pub fn select_variant(&mut self, name: &str) {
+        let kept = |variants: &[String]| variants.is_empty() || in_variant(variants, name);
+        self.experiences
+            .retain(|experience| kept(&experience.variants));
+        for experience in &mut self.experiences {
+            experience
+                .highlights
+                .retain(|highlight| kept(&highlight.variants));
+        }
+        self.projects.retain(|project| kept(&project.variants));
+        self.skills.retain(|skill| kept(&skill.variants));
+    }
+
+    fn entry_variants(&self) -> impl Iterator<Item = &Vec<String>> {
+        self.experiences
+            .iter()
+            .flat_map(|e| {
+                std::iter::once(&e.variants).chain(e.highlights.iter().map(|h| &h.variants))
+            })
+            .chain(self.projects.iter().map(|p| &p.variants))
+            .chain(self.skills.iter().map(|s| &s.variants))
+    }
+
+    /// Drops the highlights `variant` leaves out.
+    pub fn tailor(&mut self, variant: &Variant) {
+        for experience in &mut self.experiences {
+            if !variant.tags.is_empty() {
+                experience.highlights.retain(|highlight| {
+                    highlight.tags.is_empty() || has_tag(highlight, &variant.tags)
+                });
+            }
+            if let Some(max) = variant.max_highlights {
+                experience.highlights = trim(&experience.highlights, max, variant);
+            }
+        }
+        if !variant.skills.is_empty() {
+            self.skills.sort_by_key(|skill| {
+                variant
+                    .skills
+                    .iter()
+                    .position(|name| name.eq_ignore_ascii_case(&skill.name))
+                    .unwrap_or(usize::MAX)
+            });
+        }
+        if let Some(summary) = &variant.summary {
+            self.summary = Some(summary.clone());
+        }
+    }
+
+    /// A variant for the job `posting` describes, as a starting point to edit:
+    /// the skills, technologies, highlight tags and well-known tools it
+    /// mentions become keywords, most mentioned first; skills it mentions move
+    /// to the front; and the summary names the top keywords.
+    pub fn propose_variant(&self, posting: &str) -> Variant {
+        let posting = words(posting);
+        let mentions = |term: &str| {
+            let term = words(term);
+            if term.trim().is_empty() {
+                0
+            } else {
+                posting.matches(term.as_str()).count()
+            }
+        };
+
+        let tags: Vec<&str> = self
+            .experiences
+            .iter()
+            .flat_map(|e| &e.highlights)
+            .flat_map(|highlight| &highlight.tags)
+            .map(String::as_str)
+            .collect();
+        let mut candidates: Vec<&str> = self
+            .skills
+            .iter()
+            .flat_map(|skill| std::iter::once(&skill.name).chain(&skill.keywords))
+            .map(String::as_str)
+            .chain(
+                self.experiences
+                    .iter()
+                    .flat_map(|e| &e.technologies)
+                    .map(String::as_str),
+            )
+            .chain(tags.iter().copied())
+            .collect();
+        for technology in known_technologies() {
+            candidates.push(technology);
+        }
+        let mut keywords: Vec<(&str, usize, usize)> = Vec::new();
+        for candidate in candidates {
+            let count = mentions(candidate);
+            if count == 0
+                || keywords
+                    .iter()
+                    .any(|(keyword, _, _)| words(keyword) == words(candidate))
+            {
+                continue;
+            }
+            let first = posting.find(words(candidate).as_str()).unwrap_or_default();
+            keywords.push((candidate, count, first));
+        }
+        keywords.sort_by(|a, b| b.1.cmp(&a.1).then(a.2.cmp(&b.2)));
+        let keywords: Vec<String> = keywords
+            .into_iter()
+            .map(|(keyword, _, _)| keyword.to_string())
+            .collect();
+
+        let mut chosen_tags: Vec<String> = Vec::new();
+        for tag in tags {
+            if mentions(tag) > 0 && !chosen_tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+                chosen_tags.push(tag.to_string());
+            }
+        }
+
+        let score = |skill: &Skill| {
+            mentions(&skill.name)
+                + skill
+                    .keywords
+                    .iter()
+                    .map(|keyword| mentions(keyword))
+                    .sum::<usize>()
+        };
+        let mut skills: Vec<&Skill> = self.skills.iter().filter(|s| score(s) > 0).collect();
+        skills.sort_by_key(|skill| std::cmp::Reverse(score(skill)));
+
+        let summary = (!keywords.is_empty()).then(|| {
+            let top = &keywords[..keywords.len().min(SUMMARY_KEYWORDS)];
+            let list = match top {
+                [only] => only.clone(),
+                [rest @ .., last] => format!("{} and {}", rest.join(", "), last),
+                [] => unreachable!("keywords isn't empty"),
+            };
+            let sentence = format!("Hands-on experience with {}.", list);
+            match &self.summary {
+                Some(summary) => format!("{}\n\n{}", summary.trim_end(), sentence),
+                None => sentence,
+            }
+        });
+
+        Variant {
+            max_highlights: None,
+            keywords,
+            tags: chosen_tags,
+            skills: skills.into_iter().map(|skill| skill.name.clone()).collect(),
+            summary,
+        }
+    }
+}
+
+fn in_variant(variants: &[String], name: &str) -> bool {
+    variants.iter().any(|variant| variant == name)
+}
+
+fn has_tag(highlight: &Highlight, tags: &[String]) -> bool {
+    highlight
+        .tags
+        .iter()
+        .any(|tag| tags.iter().any(|wanted| wanted.eq_ignore_ascii_case(tag)))
+}
+
+/// The first `max` highlights, preferring those that match a keyword, in
+/// their original order.
+fn trim(highlights: &[Highlight], max: usize, variant: &Variant) -> Vec<Highlight> {
+    let keywords: Vec<String> = variant.keywords.iter().map(|k| words(k)).collect();
+    let matches = |highlight: &Highlight| {
+        let text = words(highlight);
+        has_tag(highlight, &variant.keywords)
+            || keywords
+                .iter()
+                .any(|keyword| !keyword.trim().is_empty() && text.contains(keyword.as_str()))
+    };
+    let mut keep: Vec<usize> = (0..highlights.len())
+        .filter(|&i| matches(&highlights[i]))
+        .chain((0..highlights.len()).filter(|&i| !matches(&highlights[i])))
+        .take(max)
+        .collect();
+    keep.sort_unstable();
+    keep.into_iter().map(|i| highlights[i].clone()).collect()
+}
+
+/// Lowercased words padded with spaces, so ` rust ` matches the word but not
+/// `trust`.
+pub(crate) fn words(text: &str) -> String {
+    let mut out = String::from(" ");
+    for word in text
+        .split(|c: char| !(c.is_alphanumeric() || matches!(c, '+' | '#')))
+        .filter(|word| !word.is_empty())
+    {
+        out.push_str(&word.to_lowercase());
+        out.push(' ');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+
+    fn highlight(text: &str, tags: &[&str]) -> Highlight {
+        Highlight {
+            text: text.to_string(),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            variants: Vec::new(),
+        }
+    }
+
+    fn texts(highlights: &[Highlight]) -> Vec<&str> {
+        highlights.iter().map(Highlight::as_str).collect()
+    }
+
+    fn keywords(keywords: &[&str]) -> Variant {
+        Variant {
+            keywords: keywords.iter().map(|k| k.to_string()).collect(),
+            ..Variant::default()
+        }
+    }
+
+    #[test]
+    fn trimming_prefers_keyword_matches_in_their_original_order() {
+        let highlights = [
+            highlight("Ran the hiring loop", &[]),
+            highlight("Rewrote the scheduler in Rust", &[]),
+            highlight("Set up on-call", &[]),
+            highlight("Built a Distributed Systems course", &[]),
+        ];
+        let variant = keywords(&["rust", "distributed systems"]);
+        assert_eq!(
+            texts(&trim(&highlights, 2, &variant)),
+            [
+                "Rewrote the scheduler in Rust",
+                "Built a Distributed Systems course"
+            ]
+        );
+        assert_eq!(
+            texts(&trim(&highlights, 3, &variant)),
+            [
+                "Ran the hiring loop",
+                "Rewrote the scheduler in Rust",
+                "Built a Distributed Systems course"
+            ]
+        );
+    }
+
+    #[test]
+    fn trimming_matches_tags_and_whole_words_only() {
+        let highlights = [
+            highlight("Earned the team's trust", &[]),
+            highlight("Shipped the billing service", &["Rust"]),
+        ];
+        assert_eq!(
+            texts(&trim(&highlights, 1, &keywords(&["rust"]))),
+            ["Shipped the billing service"]
+        );
+    }
+
+    #[test]
+    fn trimming_keeps_everything_under_the_limit() {
+        let highlights = [highlight("One", &[]), highlight("Two", &[])];
+        assert_eq!(texts(&trim(&highlights, 5, &keywords(&[]))), ["One", "Two"]);
+        assert!(trim(&highlights, 0, &keywords(&[])).is_empty());
+    }
+
+    #[test]
+    fn tailoring_trims_each_experience() {
+        let mut author = testing::resume(
+            "",
+            &["Wrote docs", "Ported the CLI to Rust", "Ran standups"],
+        )
+        .author;
+        author.tailor(&Variant {
+            max_highlights: Some(1),
+            ..keywords(&["rust"])
+        });
+        assert_eq!(
+            texts(&author.experiences[0].highlights),
+            ["Ported the CLI to Rust"]
+        );
+    }
+}
@@ -0,0 +1,27 @@
+//! Tectonic's engines can't be interrupted, so a compile that runs away is
+//! ended by ending the process.
+
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::Duration;
+
+/// How long past a compile's own timeout to wait for it to notice before
+/// giving up on it.
+pub const GRACE: Duration = Duration::from_secs(2);
+
+/// Disarms the watchdog when dropped.
+pub struct Watchdog {
+    _disarm: Sender<()>,
+}
+
+/// Calls `expired` on another thread unless the returned [`Watchdog`] is
+/// dropped within `timeout`. `expired` is expected to exit.
+pub fn watch(timeout: Duration, expired: impl FnOnce() + Send + 'static) -> Watchdog {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        if let Err(RecvTimeoutError::Timeout) = receiver.recv_timeout(timeout) {
+            expired();
+        }
+    });
+    Watchdog { _disarm: sender }
+}